@@ -0,0 +1,404 @@
+use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+use crate::common::json_fields::{api_method_error, take, take_date, take_f64, take_string};
+use crate::errors::TradernetError;
+use crate::market_time::API_TIMEZONE;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Kind of a corporate action.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CorporateActionKind {
+    /// Forward or reverse split.
+    Split,
+    /// Cash dividend.
+    Dividend,
+    /// Any other action type as reported by the API.
+    Other(String),
+}
+
+/// Corporate action of a symbol, built by hand or parsed from `getPlannedCorpActions`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorporateAction {
+    pub symbol: String,
+    pub kind: CorporateActionKind,
+    /// First date the instrument trades without the split or dividend.
+    pub ex_date: NaiveDate,
+    /// New shares per old share (`4.0` for a 4:1 split, `0.1` for a 1:10 reverse split).
+    pub split_ratio: Option<f64>,
+    /// Dividend per share.
+    pub dividend: Option<f64>,
+    pub currency: Option<String>,
+}
+
+impl CorporateAction {
+    /// Creates a split with `ratio` new shares per old share.
+    pub fn split(symbol: impl Into<String>, ex_date: NaiveDate, ratio: f64) -> Self {
+        Self {
+            symbol: symbol.into(),
+            kind: CorporateActionKind::Split,
+            ex_date,
+            split_ratio: Some(ratio),
+            dividend: None,
+            currency: None,
+        }
+    }
+
+    /// Creates a cash dividend of `amount` per share.
+    pub fn dividend(symbol: impl Into<String>, ex_date: NaiveDate, amount: f64) -> Self {
+        Self {
+            symbol: symbol.into(),
+            kind: CorporateActionKind::Dividend,
+            ex_date,
+            split_ratio: None,
+            dividend: Some(amount),
+            currency: None,
+        }
+    }
+}
+
+/// Which corporate actions [`SymbolCandles::adjust`] applies.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum AdjustmentMode {
+    /// Leave prices and volumes unadjusted.
+    Raw,
+    /// Adjust for splits only.
+    Splits,
+    /// Adjust for splits and cash dividends.
+    #[default]
+    SplitsAndDividends,
+}
+
+/// Factor applied to every bar dated before `ex_date`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdjustmentFactor {
+    pub ex_date: NaiveDate,
+    pub kind: CorporateActionKind,
+    /// Multiplier for open, high, low and close.
+    pub price_factor: f64,
+    /// Multiplier for volume.
+    pub volume_factor: f64,
+}
+
+/// Adjusted candle series together with the factors used to produce it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AdjustedCandles {
+    pub candles: SymbolCandles,
+    /// Applied factors sorted by ex-date.
+    pub factors: Vec<AdjustmentFactor>,
+}
+
+impl AdjustedCandles {
+    /// Returns the cumulative price multiplier applied to bars dated `date`.
+    pub fn price_factor_at(&self, date: NaiveDate) -> f64 {
+        self.factors
+            .iter()
+            .filter(|factor| factor.ex_date > date)
+            .map(|factor| factor.price_factor)
+            .product()
+    }
+}
+
+impl SymbolCandles {
+    /// Returns a series adjusted for the symbol's splits and dividends.
+    ///
+    /// Bars dated before an ex-date are back-adjusted, so the latest bars keep their
+    /// traded prices. Splits divide prices and multiply volumes by the split ratio.
+    /// Dividends multiply prices by `1 - dividend / close`, where `close` is the last
+    /// close before the ex-date. Daily bars are dated in [`API_TIMEZONE`], intraday
+    /// bars in the exchange time zone. Actions for other symbols are ignored.
+    pub fn adjust(
+        &self,
+        timeframe: Timeframe,
+        actions: &[CorporateAction],
+        mode: AdjustmentMode,
+    ) -> AdjustedCandles {
+        let zone = if timeframe == Timeframe::Day1 {
+            API_TIMEZONE
+        } else {
+            self.exchange_timezone()
+        };
+        let dates = self
+            .items
+            .iter()
+            .map(|item| bar_date(item, zone))
+            .collect::<Vec<_>>();
+
+        let mut factors = actions
+            .iter()
+            .filter(|action| action.symbol.eq_ignore_ascii_case(&self.symbol))
+            .filter_map(|action| self.factor_for(action, &dates, mode))
+            .collect::<Vec<_>>();
+        factors.sort_by_key(|factor| factor.ex_date);
+
+        let items = self
+            .items
+            .iter()
+            .zip(&dates)
+            .map(|(item, date)| {
+                let (price, volume) = factors
+                    .iter()
+                    .filter(|factor| date.is_some_and(|date| factor.ex_date > date))
+                    .fold((1.0, 1.0), |(price, volume), factor| {
+                        (price * factor.price_factor, volume * factor.volume_factor)
+                    });
+                CandlePoint {
+                    ts: item.ts,
+                    open: item.open * price,
+                    high: item.high * price,
+                    low: item.low * price,
+                    close: item.close * price,
+                    volume: (item.volume as f64 * volume).round() as i64,
+                }
+            })
+            .collect();
+
+        AdjustedCandles {
+            candles: SymbolCandles {
+                symbol: self.symbol.clone(),
+                items,
+            },
+            factors,
+        }
+    }
+
+    fn factor_for(
+        &self,
+        action: &CorporateAction,
+        dates: &[Option<NaiveDate>],
+        mode: AdjustmentMode,
+    ) -> Option<AdjustmentFactor> {
+        match (&action.kind, mode) {
+            (CorporateActionKind::Split, AdjustmentMode::Splits)
+            | (CorporateActionKind::Split, AdjustmentMode::SplitsAndDividends) => {
+                let ratio = action.split_ratio.filter(|ratio| *ratio > 0.0)?;
+                Some(AdjustmentFactor {
+                    ex_date: action.ex_date,
+                    kind: CorporateActionKind::Split,
+                    price_factor: 1.0 / ratio,
+                    volume_factor: ratio,
+                })
+            }
+            (CorporateActionKind::Dividend, AdjustmentMode::SplitsAndDividends) => {
+                let amount = action.dividend.filter(|amount| *amount > 0.0)?;
+                let close = self
+                    .items
+                    .iter()
+                    .zip(dates)
+                    .filter(|(_, date)| date.is_some_and(|date| date < action.ex_date))
+                    .map(|(item, _)| item.close)
+                    .next_back()?;
+                if close <= amount {
+                    log::warn!(
+                        "dividend {amount} of {} on {} is not below the previous close {close}, skipped",
+                        self.symbol,
+                        action.ex_date
+                    );
+                    return None;
+                }
+                Some(AdjustmentFactor {
+                    ex_date: action.ex_date,
+                    kind: CorporateActionKind::Dividend,
+                    price_factor: 1.0 - amount / close,
+                    volume_factor: 1.0,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses raw `getPlannedCorpActions` JSON into corporate actions and surfaces
+/// method-level API errors.
+///
+/// Rows are read from a top-level array or from `result`, `data` or `actions`.
+/// Rows without a symbol or a parseable ex-date are skipped with a warning.
+pub fn parse_corporate_actions_response(
+    response: Value,
+) -> Result<Vec<CorporateAction>, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let rows = match response {
+        Value::Array(rows) => rows,
+        Value::Object(mut map) => ["result", "data", "actions"]
+            .into_iter()
+            .find_map(|key| match map.remove(key) {
+                Some(Value::Array(rows)) => Some(rows),
+                _ => None,
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    Ok(rows
+        .into_iter()
+        .filter_map(parse_corporate_action)
+        .collect())
+}
+
+fn parse_corporate_action(row: Value) -> Option<CorporateAction> {
+    let Value::Object(mut row) = row else {
+        log::warn!("corporate action row expected object, got: {row}");
+        return None;
+    };
+    let Some(symbol) = take_string(&mut row, &["ticker", "symbol", "instr_name", "i"]) else {
+        log::warn!("corporate action without symbol skipped: {row:?}");
+        return None;
+    };
+    let Some(ex_date) = take_date(&mut row, &["ex_date", "exDate", "date"]) else {
+        log::warn!("corporate action of {symbol} without ex-date skipped: {row:?}");
+        return None;
+    };
+
+    let type_name = take_string(&mut row, &["type", "action_type", "corporate_action_type"])
+        .unwrap_or_default();
+    let lower = type_name.to_lowercase();
+    let kind = if lower.contains("split") {
+        CorporateActionKind::Split
+    } else if lower.contains("dividend") {
+        CorporateActionKind::Dividend
+    } else {
+        CorporateActionKind::Other(type_name)
+    };
+
+    Some(CorporateAction {
+        symbol,
+        kind,
+        ex_date,
+        split_ratio: take(&mut row, &["ratio", "split_ratio", "factor"]).and_then(parse_ratio),
+        dividend: take_f64(&mut row, &["amount_per_one", "dividend_amount", "amount"]),
+        currency: take_string(&mut row, &["currency", "curr"]),
+    })
+}
+
+/// Parses `4`, `"4"`, `"4:1"` or `"4/1"` as new shares per old share.
+fn parse_ratio(value: Value) -> Option<f64> {
+    match value {
+        Value::Number(value) => value.as_f64(),
+        Value::String(text) => match text.split_once([':', '/']) {
+            Some((new, old)) => {
+                let new = new.trim().parse::<f64>().ok()?;
+                let old = old.trim().parse::<f64>().ok()?;
+                (old > 0.0).then(|| new / old)
+            }
+            None => text.trim().parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn bar_date(item: &CandlePoint, zone: Tz) -> Option<NaiveDate> {
+    DateTime::from_timestamp(item.ts, 0).map(|utc| utc.with_timezone(&zone).date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_corporate_actions_response;
+    use super::{AdjustmentMode, CorporateAction, CorporateActionKind};
+    use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    // Midnight Moscow time on 2024-06-07, 2024-06-10 and 2024-06-11.
+    const FRIDAY: i64 = 1_717_707_600;
+    const MONDAY: i64 = 1_717_966_800;
+    const TUESDAY: i64 = 1_718_053_200;
+
+    fn bar(ts: i64, close: f64, volume: i64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+        }
+    }
+
+    fn series() -> SymbolCandles {
+        SymbolCandles {
+            symbol: "NVDA.US".to_string(),
+            items: vec![
+                bar(FRIDAY, 1200.0, 100),
+                bar(MONDAY, 120.0, 1000),
+                bar(TUESDAY, 121.0, 900),
+            ],
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).expect("valid date")
+    }
+
+    #[test]
+    fn split_back_adjusts_prices_and_volumes() {
+        let actions = [
+            CorporateAction::split("NVDA.US", date(10), 10.0),
+            CorporateAction::split("AAPL.US", date(10), 4.0),
+        ];
+
+        let adjusted = series().adjust(Timeframe::Day1, &actions, AdjustmentMode::Splits);
+
+        assert_eq!(adjusted.factors.len(), 1);
+        assert_eq!(adjusted.factors[0].price_factor, 0.1);
+        assert_eq!(adjusted.candles.items[0].close, 120.0);
+        assert_eq!(adjusted.candles.items[0].volume, 1000);
+        assert_eq!(adjusted.candles.items[1], bar(MONDAY, 120.0, 1000));
+        assert_eq!(adjusted.price_factor_at(date(7)), 0.1);
+        assert_eq!(adjusted.price_factor_at(date(10)), 1.0);
+    }
+
+    #[test]
+    fn dividends_use_previous_close_and_respect_mode() {
+        let actions = [CorporateAction::dividend("NVDA.US", date(11), 1.2)];
+
+        let adjusted = series().adjust(
+            Timeframe::Day1,
+            &actions,
+            AdjustmentMode::SplitsAndDividends,
+        );
+        let factor = 1.0 - 1.2 / 120.0;
+        assert_eq!(adjusted.factors[0].kind, CorporateActionKind::Dividend);
+        assert_eq!(adjusted.candles.items[1].close, 120.0 * factor);
+        assert_eq!(adjusted.candles.items[1].volume, 1000);
+        assert_eq!(adjusted.candles.items[2].close, 121.0);
+
+        let splits_only = series().adjust(Timeframe::Day1, &actions, AdjustmentMode::Splits);
+        assert!(splits_only.factors.is_empty());
+        assert_eq!(splits_only.candles, series());
+
+        let raw = series().adjust(Timeframe::Day1, &actions, AdjustmentMode::Raw);
+        assert_eq!(raw.candles, series());
+    }
+
+    #[test]
+    fn parses_planned_corporate_actions() {
+        let actions = parse_corporate_actions_response(json!([
+            {"ticker": "NVDA.US", "type": "Stock split", "ex_date": "2024-06-10", "ratio": "10:1"},
+            {"ticker": "AAPL.US", "type": "dividend", "ex_date": "2024-05-10 00:00:00",
+             "amount": "0.25", "currency": "USD"},
+            {"ticker": "TSLA.US", "type": "meeting"},
+            {"type": "dividend", "ex_date": "2024-05-10"}
+        ]))
+        .expect("actions must parse");
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].kind, CorporateActionKind::Split);
+        assert_eq!(actions[0].split_ratio, Some(10.0));
+        assert_eq!(actions[1].dividend, Some(0.25));
+        assert_eq!(
+            actions[1].ex_date,
+            NaiveDate::from_ymd_opt(2024, 5, 10).expect("valid date")
+        );
+
+        let error = parse_corporate_actions_response(json!({"errMsg": "denied", "code": 7}))
+            .expect_err("API errors must surface");
+        assert!(matches!(
+            error,
+            crate::errors::TradernetError::ApiMethodError { code: 7, .. }
+        ));
+    }
+}
@@ -1,16 +1,40 @@
-use crate::candles::{CandlesResponse, SymbolCandles, parse_candles_response};
+use crate::adjustments::{CorporateAction, parse_corporate_actions_response};
+use crate::broker_report::{BrokerReport, parse_broker_report_response};
+use crate::candle_cache::{CachePlan, CandleCache};
+use crate::candles::{
+    CandlesBatch, CandlesResponse, SymbolCandles, Timeframe, parse_candles_response,
+};
 use crate::common::client_helpers;
 use crate::core::{AsyncCore, Core};
+use crate::cps::{
+    CpsFile, CpsHistory, CpsHistoryFilters, CpsHistoryPager, CpsRequest, CpsStatus,
+    parse_cps_files_response, parse_cps_history_response,
+};
 use crate::errors::TradernetError;
-use crate::trades_history::{TradesHistoryResponse, parse_trades_history_response};
-use crate::user_data::UserDataResponse;
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::market_time::to_api_time;
+use crate::quote_cache::parse_quotes_response;
+use crate::security_info::{
+    SecurityInfo, StockData, parse_security_info_response, parse_stock_data_response,
+};
+use crate::symbols::option_chain::{OptionChain, parse_option_chain_response};
+use crate::trades_history::{
+    TradeRow, TradesHistoryFilters, TradesHistoryPager, TradesHistoryResponse,
+    parse_trades_history_response,
+};
+use crate::user_data::{Quote, UserDataResponse};
+use async_stream::stream;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use futures_util::stream::{self, BoxStream, StreamExt};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
+/// Maximum number of concurrent `getHloc` requests issued by range helpers.
+const CANDLES_RANGE_CONCURRENCY: usize = 4;
+
 /// Asynchronous REST client for the Tradernet API.
 pub struct AsyncTradernet {
     core: AsyncCore,
+    candle_cache: Option<CandleCache>,
 }
 
 impl AsyncTradernet {
@@ -19,6 +43,7 @@ impl AsyncTradernet {
     pub fn new(public: Option<String>, private: Option<String>) -> Result<Self, TradernetError> {
         Ok(Self {
             core: AsyncCore::new(public, private)?,
+            candle_cache: None,
         })
     }
 
@@ -27,9 +52,21 @@ impl AsyncTradernet {
     pub fn from_config(path: impl AsRef<std::path::Path>) -> Result<Self, TradernetError> {
         Ok(Self {
             core: AsyncCore::from_config(path)?,
+            candle_cache: None,
         })
     }
 
+    /// Attaches a persistent candle cache consulted by candle range downloads.
+    pub fn with_candle_cache(mut self, cache: CandleCache) -> Self {
+        self.candle_cache = Some(cache);
+        self
+    }
+
+    /// Returns the attached candle cache.
+    pub fn candle_cache(&self) -> Option<&CandleCache> {
+        self.candle_cache.as_ref()
+    }
+
     /// Returns a reference to the underlying [`AsyncCore`].
     pub fn core(&self) -> &AsyncCore {
         &self.core
@@ -210,6 +247,16 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns typed security information for a symbol.
+    pub async fn security_info_typed(
+        &self,
+        symbol: &str,
+        sup: bool,
+    ) -> Result<SecurityInfo, TradernetError> {
+        let response = self.security_info(symbol, sup).await?;
+        parse_security_info_response(response)
+    }
+
     /// Returns options chain for an underlying symbol.
     pub async fn get_options(
         &self,
@@ -227,6 +274,18 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns the option chain for an underlying grouped by expiry and strike.
+    ///
+    /// Narrow it with [`OptionChain::filter`].
+    pub async fn get_option_chain(
+        &self,
+        underlying: &str,
+        exchange: &str,
+    ) -> Result<OptionChain, TradernetError> {
+        let response = self.get_options(underlying, exchange).await?;
+        parse_option_chain_response(underlying, response)
+    }
+
     /// Returns candles for a symbol.
     pub async fn get_candles(
         &self,
@@ -243,6 +302,9 @@ impl AsyncTradernet {
     ///
     /// `count` must be negative: `-1` means no extra candles outside the requested interval,
     /// `-100` means include 100 candles before `date_from`.
+    ///
+    /// Naive `start`/`end` are wall-clock times in [`crate::market_time::API_TIMEZONE`];
+    /// see [`Self::get_candles_range_tz`] for zone-aware input.
     pub async fn get_candles_with_count(
         &self,
         symbol: &str,
@@ -286,6 +348,8 @@ impl AsyncTradernet {
     }
 
     /// Returns normalized candle series for a symbol.
+    ///
+    /// Reads go through the candle cache when one is attached.
     pub async fn get_candles_series(
         &self,
         symbol: &str,
@@ -293,6 +357,10 @@ impl AsyncTradernet {
         end: NaiveDateTime,
         timeframe_seconds: i64,
     ) -> Result<SymbolCandles, TradernetError> {
+        if self.candle_cache.is_some() {
+            let timeframe = Timeframe::try_from(timeframe_seconds)?;
+            return self.get_candles_range(symbol, start, end, timeframe).await;
+        }
         self.get_candles_with_count_series(symbol, start, end, timeframe_seconds, -1)
             .await
     }
@@ -312,6 +380,222 @@ impl AsyncTradernet {
         Ok(response.series_for_symbol(symbol))
     }
 
+    /// Returns a normalized candle series for an arbitrarily long time range.
+    ///
+    /// The range is split into chunks of [`crate::candles::CANDLES_PER_CHUNK`] bars,
+    /// fetched concurrently and merged into one series sorted by timestamp, with
+    /// duplicated boundary bars removed. With a candle cache attached, only ranges
+    /// missing from the cache and the last partial bar are downloaded.
+    pub async fn get_candles_range(
+        &self,
+        symbol: &str,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        let Some(cache) = &self.candle_cache else {
+            return self
+                .fetch_candles_range(symbol, start, end, timeframe)
+                .await;
+        };
+        let owned_symbol = symbol.to_string();
+        let plan = run_cache_io(cache, move |cache| {
+            cache.plan(&owned_symbol, timeframe, start, end)
+        })
+        .await?;
+        let mut fetched = Vec::new();
+        for (missing_start, missing_end) in plan.missing_ranges() {
+            fetched.push(
+                self.fetch_candles_range(symbol, missing_start, missing_end, timeframe)
+                    .await?,
+            );
+        }
+        run_cache_io(cache, move |cache| cache.complete(plan, fetched)).await
+    }
+
+    async fn fetch_candles_range(
+        &self,
+        symbol: &str,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        let chunks = client_helpers::split_candles_range(start, end, timeframe)?;
+        let responses = stream::iter(chunks)
+            .map(|(chunk_start, chunk_end)| {
+                self.get_candles_with_count_series(
+                    symbol,
+                    chunk_start,
+                    chunk_end,
+                    timeframe.seconds(),
+                    -1,
+                )
+            })
+            .buffered(CANDLES_RANGE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut series = SymbolCandles {
+            symbol: symbol.to_string(),
+            items: Vec::new(),
+        };
+        for response in responses {
+            series.merge(response?);
+        }
+        Ok(series)
+    }
+
+    /// Returns a normalized candle series for a range given in any time zone.
+    ///
+    /// `start` and `end` are converted to the API zone before the request; the
+    /// returned timestamps are UTC and can be shown in exchange-local time with
+    /// [`SymbolCandles::zoned`].
+    pub async fn get_candles_range_tz<Z: TimeZone>(
+        &self,
+        symbol: &str,
+        start: DateTime<Z>,
+        end: DateTime<Z>,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        self.get_candles_range(symbol, to_api_time(&start), to_api_time(&end), timeframe)
+            .await
+    }
+
+    /// Downloads candles for several symbols over one range.
+    ///
+    /// Symbols are requested in groups of [`crate::candles::CANDLES_SYMBOLS_PER_BATCH`],
+    /// with up to four groups in flight, and long ranges are split into chunks like
+    /// in [`Self::get_candles_range`]. When the API rejects a group because of one of
+    /// its symbols, the group is retried one symbol at a time so a single bad symbol
+    /// does not fail the others; per-symbol failures are returned in
+    /// [`CandlesBatch::errors`]. Other request errors (transport, authentication, ...)
+    /// fail the whole call. With a candle cache attached, only symbols with missing
+    /// ranges are downloaded, batched by the ranges they miss.
+    pub async fn get_candles_multi<I, S>(
+        &self,
+        symbols: I,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<CandlesBatch, TradernetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let symbols = client_helpers::unique_candle_symbols(symbols)?;
+        let mut plans = match &self.candle_cache {
+            Some(cache) => {
+                let symbols = symbols.clone();
+                run_cache_io(cache, move |cache| {
+                    symbols
+                        .into_iter()
+                        .map(|symbol| {
+                            let plan = cache.plan(&symbol, timeframe, start, end)?;
+                            Ok((symbol, plan))
+                        })
+                        .collect::<Result<HashMap<_, _>, TradernetError>>()
+                })
+                .await?
+            }
+            None => HashMap::new(),
+        };
+        let missing = symbols
+            .iter()
+            .map(|symbol| {
+                let ranges = plans
+                    .get(symbol)
+                    .map_or_else(|| vec![(start, end)], CachePlan::missing_ranges);
+                (symbol.clone(), ranges)
+            })
+            .collect();
+
+        let outcomes = stream::iter(client_helpers::group_candle_requests(missing, timeframe)?)
+            .map(|request| async move {
+                let outcome = self
+                    .fetch_candles_batch(&request.symbols, &request.chunks, timeframe)
+                    .await;
+                (request, outcome)
+            })
+            .buffer_unordered(CANDLES_RANGE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut fetched = HashMap::new();
+        for (request, outcome) in outcomes {
+            match outcome {
+                Ok(mut series) => {
+                    for symbol in request.symbols {
+                        let candles = series.remove(&symbol).into_iter().collect();
+                        fetched.insert(symbol, Ok(candles));
+                    }
+                }
+                Err(error) if client_helpers::is_symbol_error(&error, &request.symbols) => {
+                    log::warn!("candle batch request failed, retrying symbols one by one: {error}");
+                    for symbol in request.symbols {
+                        let candles = async {
+                            let mut candles = Vec::new();
+                            for (range_start, range_end) in &request.ranges {
+                                candles.push(
+                                    self.fetch_candles_range(
+                                        &symbol,
+                                        *range_start,
+                                        *range_end,
+                                        timeframe,
+                                    )
+                                    .await?,
+                                );
+                            }
+                            Ok::<_, TradernetError>(candles)
+                        }
+                        .await;
+                        fetched.insert(symbol, candles);
+                    }
+                }
+                Err(error) => {
+                    log::warn!("candle batch request failed: {error}");
+                    return Err(error);
+                }
+            }
+        }
+
+        let mut result = CandlesBatch::default();
+        for symbol in symbols {
+            match (
+                fetched.remove(&symbol),
+                plans.remove(&symbol),
+                &self.candle_cache,
+            ) {
+                (Some(Err(error)), _, _) => result.insert(symbol, Err(error)),
+                (candles, Some(plan), Some(cache)) => {
+                    let candles = candles.and_then(Result::ok).unwrap_or_default();
+                    let candles =
+                        run_cache_io(cache, move |cache| cache.complete(plan, candles)).await;
+                    result.insert(symbol, candles);
+                }
+                (candles, _, _) => {
+                    result.insert_fetched(symbol, candles.and_then(Result::ok).unwrap_or_default());
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn fetch_candles_batch(
+        &self,
+        symbols: &[String],
+        chunks: &[(NaiveDateTime, NaiveDateTime)],
+        timeframe: Timeframe,
+    ) -> Result<HashMap<String, SymbolCandles>, TradernetError> {
+        let ids = symbols.join(",");
+        let mut series = HashMap::new();
+        for (chunk_start, chunk_end) in chunks {
+            self.get_candles_typed(&ids, *chunk_start, *chunk_end, timeframe.seconds())
+                .await?
+                .merge_into(symbols, &mut series);
+        }
+        Ok(series)
+    }
+
     /// Returns quote data for a list of symbols.
     pub async fn get_quotes<I, S>(&self, symbols: I) -> Result<Value, TradernetError>
     where
@@ -330,6 +614,16 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns typed quotes for a list of symbols.
+    pub async fn get_quotes_typed<I, S>(&self, symbols: I) -> Result<Vec<Quote>, TradernetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let response = self.get_quotes(symbols).await?;
+        parse_quotes_response(response)
+    }
+
     /// Returns trade history.
     #[allow(clippy::too_many_arguments)]
     pub async fn get_trades_history(
@@ -402,6 +696,45 @@ impl AsyncTradernet {
         parse_trades_history_response(response)
     }
 
+    /// Streams all trades in a date range, following the `tradeId` cursor page by page.
+    ///
+    /// Trades are de-duplicated by id and pages are requested no more often than
+    /// [`TradesHistoryFilters::request_interval`]. The stream ends after the first error.
+    pub fn trades_history_iter(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        filters: TradesHistoryFilters,
+    ) -> Result<BoxStream<'_, Result<TradeRow, TradernetError>>, TradernetError> {
+        let mut pager = TradesHistoryPager::new(start, end, filters)?;
+        Ok(Box::pin(stream! {
+            while !pager.is_done() {
+                if let Some(delay) = pager.delay() {
+                    tokio::time::sleep(delay).await;
+                }
+                let page = match pager.next_params() {
+                    Ok(params) => self
+                        .core
+                        .authorized_request("getTradesHistory", Some(params), Some(2))
+                        .await
+                        .and_then(parse_trades_history_response),
+                    Err(error) => Err(error),
+                };
+                match page {
+                    Ok(page) => {
+                        for row in pager.absorb(page) {
+                            yield Ok(row);
+                        }
+                    }
+                    Err(error) => {
+                        pager.finish();
+                        yield Err(error);
+                    }
+                }
+            }
+        }))
+    }
+
     /// Finds a symbol by query and optional exchange.
     pub async fn find_symbol(
         &self,
@@ -603,6 +936,71 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns one typed page of client requests (CPS) history.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_requests_history_typed(
+        &self,
+        doc_id: Option<i64>,
+        exec_id: Option<i64>,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        status: Option<CpsStatus>,
+    ) -> Result<CpsHistory, TradernetError> {
+        let response = self
+            .get_requests_history(
+                doc_id,
+                exec_id,
+                start,
+                end,
+                limit,
+                offset,
+                status.map(CpsStatus::code),
+            )
+            .await?;
+        parse_cps_history_response(response)
+    }
+
+    /// Returns all client requests in a date range, following `limit`/`offset` pages.
+    ///
+    /// Paging stops once a page brings no new request ids.
+    pub async fn get_requests_history_all(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        filters: CpsHistoryFilters,
+    ) -> Result<Vec<CpsRequest>, TradernetError> {
+        let mut pager = CpsHistoryPager::new(filters)?;
+        let mut requests = Vec::new();
+        while let Some(offset) = pager.offset() {
+            let filters = pager.filters();
+            let page = self
+                .get_requests_history_typed(
+                    filters.doc_id,
+                    filters.exec_id,
+                    start,
+                    end,
+                    Some(filters.page_size),
+                    Some(offset),
+                    filters.status,
+                )
+                .await?;
+            requests.extend(pager.absorb(page));
+        }
+        Ok(requests)
+    }
+
+    /// Returns typed files attached to an order; see [`CpsFile::save_to`].
+    pub async fn get_order_files_typed(
+        &self,
+        order_id: Option<i64>,
+        internal_id: Option<i64>,
+    ) -> Result<Vec<CpsFile>, TradernetError> {
+        let response = self.get_order_files(order_id, internal_id).await?;
+        parse_cps_files_response(response)
+    }
+
     /// Returns broker report data.
     pub async fn get_broker_report(
         &self,
@@ -630,6 +1028,20 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Retrieves a typed broker report for a given date range.
+    pub async fn get_broker_report_typed(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        period: NaiveTime,
+        data_block_type: Option<&str>,
+    ) -> Result<BrokerReport, TradernetError> {
+        let response = self
+            .get_broker_report(start, end, period, data_block_type)
+            .await?;
+        parse_broker_report_response(response)
+    }
+
     /// Returns symbol metadata in a specific language.
     pub async fn symbol(&self, symbol: &str, lang: &str) -> Result<Value, TradernetError> {
         let mut params = Map::new();
@@ -640,6 +1052,16 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns typed stock data for a symbol.
+    pub async fn symbol_typed(
+        &self,
+        symbol: &str,
+        lang: &str,
+    ) -> Result<StockData, TradernetError> {
+        let response = self.symbol(symbol, lang).await?;
+        parse_stock_data_response(response)
+    }
+
     /// Returns symbols list for a given exchange.
     pub async fn symbols(&self, exchange: Option<&str>) -> Result<Value, TradernetError> {
         let params = exchange.map(|exchange| {
@@ -661,6 +1083,15 @@ impl AsyncTradernet {
             .await
     }
 
+    /// Returns typed planned corporate actions for a reception.
+    pub async fn corporate_actions_typed(
+        &self,
+        reception: i64,
+    ) -> Result<Vec<CorporateAction>, TradernetError> {
+        let response = self.corporate_actions(reception).await?;
+        parse_corporate_actions_response(response)
+    }
+
     /// Places a buy order.
     pub async fn buy(
         &self,
@@ -877,3 +1308,15 @@ impl AsyncTradernet {
         client_helpers::parse_refbooks(&content)
     }
 }
+
+/// Runs candle cache file I/O on the blocking thread pool.
+async fn run_cache_io<T, F>(cache: &CandleCache, work: F) -> Result<T, TradernetError>
+where
+    T: Send + 'static,
+    F: FnOnce(CandleCache) -> Result<T, TradernetError> + Send + 'static,
+{
+    let cache = cache.clone();
+    tokio::task::spawn_blocking(move || work(cache))
+        .await
+        .map_err(std::io::Error::other)?
+}
@@ -0,0 +1,476 @@
+use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+use crate::market_time::API_TIMEZONE;
+use crate::resample::{ResampleTarget, TradingSession};
+use crate::user_data::Quote;
+use crate::ws_types::WsEvent;
+use async_stream::stream;
+use chrono::{DateTime, NaiveDateTime};
+use chrono_tz::Tz;
+use futures_util::stream::BoxStream;
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+/// Bar produced by [`BarBuilder`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BarUpdate {
+    /// Current bar after applying a trade; it may still change.
+    Forming(CandlePoint),
+    /// Bar whose time bucket has ended; it will not change anymore.
+    Closed(CandlePoint),
+}
+
+type CloseCallback = Box<dyn FnMut(&CandlePoint) + Send>;
+
+/// Builds live candles from WebSocket quote ticks.
+///
+/// Trades are read from `ltp` (price), `lts` (size) and `ltt` (time) of
+/// [`WsEvent::Quote`] updates. Bars use the same bucketing as
+/// [`SymbolCandles::resample_with_session`], so with a matching
+/// [`TradingSession`] they line up with `getHloc` history: `ts` is the bucket
+/// start and `volume` the summed trade size.
+///
+/// A quote is treated as a repeat of the previous one when the cumulative
+/// `trades` and `vol` counters it carries did not change; quotes without either
+/// counter are always counted. Ticks older than the forming bar are ignored.
+pub struct BarBuilder {
+    symbol: String,
+    timeframe: Timeframe,
+    session: TradingSession,
+    trade_time_zone: Tz,
+    current: Option<CandlePoint>,
+    last_trade_time: Option<i64>,
+    last_trades: Option<i64>,
+    last_volume: Option<f64>,
+    on_close: Vec<CloseCallback>,
+}
+
+impl BarBuilder {
+    /// Creates a builder for one symbol and timeframe with midnight-UTC days.
+    pub fn new(symbol: impl Into<String>, timeframe: Timeframe) -> Self {
+        Self {
+            symbol: symbol.into(),
+            timeframe,
+            session: TradingSession::default(),
+            trade_time_zone: API_TIMEZONE,
+            current: None,
+            last_trade_time: None,
+            last_trades: None,
+            last_volume: None,
+            on_close: Vec::new(),
+        }
+    }
+
+    /// Sets trading day boundaries used to align bars.
+    pub fn with_session(mut self, session: TradingSession) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Sets the time zone of `ltt` values without explicit offset (default [`API_TIMEZONE`]).
+    pub fn with_trade_time_zone(mut self, zone: Tz) -> Self {
+        self.trade_time_zone = zone;
+        self
+    }
+
+    /// Registers a callback invoked with every closed bar.
+    pub fn on_close(mut self, callback: impl FnMut(&CandlePoint) + Send + 'static) -> Self {
+        self.on_close.push(Box::new(callback));
+        self
+    }
+
+    /// Returns the symbol this builder accepts.
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns the bar currently being formed.
+    pub fn forming(&self) -> Option<&CandlePoint> {
+        self.current.as_ref()
+    }
+
+    /// Continues the last historical bar instead of starting a fresh one.
+    ///
+    /// If the last bar of `history` is in the bucket of the next trades, live
+    /// ticks are merged into it, so history and live bars join without a gap or
+    /// a duplicated bar.
+    pub fn seed_from_history(&mut self, history: &SymbolCandles) {
+        if let Some(last) = history.items.iter().max_by_key(|item| item.ts)
+            && self
+                .current
+                .as_ref()
+                .is_none_or(|current| current.ts <= last.ts)
+        {
+            self.current = Some(last.clone());
+        }
+    }
+
+    /// Applies a WebSocket event. Events other than quotes for this symbol are ignored.
+    pub fn push_event(&mut self, event: &WsEvent) -> Vec<BarUpdate> {
+        match event {
+            WsEvent::Quote(quote) => self.push_quote(quote),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Applies a quote update. Updates without `ltp` carry no trade and are ignored.
+    ///
+    /// Incremental `q` frames omit an unchanged `ltt`, so the last trade time seen
+    /// for the symbol is reused; ticks with no known trade time are skipped.
+    pub fn push_quote(&mut self, quote: &Quote) -> Vec<BarUpdate> {
+        if quote.c.as_deref() != Some(self.symbol.as_str()) {
+            return Vec::new();
+        }
+        let Some(price) = quote.ltp else {
+            return Vec::new();
+        };
+        let ts = match quote.ltt.as_deref() {
+            Some(ltt) => parse_trade_time(ltt, self.trade_time_zone),
+            None => self.last_trade_time,
+        };
+        let Some(ts) = ts else {
+            log::debug!("tick without trade time skipped: {:?}", quote.ltt);
+            return Vec::new();
+        };
+        self.last_trade_time = Some(ts);
+        if self.is_repeat(quote) {
+            return Vec::new();
+        }
+        self.push_trade(ts, price, quote.lts.unwrap_or(0.0))
+    }
+
+    /// Applies a single trade at Unix time `ts`.
+    pub fn push_trade(&mut self, ts: i64, price: f64, size: f64) -> Vec<BarUpdate> {
+        if !price.is_finite() {
+            return Vec::new();
+        }
+        let Some(bucket_ts) = self
+            .session
+            .bucket_timestamp(ts, ResampleTarget::from(self.timeframe))
+        else {
+            log::warn!("trade timestamp out of range, skipped: {ts}");
+            return Vec::new();
+        };
+
+        let volume = size.max(0.0).round() as i64;
+        let mut updates = Vec::new();
+        match self.current.as_mut() {
+            Some(current) if current.ts == bucket_ts => {
+                current.high = current.high.max(price);
+                current.low = current.low.min(price);
+                current.close = price;
+                current.volume = current.volume.saturating_add(volume);
+            }
+            Some(current) if current.ts > bucket_ts => {
+                log::debug!("late trade for closed bar ignored: {ts}");
+                return Vec::new();
+            }
+            _ => {
+                if let Some(closed) = self.current.take() {
+                    updates.push(self.close_bar(closed));
+                }
+                self.current = Some(CandlePoint {
+                    ts: bucket_ts,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume,
+                });
+            }
+        }
+
+        if let Some(current) = &self.current {
+            updates.push(BarUpdate::Forming(current.clone()));
+        }
+        updates
+    }
+
+    /// Closes the forming bar if its bucket ended before `now` (Unix seconds).
+    ///
+    /// Useful on timers, since a bar otherwise closes only when the next trade
+    /// arrives.
+    pub fn close_due(&mut self, now: i64) -> Option<BarUpdate> {
+        let bucket_ts = self
+            .session
+            .bucket_timestamp(now, ResampleTarget::from(self.timeframe))?;
+        if self.current.as_ref()?.ts >= bucket_ts {
+            return None;
+        }
+        let closed = self.current.take()?;
+        Some(self.close_bar(closed))
+    }
+
+    /// Closes and returns the forming bar regardless of time.
+    pub fn flush(&mut self) -> Option<BarUpdate> {
+        let closed = self.current.take()?;
+        Some(self.close_bar(closed))
+    }
+
+    /// Turns a stream of quotes (e.g. [`crate::ws::TradernetWsSession::quote_stream`])
+    /// into a stream of bar updates. The forming bar is flushed when input ends.
+    pub fn into_stream<S>(mut self, quotes: S) -> BoxStream<'static, BarUpdate>
+    where
+        S: Stream<Item = Quote> + Send + 'static,
+    {
+        Box::pin(stream! {
+            let mut quotes = Box::pin(quotes);
+            while let Some(quote) = quotes.next().await {
+                for update in self.push_quote(&quote) {
+                    yield update;
+                }
+            }
+            if let Some(update) = self.flush() {
+                yield update;
+            }
+        })
+    }
+
+    /// Checks the quote's cumulative trade counters against the previous quote and
+    /// remembers them.
+    fn is_repeat(&mut self, quote: &Quote) -> bool {
+        let carries_counters = quote.trades.is_some() || quote.vol.is_some();
+        let repeat = carries_counters
+            && quote
+                .trades
+                .is_none_or(|trades| self.last_trades == Some(trades))
+            && quote
+                .vol
+                .is_none_or(|volume| self.last_volume == Some(volume));
+        self.last_trades = quote.trades.or(self.last_trades);
+        self.last_volume = quote.vol.or(self.last_volume);
+        repeat
+    }
+
+    fn close_bar(&mut self, bar: CandlePoint) -> BarUpdate {
+        for callback in &mut self.on_close {
+            callback(&bar);
+        }
+        BarUpdate::Closed(bar)
+    }
+}
+
+impl From<Timeframe> for ResampleTarget {
+    fn from(timeframe: Timeframe) -> Self {
+        match timeframe {
+            Timeframe::Day1 => ResampleTarget::Daily,
+            other => ResampleTarget::Minutes(other.minutes() as u32),
+        }
+    }
+}
+
+/// Joins historical candles with live bars.
+///
+/// History is authoritative for bars before its last timestamp. For the seam bar
+/// present in both series, the open comes from history, high/low are combined,
+/// the close comes from the live bar and the larger volume is kept. Live bars
+/// after the seam are appended.
+pub fn stitch_candles<I>(history: SymbolCandles, live: I) -> SymbolCandles
+where
+    I: IntoIterator<Item = CandlePoint>,
+{
+    let mut stitched = history;
+    stitched.items.sort_by_key(|item| item.ts);
+    let history_end = stitched.items.last().map(|item| item.ts);
+
+    let mut live = live.into_iter().collect::<Vec<_>>();
+    live.sort_by_key(|item| item.ts);
+    for bar in live {
+        match history_end {
+            Some(end) if bar.ts < end => {}
+            Some(end) if bar.ts == end => {
+                if let Some(seam) = stitched.items.last_mut() {
+                    seam.high = seam.high.max(bar.high);
+                    seam.low = seam.low.min(bar.low);
+                    seam.close = bar.close;
+                    seam.volume = seam.volume.max(bar.volume);
+                }
+            }
+            _ => match stitched.items.last_mut() {
+                Some(last) if last.ts == bar.ts => *last = bar,
+                _ => stitched.items.push(bar),
+            },
+        }
+    }
+    stitched
+}
+
+fn parse_trade_time(value: &str, zone: Tz) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.timestamp());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| naive.and_local_timezone(zone).earliest())
+        .map(|datetime| datetime.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BarBuilder, BarUpdate, stitch_candles};
+    use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+    use crate::user_data::Quote;
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    fn tick(symbol: &str, ltt: &str, ltp: f64, lts: f64, trades: i64) -> Quote {
+        serde_json::from_value(
+            json!({"c": symbol, "ltt": ltt, "ltp": ltp, "lts": lts, "trades": trades}),
+        )
+        .expect("quote payload must parse")
+    }
+
+    fn bar(ts: i64, open: f64, high: f64, low: f64, close: f64, volume: i64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open,
+            high,
+            low,
+            close,
+            volume,
+        }
+    }
+
+    // 2023-11-15 17:30:00 MSK == 14:30:00 UTC
+    const BAR_TS: i64 = 1_700_058_600;
+
+    #[test]
+    fn builds_forming_and_closed_bars_from_ticks() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&closed);
+        let mut builder = BarBuilder::new("AAPL.US", Timeframe::Minute5)
+            .on_close(move |bar| sink.lock().expect("lock").push(bar.clone()));
+
+        builder.push_quote(&tick("AAPL.US", "2023-11-15T17:30:05", 10.0, 5.0, 1));
+        builder.push_quote(&tick("AAPL.US", "2023-11-15T17:31:00", 10.4, 2.0, 2));
+        builder.push_quote(&tick("AAPL.US", "2023-11-15T17:31:00", 10.4, 2.0, 2));
+        builder.push_quote(&tick("TSLA.US", "2023-11-15T17:32:00", 99.0, 1.0, 1));
+        let updates = builder.push_quote(&tick("AAPL.US", "2023-11-15T17:34:59", 9.9, 3.0, 3));
+        assert_eq!(
+            updates,
+            vec![BarUpdate::Forming(bar(BAR_TS, 10.0, 10.4, 9.9, 9.9, 10))]
+        );
+
+        let updates = builder.push_quote(&tick("AAPL.US", "2023-11-15T17:35:01", 10.1, 1.0, 4));
+        assert_eq!(
+            updates,
+            vec![
+                BarUpdate::Closed(bar(BAR_TS, 10.0, 10.4, 9.9, 9.9, 10)),
+                BarUpdate::Forming(bar(BAR_TS + 300, 10.1, 10.1, 10.1, 10.1, 1)),
+            ]
+        );
+        assert!(
+            builder
+                .push_quote(&tick("AAPL.US", "2023-11-15T17:33:00", 50.0, 1.0, 5))
+                .is_empty()
+        );
+        assert_eq!(closed.lock().expect("lock").len(), 1);
+
+        assert_eq!(builder.close_due(BAR_TS + 599), None);
+        assert_eq!(
+            builder.close_due(BAR_TS + 600),
+            Some(BarUpdate::Closed(bar(
+                BAR_TS + 300,
+                10.1,
+                10.1,
+                10.1,
+                10.1,
+                1
+            )))
+        );
+        assert_eq!(closed.lock().expect("lock").len(), 2);
+    }
+
+    #[test]
+    fn repeated_prints_count_and_missing_trade_time_reuses_last() {
+        let mut builder = BarBuilder::new("AAPL.US", Timeframe::Minute5);
+        let delta =
+            |value| serde_json::from_value::<Quote>(value).expect("quote payload must parse");
+
+        assert!(
+            builder
+                .push_quote(&delta(json!({"c": "AAPL.US", "ltp": 10.0, "lts": 1})))
+                .is_empty()
+        );
+        builder.push_quote(&tick("AAPL.US", "2023-11-15T17:30:05", 10.0, 2.0, 1));
+        builder.push_quote(&tick("AAPL.US", "2023-11-15T17:30:05", 10.0, 2.0, 2));
+        let updates = builder.push_quote(&delta(
+            json!({"c": "AAPL.US", "ltp": 10.2, "lts": 3, "trades": 3}),
+        ));
+
+        assert_eq!(
+            updates,
+            vec![BarUpdate::Forming(bar(BAR_TS, 10.0, 10.2, 10.0, 10.2, 7))]
+        );
+    }
+
+    #[test]
+    fn seeded_history_bar_continues_without_duplicate() {
+        let history = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![
+                bar(BAR_TS - 300, 9.0, 9.5, 8.9, 9.4, 100),
+                bar(BAR_TS, 9.4, 9.8, 9.3, 9.6, 40),
+            ],
+        };
+        let mut builder = BarBuilder::new("AAPL.US", Timeframe::Minute5);
+        builder.seed_from_history(&history);
+
+        let updates = builder.push_trade(BAR_TS + 120, 10.0, 5.0);
+        assert_eq!(
+            updates,
+            vec![BarUpdate::Forming(bar(BAR_TS, 9.4, 10.0, 9.3, 10.0, 45))]
+        );
+    }
+
+    #[test]
+    fn stitch_joins_history_and_live_at_the_seam() {
+        let history = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![
+                bar(BAR_TS - 300, 9.0, 9.5, 8.9, 9.4, 100),
+                bar(BAR_TS, 9.4, 9.8, 9.3, 9.6, 40),
+            ],
+        };
+        let live = vec![
+            bar(BAR_TS - 300, 1.0, 1.0, 1.0, 1.0, 1),
+            bar(BAR_TS + 300, 10.1, 10.2, 10.0, 10.2, 7),
+            bar(BAR_TS, 9.5, 10.0, 9.5, 9.9, 12),
+        ];
+
+        let stitched = stitch_candles(history, live);
+
+        assert_eq!(
+            stitched.items,
+            vec![
+                bar(BAR_TS - 300, 9.0, 9.5, 8.9, 9.4, 100),
+                bar(BAR_TS, 9.4, 10.0, 9.3, 9.9, 40),
+                bar(BAR_TS + 300, 10.1, 10.2, 10.0, 10.2, 7),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn into_stream_yields_updates_and_flushes_on_end() {
+        let quotes = futures_util::stream::iter(vec![
+            tick("AAPL.US", "2023-11-15T14:30:00Z", 10.0, 1.0, 1),
+            tick("AAPL.US", "2023-11-15T14:31:00+00:00", 11.0, 1.0, 2),
+        ]);
+        let updates = BarBuilder::new("AAPL.US", Timeframe::Minute1)
+            .into_stream(quotes)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            updates,
+            vec![
+                BarUpdate::Forming(bar(BAR_TS, 10.0, 10.0, 10.0, 10.0, 1)),
+                BarUpdate::Closed(bar(BAR_TS, 10.0, 10.0, 10.0, 10.0, 1)),
+                BarUpdate::Forming(bar(BAR_TS + 60, 11.0, 11.0, 11.0, 11.0, 1)),
+                BarUpdate::Closed(bar(BAR_TS + 60, 11.0, 11.0, 11.0, 11.0, 1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,526 @@
+use crate::common::json_fields::{
+    api_method_error, parse_datetime, take_date, take_datetime, take_decimal, take_i64, take_string,
+};
+use crate::errors::TradernetError;
+use crate::trades_history::TradeSide;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Typed `getBrokerReport` response.
+///
+/// Parsing is intentionally lossy: unparseable values become `None`, unknown row
+/// fields are kept in each row's `extra` map and unknown blocks in [`BrokerReport::extra`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BrokerReport {
+    pub date_start: Option<NaiveDateTime>,
+    pub date_end: Option<NaiveDateTime>,
+    pub cash_flows: Vec<CashFlow>,
+    pub trades: Vec<ReportTrade>,
+    pub commissions: Vec<ReportCommission>,
+    pub positions_at_start: Vec<ReportPosition>,
+    pub positions_at_end: Vec<ReportPosition>,
+    pub corporate_actions: Vec<ReportCorporateAction>,
+    /// Blocks without a typed model, keyed by block name.
+    pub extra: HashMap<String, Value>,
+}
+
+/// Cash movement (`cash_flows` block).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CashFlow {
+    pub date: Option<NaiveDateTime>,
+    /// Movement type, e.g. `dividend`, `commission`, `card`.
+    pub kind: Option<String>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub comment: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+/// Executed trade (`trades` block).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportTrade {
+    pub trade_id: Option<i64>,
+    pub order_id: Option<i64>,
+    pub date: Option<NaiveDateTime>,
+    pub settlement_date: Option<NaiveDate>,
+    pub symbol: Option<String>,
+    pub isin: Option<String>,
+    pub side: Option<TradeSide>,
+    pub price: Option<Decimal>,
+    pub quantity: Option<Decimal>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub profit: Option<Decimal>,
+    pub commission: Option<Decimal>,
+    pub commission_currency: Option<String>,
+    pub market: Option<String>,
+    pub comment: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+/// Commission or fee charge (`commissions` block).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportCommission {
+    pub date: Option<NaiveDateTime>,
+    pub kind: Option<String>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub comment: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+/// Position at the start or end of the report period.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportPosition {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub currency: Option<String>,
+    pub quantity: Option<Decimal>,
+    pub price: Option<Decimal>,
+    pub average_price: Option<Decimal>,
+    pub market_value: Option<Decimal>,
+    pub profit: Option<Decimal>,
+    pub extra: HashMap<String, Value>,
+}
+
+/// Corporate action (`corporate_actions` block), including dividends and their taxes.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportCorporateAction {
+    pub date: Option<NaiveDateTime>,
+    pub ex_date: Option<NaiveDate>,
+    /// Action type, e.g. `dividend`, `split`.
+    pub kind: Option<String>,
+    pub symbol: Option<String>,
+    pub isin: Option<String>,
+    pub currency: Option<String>,
+    /// Gross amount credited or debited.
+    pub amount: Option<Decimal>,
+    pub amount_per_share: Option<Decimal>,
+    /// Quantity held on the ex-date.
+    pub quantity: Option<Decimal>,
+    /// Tax withheld by the broker.
+    pub withholding_tax: Option<Decimal>,
+    pub withholding_tax_currency: Option<String>,
+    /// Tax withheld at source before the payment reached the broker.
+    pub external_tax: Option<Decimal>,
+    pub external_tax_currency: Option<String>,
+    pub comment: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+impl ReportCorporateAction {
+    /// Returns `true` for dividend actions.
+    pub fn is_dividend(&self) -> bool {
+        self.kind
+            .as_deref()
+            .is_some_and(|kind| kind.to_lowercase().contains("dividend"))
+    }
+
+    /// Withheld taxes as `(currency, amount)` pairs with positive amounts.
+    ///
+    /// A tax without its own currency is in the action currency; zero taxes are skipped.
+    pub fn taxes(&self) -> impl Iterator<Item = (Option<&str>, Decimal)> {
+        [
+            (self.withholding_tax, &self.withholding_tax_currency),
+            (self.external_tax, &self.external_tax_currency),
+        ]
+        .into_iter()
+        .filter_map(|(tax, currency)| {
+            let tax = tax.filter(|tax| !tax.is_zero())?;
+            Some((currency.as_deref().or(self.currency.as_deref()), tax.abs()))
+        })
+    }
+}
+
+/// Dividend with withholding taxes, derived from corporate actions.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ReportDividend {
+    pub date: Option<NaiveDateTime>,
+    pub ex_date: Option<NaiveDate>,
+    pub symbol: Option<String>,
+    pub isin: Option<String>,
+    pub currency: Option<String>,
+    pub gross: Decimal,
+    pub per_share: Option<Decimal>,
+    pub quantity: Option<Decimal>,
+    /// Tax withheld in the dividend currency (broker and at source), as a positive amount.
+    pub withholding_tax: Decimal,
+    /// Taxes withheld in other currencies, keyed by currency; not deducted from `net`.
+    pub other_taxes: BTreeMap<String, Decimal>,
+    /// `gross - withholding_tax`.
+    pub net: Decimal,
+}
+
+/// Borrowed view of one report row, yielded by [`BrokerReport::blocks`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportBlock<'a> {
+    CashFlow(&'a CashFlow),
+    Trade(&'a ReportTrade),
+    Commission(&'a ReportCommission),
+    PositionAtStart(&'a ReportPosition),
+    PositionAtEnd(&'a ReportPosition),
+    CorporateAction(&'a ReportCorporateAction),
+    /// Untyped block.
+    Other {
+        name: &'a str,
+        value: &'a Value,
+    },
+}
+
+impl BrokerReport {
+    /// Iterates over all typed rows followed by untyped blocks.
+    pub fn blocks(&self) -> impl Iterator<Item = ReportBlock<'_>> {
+        let mut extra = self.extra.iter().collect::<Vec<_>>();
+        extra.sort_by_key(|(name, _)| name.as_str());
+
+        self.cash_flows
+            .iter()
+            .map(ReportBlock::CashFlow)
+            .chain(self.trades.iter().map(ReportBlock::Trade))
+            .chain(self.commissions.iter().map(ReportBlock::Commission))
+            .chain(
+                self.positions_at_start
+                    .iter()
+                    .map(ReportBlock::PositionAtStart),
+            )
+            .chain(self.positions_at_end.iter().map(ReportBlock::PositionAtEnd))
+            .chain(
+                self.corporate_actions
+                    .iter()
+                    .map(ReportBlock::CorporateAction),
+            )
+            .chain(
+                extra
+                    .into_iter()
+                    .map(|(name, value)| ReportBlock::Other { name, value }),
+            )
+    }
+
+    /// Returns dividends with gross amount, withholding tax and net amount.
+    ///
+    /// Taxes are counted as positive amounts regardless of the sign used in the report.
+    /// Only taxes in the dividend currency are netted; see [`ReportCorporateAction::taxes`].
+    pub fn dividends(&self) -> Vec<ReportDividend> {
+        self.corporate_actions
+            .iter()
+            .filter(|action| action.is_dividend())
+            .map(|action| {
+                let gross = action.amount.unwrap_or_default().abs();
+                let mut withholding_tax = Decimal::ZERO;
+                let mut other_taxes = BTreeMap::new();
+                for (currency, tax) in action.taxes() {
+                    match currency.filter(|currency| Some(*currency) != action.currency.as_deref())
+                    {
+                        Some(currency) => {
+                            *other_taxes.entry(currency.to_string()).or_default() += tax;
+                        }
+                        None => withholding_tax += tax,
+                    }
+                }
+                ReportDividend {
+                    date: action.date,
+                    ex_date: action.ex_date,
+                    symbol: action.symbol.clone(),
+                    isin: action.isin.clone(),
+                    currency: action.currency.clone(),
+                    gross,
+                    per_share: action.amount_per_share,
+                    quantity: action.quantity,
+                    withholding_tax,
+                    other_taxes,
+                    net: gross - withholding_tax,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses raw `getBrokerReport` JSON into [`BrokerReport`] and surfaces method-level
+/// API errors.
+///
+/// The report is read from `report`, `result.report` or the response root. Block rows
+/// may be wrapped in `detailed`; positions are read from `positions_from_ts.ps.pos`.
+pub fn parse_broker_report_response(response: Value) -> Result<BrokerReport, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let mut root = match response {
+        Value::Object(mut map) => match map.remove("report") {
+            Some(Value::Object(report)) => report,
+            _ => match map.remove("result") {
+                Some(Value::Object(mut result)) => match result.remove("report") {
+                    Some(Value::Object(report)) => report,
+                    _ => result,
+                },
+                _ => map,
+            },
+        },
+        _ => Map::new(),
+    };
+
+    let mut report = BrokerReport {
+        date_start: take_string(&mut root, &["date_start"]).and_then(|v| parse_datetime(&v)),
+        date_end: take_string(&mut root, &["date_end"]).and_then(|v| parse_datetime(&v)),
+        ..BrokerReport::default()
+    };
+    if let Some(block) = root.remove("cash_flows") {
+        report.cash_flows = rows(block).into_iter().map(parse_cash_flow).collect();
+    }
+    if let Some(block) = root.remove("trades") {
+        report.trades = rows(block).into_iter().map(parse_trade).collect();
+    }
+    if let Some(block) = root.remove("commissions") {
+        report.commissions = rows(block).into_iter().map(parse_commission).collect();
+    }
+    if let Some(block) = root.remove("account_at_start") {
+        report.positions_at_start = positions(block);
+    }
+    if let Some(block) = root.remove("account_at_end") {
+        report.positions_at_end = positions(block);
+    }
+    if let Some(block) = root.remove("corporate_actions") {
+        report.corporate_actions = rows(block)
+            .into_iter()
+            .map(parse_corporate_action)
+            .collect();
+    }
+    report.extra = root.into_iter().collect();
+    Ok(report)
+}
+
+fn parse_cash_flow(mut row: Map<String, Value>) -> CashFlow {
+    CashFlow {
+        date: take_datetime(&mut row, &["date", "datetime"]),
+        kind: take_string(&mut row, &["type_id", "type"]),
+        amount: take_decimal(&mut row, &["sum", "amount"]),
+        currency: take_string(&mut row, &["currency", "curr"]),
+        comment: take_string(&mut row, &["comment"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn parse_trade(mut row: Map<String, Value>) -> ReportTrade {
+    let side = take_string(&mut row, &["operation", "type"]).and_then(|operation| match operation
+        .to_lowercase()
+        .as_str()
+    {
+        "buy" | "1" => Some(TradeSide::Buy),
+        "sell" | "2" => Some(TradeSide::Sell),
+        _ => None,
+    });
+    ReportTrade {
+        trade_id: take_i64(&mut row, &["trade_id", "id"]),
+        order_id: take_i64(&mut row, &["order_id"]),
+        date: take_datetime(&mut row, &["date"]),
+        settlement_date: take_date(&mut row, &["pay_d", "settlement_date"]),
+        symbol: take_string(&mut row, &["instr_nm", "ticker", "instr"]),
+        isin: take_string(&mut row, &["isin"]),
+        side,
+        price: take_decimal(&mut row, &["p", "price"]),
+        quantity: take_decimal(&mut row, &["q", "quantity"]),
+        amount: take_decimal(&mut row, &["summ", "sum", "v"]),
+        currency: take_string(&mut row, &["curr_c", "currency"]),
+        profit: take_decimal(&mut row, &["profit"]),
+        commission: take_decimal(&mut row, &["commission"]),
+        commission_currency: take_string(&mut row, &["commission_currency"]),
+        market: take_string(&mut row, &["mkt_name", "mkt"]),
+        comment: take_string(&mut row, &["comment"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn parse_commission(mut row: Map<String, Value>) -> ReportCommission {
+    ReportCommission {
+        date: take_datetime(&mut row, &["datetime", "date"]),
+        kind: take_string(&mut row, &["type", "type_id"]),
+        amount: take_decimal(&mut row, &["sum", "amount"]),
+        currency: take_string(&mut row, &["currency", "curr"]),
+        comment: take_string(&mut row, &["comment"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn parse_position(mut row: Map<String, Value>) -> ReportPosition {
+    ReportPosition {
+        symbol: take_string(&mut row, &["i", "ticker", "instr_nm"]),
+        name: take_string(&mut row, &["name"]),
+        currency: take_string(&mut row, &["curr", "currency"]),
+        quantity: take_decimal(&mut row, &["q", "quantity"]),
+        price: take_decimal(&mut row, &["mkt_price", "price"]),
+        average_price: take_decimal(&mut row, &["bal_price_a", "price_a"]),
+        market_value: take_decimal(&mut row, &["market_value"]),
+        profit: take_decimal(&mut row, &["profit_close"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn parse_corporate_action(mut row: Map<String, Value>) -> ReportCorporateAction {
+    ReportCorporateAction {
+        date: take_datetime(&mut row, &["date", "pay_date"]),
+        ex_date: take_date(&mut row, &["ex_date"]),
+        kind: take_string(&mut row, &["type", "type_id"]),
+        symbol: take_string(&mut row, &["ticker", "instr_nm", "i"]),
+        isin: take_string(&mut row, &["isin"]),
+        currency: take_string(&mut row, &["currency", "curr"]),
+        amount: take_decimal(&mut row, &["amount", "sum"]),
+        amount_per_share: take_decimal(&mut row, &["amount_per_one"]),
+        quantity: take_decimal(&mut row, &["q_on_ex_date", "q"]),
+        withholding_tax: take_decimal(&mut row, &["tax_amount"]),
+        withholding_tax_currency: take_string(&mut row, &["tax_currency"]),
+        external_tax: take_decimal(&mut row, &["external_tax"]),
+        external_tax_currency: take_string(&mut row, &["external_tax_currency"]),
+        comment: take_string(&mut row, &["comment"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+/// Flattens a block into row objects, unwrapping `detailed` and skipping non-objects.
+fn rows(block: Value) -> Vec<Map<String, Value>> {
+    match block {
+        Value::Array(items) => items.into_iter().flat_map(rows).collect(),
+        Value::Object(mut map) => match map.remove("detailed") {
+            Some(detailed) => rows(detailed),
+            None if map.is_empty() => Vec::new(),
+            None => vec![map],
+        },
+        Value::Null => Vec::new(),
+        other => {
+            log::warn!("broker report row expected object, got: {other}");
+            Vec::new()
+        }
+    }
+}
+
+fn positions(block: Value) -> Vec<ReportPosition> {
+    let pos = block
+        .get("positions_from_ts")
+        .and_then(|value| value.get("ps"))
+        .and_then(|value| value.get("pos"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    rows(pos).into_iter().map(parse_position).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReportBlock, parse_broker_report_response};
+    use crate::trades_history::TradeSide;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    fn report() -> serde_json::Value {
+        json!({"report": {
+            "date_start": "2025-01-01 23:59:59",
+            "date_end": "2025-12-31 23:59:59",
+            "plainAccountInfoData": {"client_code": "X"},
+            "cash_flows": {"detailed": [
+                {"date": "2025-03-01", "type_id": "card", "sum": "1000.50", "currency": "USD"}
+            ]},
+            "trades": {"detailed": [{
+                "trade_id": "11", "order_id": 22, "date": "2025-03-02 15:00:00",
+                "pay_d": "2025-03-04", "instr_nm": "AAPL.US", "operation": "buy",
+                "p": 180.5, "q": "10", "summ": 1805, "curr_c": "USD",
+                "commission": "1.8", "commission_currency": "USD", "isin": "US0378331005",
+                "mkt_name": "NASDAQ"
+            }, "bad"]},
+            "commissions": {"detailed": [
+                {"datetime": "2025-03-31 00:00:00", "type": "custody", "sum": "-2", "currency": "USD"}
+            ]},
+            "account_at_start": {"positions_from_ts": {"ps": {"pos": []}}},
+            "account_at_end": {"positions_from_ts": {"ps": {"pos": [
+                {"i": "AAPL.US", "curr": "USD", "q": 10, "mkt_price": "200", "bal_price_a": 180.68,
+                 "market_value": 2000, "acc_pos_id": 5}
+            ]}}},
+            "corporate_actions": {"detailed": [{
+                "date": "2025-05-15", "ex_date": "2025-05-12", "type": "dividend",
+                "ticker": "AAPL.US", "currency": "USD", "amount": "2.5",
+                "amount_per_one": "0.25", "q_on_ex_date": 10,
+                "tax_amount": "-0.25", "tax_currency": "USD", "external_tax": "0.125"
+            }]}
+        }})
+    }
+
+    #[test]
+    fn broker_report_parses_main_blocks() {
+        let report = parse_broker_report_response(report()).expect("report must parse");
+
+        assert_eq!(report.cash_flows[0].amount, Some(Decimal::new(100050, 2)));
+        assert_eq!(report.trades.len(), 1);
+        let trade = &report.trades[0];
+        assert_eq!(trade.trade_id, Some(11));
+        assert_eq!(trade.side, Some(TradeSide::Buy));
+        assert_eq!(trade.quantity, Some(Decimal::from(10)));
+        assert_eq!(trade.commission, Some(Decimal::new(18, 1)));
+        assert_eq!(trade.market.as_deref(), Some("NASDAQ"));
+        assert_eq!(report.commissions[0].kind.as_deref(), Some("custody"));
+        assert!(report.positions_at_start.is_empty());
+        assert_eq!(
+            report.positions_at_end[0].symbol.as_deref(),
+            Some("AAPL.US")
+        );
+        assert_eq!(
+            report.positions_at_end[0].extra.get("acc_pos_id"),
+            Some(&json!(5))
+        );
+        assert!(report.extra.contains_key("plainAccountInfoData"));
+    }
+
+    #[test]
+    fn dividends_include_withholding_taxes() {
+        let report = parse_broker_report_response(report()).expect("report must parse");
+
+        let dividends = report.dividends();
+
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].gross, Decimal::new(25, 1));
+        assert_eq!(dividends[0].withholding_tax, Decimal::new(375, 3));
+        assert_eq!(dividends[0].net, Decimal::new(2125, 3));
+        assert!(dividends[0].other_taxes.is_empty());
+    }
+
+    #[test]
+    fn dividends_net_only_taxes_in_dividend_currency() {
+        let report = parse_broker_report_response(json!({"corporate_actions": [{
+            "date": "2025-05-15", "type": "dividend", "ticker": "SAP.EU", "currency": "EUR",
+            "amount": "10", "tax_amount": "1.5", "tax_currency": "EUR",
+            "external_tax": "-2", "external_tax_currency": "USD"
+        }]}))
+        .expect("report must parse");
+
+        let dividend = &report.dividends()[0];
+
+        assert_eq!(dividend.withholding_tax, Decimal::new(15, 1));
+        assert_eq!(dividend.net, Decimal::new(85, 1));
+        assert_eq!(dividend.other_taxes.get("USD"), Some(&Decimal::from(2)));
+        let taxes = report.corporate_actions[0].taxes().collect::<Vec<_>>();
+        assert_eq!(
+            taxes,
+            vec![
+                (Some("EUR"), Decimal::new(15, 1)),
+                (Some("USD"), Decimal::from(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_iterate_typed_rows_then_untyped_blocks() {
+        let report = parse_broker_report_response(report()).expect("report must parse");
+
+        let blocks = report.blocks().collect::<Vec<_>>();
+
+        assert_eq!(blocks.len(), 6);
+        assert!(matches!(blocks[0], ReportBlock::CashFlow(_)));
+        assert!(matches!(
+            blocks[5],
+            ReportBlock::Other {
+                name: "plainAccountInfoData",
+                ..
+            }
+        ));
+        assert!(parse_broker_report_response(json!({"errMsg": "denied"})).is_err());
+    }
+}
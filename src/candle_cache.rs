@@ -0,0 +1,465 @@
+use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+use crate::errors::TradernetError;
+use crate::market_time::API_TIMEZONE;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const ENTRY_EXTENSION: &str = "json";
+
+/// Persistent on-disk cache for `getHloc` history.
+///
+/// Each symbol and timeframe is stored as one JSON file under the cache directory
+/// together with the time ranges already downloaded. When attached to a client
+/// with `with_candle_cache`, range requests only fetch the parts that are not
+/// covered yet. Coverage never extends past the last closed bar, so the current
+/// partial bar is downloaded again on every request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandleCache {
+    dir: PathBuf,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+struct CacheEntry {
+    symbol: String,
+    timeframe: i64,
+    updated_at: i64,
+    covered: Vec<(i64, i64)>,
+    items: Vec<CandlePoint>,
+}
+
+/// Download plan produced by [`CandleCache::plan`].
+#[derive(Debug, Clone)]
+pub(crate) struct CachePlan {
+    path: PathBuf,
+    timeframe: Timeframe,
+    start: i64,
+    end: i64,
+    entry: CacheEntry,
+    missing: Vec<(i64, i64)>,
+}
+
+impl CachePlan {
+    /// Returns ranges to download as naive API times.
+    pub(crate) fn missing_ranges(&self) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        self.missing
+            .iter()
+            .filter_map(|(start, end)| Some((from_timestamp(*start)?, from_timestamp(*end)?)))
+            .collect()
+    }
+}
+
+impl CandleCache {
+    /// Creates a cache storing files in `dir`. The directory is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size: None,
+            max_age: None,
+        }
+    }
+
+    /// Limits the total size of cache files.
+    ///
+    /// When exceeded after a write, least recently used entries are removed.
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Discards entries that were not written for longer than `max_age`.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Returns the cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns cached candles for a symbol and timeframe without any download.
+    pub fn cached(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+    ) -> Result<Option<SymbolCandles>, TradernetError> {
+        Ok(self
+            .load(&self.entry_path(symbol, timeframe))?
+            .map(|entry| SymbolCandles {
+                symbol: entry.symbol,
+                items: entry.items,
+            }))
+    }
+
+    /// Removes the cached entry for a symbol and timeframe.
+    pub fn invalidate(&self, symbol: &str, timeframe: Timeframe) -> Result<(), TradernetError> {
+        remove_if_exists(&self.entry_path(symbol, timeframe))
+    }
+
+    /// Removes cached entries of a symbol for all timeframes.
+    pub fn invalidate_symbol(&self, symbol: &str) -> Result<(), TradernetError> {
+        Timeframe::ALL
+            .iter()
+            .try_for_each(|timeframe| self.invalidate(symbol, *timeframe))
+    }
+
+    /// Removes all cache entries.
+    pub fn clear(&self) -> Result<(), TradernetError> {
+        self.entries()?
+            .iter()
+            .try_for_each(|(path, _, _)| remove_if_exists(path))
+    }
+
+    /// Returns the total size of cache files in bytes.
+    pub fn size(&self) -> Result<u64, TradernetError> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Computes the ranges of `[start, end]` that are not cached yet.
+    pub(crate) fn plan(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<CachePlan, TradernetError> {
+        if end < start {
+            return Err(TradernetError::InvalidInput(
+                "date_to cannot be earlier than date_from".to_string(),
+            ));
+        }
+        let path = self.entry_path(symbol, timeframe);
+        let entry = self.load(&path)?.unwrap_or_else(|| CacheEntry {
+            symbol: symbol.to_string(),
+            timeframe: timeframe.seconds(),
+            ..CacheEntry::default()
+        });
+        let (start, end) = (to_timestamp(start), to_timestamp(end));
+        let missing = subtract_ranges((start, end), &entry.covered);
+        Ok(CachePlan {
+            path,
+            timeframe,
+            start,
+            end,
+            entry,
+            missing,
+        })
+    }
+
+    /// Stores downloaded candles and returns the requested range from the cache.
+    pub(crate) fn complete(
+        &self,
+        plan: CachePlan,
+        fetched: Vec<SymbolCandles>,
+    ) -> Result<SymbolCandles, TradernetError> {
+        let CachePlan {
+            path,
+            timeframe,
+            start,
+            end,
+            mut entry,
+            missing,
+        } = plan;
+
+        if !missing.is_empty() {
+            let now = Utc::now().timestamp();
+            let closed_until = now - timeframe.seconds();
+            let mut series = SymbolCandles {
+                symbol: entry.symbol.clone(),
+                items: std::mem::take(&mut entry.items),
+            };
+            for chunk in fetched {
+                series.merge(chunk);
+            }
+            entry.items = series.items;
+            for (missing_start, missing_end) in missing {
+                let covered_end = missing_end.min(closed_until);
+                if covered_end >= missing_start {
+                    entry.covered.push((missing_start, covered_end));
+                }
+            }
+            entry.covered = merge_ranges(std::mem::take(&mut entry.covered));
+            entry.updated_at = now;
+            self.store(&path, &entry)?;
+            self.enforce_size_limit(&path)?;
+        }
+
+        Ok(SymbolCandles {
+            symbol: entry.symbol,
+            items: entry
+                .items
+                .into_iter()
+                .filter(|item| item.ts >= start && item.ts <= end)
+                .collect(),
+        })
+    }
+
+    fn entry_path(&self, symbol: &str, timeframe: Timeframe) -> PathBuf {
+        self.dir.join(format!(
+            "{}_{}.{ENTRY_EXTENSION}",
+            urlencoding::encode(symbol.trim()),
+            timeframe.seconds()
+        ))
+    }
+
+    fn load(&self, path: &Path) -> Result<Option<CacheEntry>, TradernetError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let entry: CacheEntry = match serde_json::from_reader(BufReader::new(&file)) {
+            Ok(entry) => entry,
+            Err(error) => {
+                log::warn!("corrupted candle cache entry {}: {error}", path.display());
+                return Ok(None);
+            }
+        };
+
+        if let Some(max_age) = self.max_age {
+            let age = Utc::now().timestamp().saturating_sub(entry.updated_at);
+            if age > max_age.as_secs() as i64 {
+                return Ok(None);
+            }
+        }
+        // Reads refresh the modification time used for LRU eviction.
+        let _ = file.set_modified(SystemTime::now());
+        Ok(Some(entry))
+    }
+
+    fn store(&self, path: &Path, entry: &CacheEntry) -> Result<(), TradernetError> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp_path = path.with_extension(format!("{ENTRY_EXTENSION}.tmp"));
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, TradernetError> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        let mut entries = Vec::new();
+        for item in read_dir {
+            let item = item?;
+            let path = item.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(ENTRY_EXTENSION) {
+                continue;
+            }
+            let metadata = item.metadata()?;
+            if metadata.is_file() {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push((path, metadata.len(), modified));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn enforce_size_limit(&self, keep: &Path) -> Result<(), TradernetError> {
+        let Some(max_size) = self.max_size else {
+            return Ok(());
+        };
+        let mut entries = self.entries()?;
+        let mut total = entries.iter().map(|(_, size, _)| size).sum::<u64>();
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= max_size {
+                break;
+            }
+            if path == keep {
+                continue;
+            }
+            remove_if_exists(&path)?;
+            total = total.saturating_sub(size);
+        }
+        Ok(())
+    }
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), TradernetError> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+fn to_timestamp(value: NaiveDateTime) -> i64 {
+    API_TIMEZONE
+        .from_local_datetime(&value)
+        .earliest()
+        .map(|datetime| datetime.timestamp())
+        .unwrap_or_else(|| value.and_utc().timestamp())
+}
+
+fn from_timestamp(value: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp(value, 0).map(|utc| utc.with_timezone(&API_TIMEZONE).naive_local())
+}
+
+fn merge_ranges(mut ranges: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Returns parts of `range` not covered by sorted, non-overlapping `covered` ranges.
+fn subtract_ranges(range: (i64, i64), covered: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let (mut cursor, end) = range;
+    let mut missing = Vec::new();
+    for (covered_start, covered_end) in covered {
+        if *covered_end < cursor {
+            continue;
+        }
+        if *covered_start > end {
+            break;
+        }
+        if *covered_start > cursor {
+            missing.push((cursor, *covered_start));
+        }
+        cursor = cursor.max(*covered_end);
+        if cursor >= end {
+            return missing;
+        }
+    }
+    missing.push((cursor, end));
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CandleCache, from_timestamp, merge_ranges, subtract_ranges};
+    use crate::candles::{CandlePoint, SymbolCandles, Timeframe};
+    use chrono::Utc;
+
+    fn cache_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "tradernet-candle-cache-{name}-{}",
+            std::process::id()
+        ))
+    }
+
+    fn series(timestamps: &[i64]) -> SymbolCandles {
+        SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: timestamps
+                .iter()
+                .map(|ts| CandlePoint {
+                    ts: *ts,
+                    close: *ts as f64,
+                    ..CandlePoint::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn range_arithmetic_finds_gaps() {
+        assert_eq!(
+            merge_ranges(vec![(5, 8), (0, 3), (3, 4)]),
+            vec![(0, 4), (5, 8)]
+        );
+        assert_eq!(
+            subtract_ranges((0, 10), &[(2, 4), (6, 7)]),
+            vec![(0, 2), (4, 6), (7, 10)]
+        );
+        assert!(subtract_ranges((3, 4), &[(0, 10)]).is_empty());
+        assert_eq!(subtract_ranges((0, 5), &[]), vec![(0, 5)]);
+    }
+
+    #[test]
+    fn plan_fetches_only_missing_ranges_and_keeps_partial_bar_uncovered() {
+        let dir = cache_dir("plan");
+        let cache = CandleCache::new(&dir);
+        cache.clear().expect("clear cache");
+        let day = Timeframe::Day1.seconds();
+        let start = from_timestamp(0).expect("valid start");
+        let end = from_timestamp(10 * day).expect("valid end");
+
+        let plan = cache
+            .plan("AAPL.US", Timeframe::Day1, start, end)
+            .expect("plan");
+        assert_eq!(plan.missing_ranges(), vec![(start, end)]);
+        let stored = cache
+            .complete(plan, vec![series(&[0, day, 2 * day])])
+            .expect("store");
+        assert_eq!(stored.items.len(), 3);
+
+        let wider_end = from_timestamp(20 * day).expect("valid end");
+        let plan = cache
+            .plan("AAPL.US", Timeframe::Day1, start, wider_end)
+            .expect("plan");
+        assert_eq!(plan.missing_ranges(), vec![(end, wider_end)]);
+        let merged = cache
+            .complete(plan, vec![series(&[11 * day])])
+            .expect("store");
+        assert_eq!(merged.items.len(), 4);
+
+        let now = Utc::now().timestamp();
+        let live_end = from_timestamp(now).expect("valid end");
+        let live_start = from_timestamp(now - 3 * day).expect("valid start");
+        let plan = cache
+            .plan("AAPL.US", Timeframe::Day1, live_start, live_end)
+            .expect("plan");
+        cache.complete(plan, Vec::new()).expect("store");
+        let plan = cache
+            .plan("AAPL.US", Timeframe::Day1, live_start, live_end)
+            .expect("plan");
+        assert_eq!(plan.missing.len(), 1);
+        assert!(plan.missing[0].1 - plan.missing[0].0 <= day);
+
+        cache
+            .invalidate_symbol("AAPL.US")
+            .expect("invalidate symbol");
+        assert_eq!(
+            cache.cached("AAPL.US", Timeframe::Day1).expect("load"),
+            None
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn size_limit_evicts_least_recently_used_entries() {
+        let dir = cache_dir("size");
+        let cache = CandleCache::new(&dir).with_max_size(1);
+        cache.clear().expect("clear cache");
+        let start = from_timestamp(0).expect("valid start");
+        let end = from_timestamp(60).expect("valid end");
+
+        for symbol in ["AAPL.US", "MSFT.US"] {
+            let plan = cache
+                .plan(symbol, Timeframe::Minute1, start, end)
+                .expect("plan");
+            cache.complete(plan, vec![series(&[0])]).expect("store");
+        }
+
+        assert!(
+            cache
+                .cached("AAPL.US", Timeframe::Minute1)
+                .expect("load")
+                .is_none()
+        );
+        assert!(
+            cache
+                .cached("MSFT.US", Timeframe::Minute1)
+                .expect("load")
+                .is_some()
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
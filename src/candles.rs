@@ -1,8 +1,19 @@
 use crate::errors::TradernetError;
+use crate::market_time::exchange_timezone;
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// Number of bars requested per `getHloc` call by range helpers such as
+/// [`crate::Tradernet::get_candles_range`].
+pub const CANDLES_PER_CHUNK: i64 = 5_000;
+
+/// Maximum number of symbols requested in one `getHloc` call by
+/// [`crate::Tradernet::get_candles_multi`].
+pub const CANDLES_SYMBOLS_PER_BATCH: usize = 10;
+
 /// Typed response for the `getHloc` endpoint.
 ///
 /// Deserialization is intentionally lossy to handle inconsistent API payloads.
@@ -47,6 +58,30 @@ pub struct CandlePoint {
     pub volume: i64,
 }
 
+impl CandlePoint {
+    /// Returns the candle timestamp in UTC.
+    pub fn utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.ts, 0)
+    }
+
+    /// Returns the candle timestamp in the given zone, e.g. the exchange zone from
+    /// [`crate::market_time::exchange_timezone`].
+    pub fn local_time(&self, zone: Tz) -> Option<DateTime<Tz>> {
+        self.utc().map(|utc| utc.with_timezone(&zone))
+    }
+}
+
+/// Candle with its timestamp resolved to UTC and exchange-local time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZonedCandle {
+    /// Candle timestamp in UTC.
+    pub utc: DateTime<Utc>,
+    /// Candle timestamp in the exchange time zone.
+    pub local: DateTime<Tz>,
+    /// Source candle.
+    pub candle: CandlePoint,
+}
+
 /// Normalized candle series for one symbol.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct SymbolCandles {
@@ -54,6 +89,116 @@ pub struct SymbolCandles {
     pub items: Vec<CandlePoint>,
 }
 
+impl SymbolCandles {
+    /// Returns the time zone of the market the symbol is listed on.
+    pub fn exchange_timezone(&self) -> Tz {
+        exchange_timezone(&self.symbol)
+    }
+
+    /// Returns candles with UTC and exchange-local timestamps.
+    ///
+    /// Bars with out-of-range timestamps are skipped.
+    pub fn zoned(&self) -> Vec<ZonedCandle> {
+        let zone = self.exchange_timezone();
+        self.items
+            .iter()
+            .filter_map(|candle| {
+                let utc = candle.utc()?;
+                Some(ZonedCandle {
+                    utc,
+                    local: utc.with_timezone(&zone),
+                    candle: candle.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Merges another series into this one.
+    ///
+    /// Items are sorted by timestamp; when both series contain a bar with the same
+    /// timestamp, the bar from `other` wins.
+    pub fn merge(&mut self, other: SymbolCandles) {
+        self.items.extend(other.items);
+        self.items.sort_by_key(|item| item.ts);
+
+        let mut merged: Vec<CandlePoint> = Vec::with_capacity(self.items.len());
+        for item in self.items.drain(..) {
+            match merged.last_mut() {
+                Some(last) if last.ts == item.ts => *last = item,
+                _ => merged.push(item),
+            }
+        }
+        self.items = merged;
+    }
+}
+
+/// Candle timeframe supported by `getHloc`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Timeframe {
+    /// 1-minute bars.
+    Minute1,
+    /// 5-minute bars.
+    Minute5,
+    /// 15-minute bars.
+    Minute15,
+    /// 1-hour bars.
+    Hour1,
+    /// Daily bars.
+    Day1,
+}
+
+impl Timeframe {
+    /// All supported timeframes, from shortest to longest.
+    pub const ALL: [Timeframe; 5] = [
+        Timeframe::Minute1,
+        Timeframe::Minute5,
+        Timeframe::Minute15,
+        Timeframe::Hour1,
+        Timeframe::Day1,
+    ];
+
+    /// Bar length in seconds.
+    pub fn seconds(self) -> i64 {
+        self.minutes() * 60
+    }
+
+    /// Bar length in minutes, as sent in the `timeframe` request parameter.
+    pub fn minutes(self) -> i64 {
+        match self {
+            Timeframe::Minute1 => 1,
+            Timeframe::Minute5 => 5,
+            Timeframe::Minute15 => 15,
+            Timeframe::Hour1 => 60,
+            Timeframe::Day1 => 1440,
+        }
+    }
+
+    /// Returns the timeframe for a bar length in seconds, if supported.
+    pub fn from_seconds(seconds: i64) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|timeframe| timeframe.seconds() == seconds)
+    }
+
+    /// Time span covered by one chunk of [`CANDLES_PER_CHUNK`] bars.
+    pub fn chunk_span(self) -> TimeDelta {
+        TimeDelta::seconds(self.seconds() * CANDLES_PER_CHUNK)
+    }
+}
+
+impl TryFrom<i64> for Timeframe {
+    type Error = TradernetError;
+
+    fn try_from(seconds: i64) -> Result<Self, Self::Error> {
+        Self::from_seconds(seconds).ok_or_else(|| {
+            TradernetError::InvalidInput(
+                "unsupported timeframe, allowed values: 60, 300, 900, 3600, 86400 seconds"
+                    .to_string(),
+            )
+        })
+    }
+}
+
 /// A single candle in `[high, low, open, close]` form.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct CandleOhlc {
@@ -125,6 +270,79 @@ impl CandlesResponse {
     }
 }
 
+/// Result of a multi-symbol candle download.
+///
+/// Symbols that failed are reported in `errors` instead of failing the whole batch.
+#[derive(Debug, Default)]
+pub struct CandlesBatch {
+    /// Downloaded series by requested symbol.
+    pub candles: HashMap<String, SymbolCandles>,
+    /// Errors by requested symbol.
+    pub errors: HashMap<String, TradernetError>,
+}
+
+impl CandlesBatch {
+    /// Returns the series of a symbol if it was downloaded.
+    pub fn get(&self, symbol: &str) -> Option<&SymbolCandles> {
+        self.candles.get(symbol)
+    }
+
+    /// Returns `true` when no symbol failed.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Records series downloaded for a symbol; no series at all is reported as an error.
+    pub(crate) fn insert_fetched(&mut self, symbol: String, fetched: Vec<SymbolCandles>) {
+        let mut fetched = fetched.into_iter();
+        let Some(mut series) = fetched.next() else {
+            self.errors.insert(
+                symbol.clone(),
+                TradernetError::InvalidInput(format!("no candle data returned for {symbol}")),
+            );
+            return;
+        };
+        for chunk in fetched {
+            series.merge(chunk);
+        }
+        self.candles.insert(symbol, series);
+    }
+
+    /// Records the result of a single-symbol request.
+    pub(crate) fn insert(&mut self, symbol: String, result: Result<SymbolCandles, TradernetError>) {
+        match result {
+            Ok(candles) => {
+                self.candles.insert(symbol, candles);
+            }
+            Err(error) => {
+                self.errors.insert(symbol, error);
+            }
+        }
+    }
+}
+
+impl CandlesResponse {
+    /// Merges series of the given symbols present in this response into `target`.
+    pub(crate) fn merge_into(
+        &self,
+        symbols: &[String],
+        target: &mut HashMap<String, SymbolCandles>,
+    ) {
+        for symbol in symbols {
+            if !self.hloc.contains_key(symbol) {
+                continue;
+            }
+            let series = self.series_for_symbol(symbol);
+            match target.get_mut(symbol) {
+                Some(existing) => existing.merge(series),
+                None => {
+                    target.insert(symbol.clone(), series);
+                }
+            }
+        }
+    }
+}
+
 /// Parses raw `getHloc` JSON into [`CandlesResponse`] and surfaces method-level API errors.
 pub fn parse_candles_response(response: Value) -> Result<CandlesResponse, TradernetError> {
     if let Some(api_error) = parse_candles_api_error(&response) {
@@ -376,10 +594,51 @@ fn parse_string(value: Option<&Value>) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::{
-        CandlePoint, CandlesResponse, SymbolCandles, parse_candles_api_error,
-        parse_candles_response,
+        CandlePoint, CandlesBatch, CandlesResponse, SymbolCandles, Timeframe,
+        parse_candles_api_error, parse_candles_response,
     };
+    use crate::errors::TradernetError;
     use serde_json::json;
+    use std::collections::HashMap;
+
+    fn point(ts: i64, close: f64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+        }
+    }
+
+    #[test]
+    fn merge_sorts_and_replaces_boundary_bars() {
+        let mut series = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![point(120, 2.0), point(60, 1.0)],
+        };
+        series.merge(SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![point(120, 2.5), point(180, 3.0)],
+        });
+
+        let closes = series
+            .items
+            .iter()
+            .map(|item| (item.ts, item.close))
+            .collect::<Vec<_>>();
+        assert_eq!(closes, vec![(60, 1.0), (120, 2.5), (180, 3.0)]);
+    }
+
+    #[test]
+    fn timeframe_converts_from_supported_seconds_only() {
+        assert_eq!(Timeframe::try_from(900).ok(), Some(Timeframe::Minute15));
+        assert_eq!(Timeframe::Day1.minutes(), 1440);
+        assert_eq!(Timeframe::Hour1.seconds(), 3600);
+        let error = Timeframe::try_from(120).expect_err("2 minutes unsupported");
+        assert!(error.to_string().contains("unsupported timeframe"));
+    }
 
     #[test]
     fn candles_response_parses_nominal_payload() {
@@ -547,4 +806,40 @@ mod tests {
         assert_eq!(series.items[1].ts, 1_700_003_600);
         assert_eq!(series.items[1].close, 11.1);
     }
+
+    #[test]
+    fn multi_symbol_response_is_split_per_symbol_with_errors() {
+        let payload = json!({
+            "hloc": {
+                "AAPL.US": [[10.5, 9.8, 10.0, 10.2]],
+                "TSLA.US": [[250.0, 240.0, 245.0, 248.0]]
+            },
+            "vl": {"AAPL.US": [1000], "TSLA.US": [500]},
+            "xSeries": {"AAPL.US": [1700000000], "TSLA.US": [1700000000]}
+        });
+        let response = parse_candles_response(payload).expect("must parse candles");
+        let symbols = ["AAPL.US", "TSLA.US", "BAD.US"].map(String::from);
+
+        let mut series = HashMap::new();
+        response.merge_into(&symbols, &mut series);
+        let mut batch = CandlesBatch::default();
+        for symbol in symbols {
+            let fetched = series.remove(&symbol).into_iter().collect();
+            batch.insert_fetched(symbol, fetched);
+        }
+
+        assert_eq!(
+            batch.get("TSLA.US").map(|series| series.items[0].close),
+            Some(248.0)
+        );
+        assert_eq!(
+            batch.get("AAPL.US").map(|series| series.items[0].volume),
+            Some(1000)
+        );
+        assert!(!batch.is_complete());
+        assert!(matches!(
+            batch.errors.get("BAD.US"),
+            Some(TradernetError::InvalidInput(_))
+        ));
+    }
 }
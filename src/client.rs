@@ -1,17 +1,36 @@
-use crate::candles::{CandlesResponse, SymbolCandles, parse_candles_response};
+use crate::adjustments::{CorporateAction, parse_corporate_actions_response};
+use crate::broker_report::{BrokerReport, parse_broker_report_response};
+use crate::candle_cache::{CachePlan, CandleCache};
+use crate::candles::{
+    CandlesBatch, CandlesResponse, SymbolCandles, Timeframe, parse_candles_response,
+};
 use crate::common::client_helpers;
 use crate::core::Core;
+use crate::cps::{
+    CpsFile, CpsHistory, CpsHistoryFilters, CpsHistoryPager, CpsRequest, CpsStatus,
+    parse_cps_files_response, parse_cps_history_response,
+};
 use crate::errors::TradernetError;
-use crate::trades_history::{TradesHistoryResponse, parse_trades_history_response};
-use crate::user_data::UserDataResponse;
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::market_time::to_api_time;
+use crate::quote_cache::parse_quotes_response;
+use crate::security_info::{
+    SecurityInfo, StockData, parse_security_info_response, parse_stock_data_response,
+};
+use crate::symbols::option_chain::{OptionChain, parse_option_chain_response};
+use crate::trades_history::{
+    TradeRow, TradesHistoryFilters, TradesHistoryPager, TradesHistoryResponse,
+    parse_trades_history_response,
+};
+use crate::user_data::{Quote, UserDataResponse};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::Read;
 
 /// High-level REST client for the Tradernet API.
 pub struct Tradernet {
     core: Core,
+    candle_cache: Option<CandleCache>,
 }
 
 impl Tradernet {
@@ -20,6 +39,7 @@ impl Tradernet {
     pub fn new(public: Option<String>, private: Option<String>) -> Result<Self, TradernetError> {
         Ok(Self {
             core: Core::new(public, private)?,
+            candle_cache: None,
         })
     }
 
@@ -28,9 +48,21 @@ impl Tradernet {
     pub fn from_config(path: impl AsRef<std::path::Path>) -> Result<Self, TradernetError> {
         Ok(Self {
             core: Core::from_config(path)?,
+            candle_cache: None,
         })
     }
 
+    /// Attaches a persistent candle cache consulted by candle range downloads.
+    pub fn with_candle_cache(mut self, cache: CandleCache) -> Self {
+        self.candle_cache = Some(cache);
+        self
+    }
+
+    /// Returns the attached candle cache.
+    pub fn candle_cache(&self) -> Option<&CandleCache> {
+        self.candle_cache.as_ref()
+    }
+
     /// Returns a reference to the underlying [`Core`].
     pub fn core(&self) -> &Core {
         &self.core
@@ -192,6 +224,16 @@ impl Tradernet {
             .authorized_request("getSecurityInfo", Some(params), Some(2))
     }
 
+    /// Returns typed security information for a symbol.
+    pub fn security_info_typed(
+        &self,
+        symbol: &str,
+        sup: bool,
+    ) -> Result<SecurityInfo, TradernetError> {
+        let response = self.security_info(symbol, sup)?;
+        parse_security_info_response(response)
+    }
+
     /// Returns options chain data for an underlying on a given exchange.
     pub fn get_options(&self, underlying: &str, exchange: &str) -> Result<Value, TradernetError> {
         let mut params = Map::new();
@@ -204,6 +246,18 @@ impl Tradernet {
             .authorized_request("getOptionsByMkt", Some(params), Some(2))
     }
 
+    /// Returns the option chain for an underlying grouped by expiry and strike.
+    ///
+    /// Narrow it with [`OptionChain::filter`].
+    pub fn get_option_chain(
+        &self,
+        underlying: &str,
+        exchange: &str,
+    ) -> Result<OptionChain, TradernetError> {
+        let response = self.get_options(underlying, exchange)?;
+        parse_option_chain_response(underlying, response)
+    }
+
     /// Returns candle data for a symbol and time range.
     pub fn get_candles(
         &self,
@@ -219,6 +273,9 @@ impl Tradernet {
     ///
     /// `count` must be negative: `-1` means no extra candles outside the requested interval,
     /// `-100` means include 100 candles before `date_from`.
+    ///
+    /// Naive `start`/`end` are wall-clock times in [`crate::market_time::API_TIMEZONE`];
+    /// see [`Self::get_candles_range_tz`] for zone-aware input.
     pub fn get_candles_with_count(
         &self,
         symbol: &str,
@@ -258,6 +315,8 @@ impl Tradernet {
     }
 
     /// Returns normalized candle series for a symbol and time range.
+    ///
+    /// Reads go through the candle cache when one is attached.
     pub fn get_candles_series(
         &self,
         symbol: &str,
@@ -265,6 +324,10 @@ impl Tradernet {
         end: NaiveDateTime,
         timeframe_seconds: i64,
     ) -> Result<SymbolCandles, TradernetError> {
+        if self.candle_cache.is_some() {
+            let timeframe = Timeframe::try_from(timeframe_seconds)?;
+            return self.get_candles_range(symbol, start, end, timeframe);
+        }
         self.get_candles_with_count_series(symbol, start, end, timeframe_seconds, -1)
     }
 
@@ -282,6 +345,180 @@ impl Tradernet {
         Ok(response.series_for_symbol(symbol))
     }
 
+    /// Returns a normalized candle series for an arbitrarily long time range.
+    ///
+    /// The range is split into chunks of [`crate::candles::CANDLES_PER_CHUNK`] bars,
+    /// fetched sequentially and merged into one series sorted by timestamp, with
+    /// duplicated boundary bars removed. With a candle cache attached, only ranges
+    /// missing from the cache and the last partial bar are downloaded.
+    pub fn get_candles_range(
+        &self,
+        symbol: &str,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        let Some(cache) = &self.candle_cache else {
+            return self.fetch_candles_range(symbol, start, end, timeframe);
+        };
+        let plan = cache.plan(symbol, timeframe, start, end)?;
+        let fetched = plan
+            .missing_ranges()
+            .into_iter()
+            .map(|(missing_start, missing_end)| {
+                self.fetch_candles_range(symbol, missing_start, missing_end, timeframe)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        cache.complete(plan, fetched)
+    }
+
+    fn fetch_candles_range(
+        &self,
+        symbol: &str,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        let mut series = SymbolCandles {
+            symbol: symbol.to_string(),
+            items: Vec::new(),
+        };
+        for (chunk_start, chunk_end) in client_helpers::split_candles_range(start, end, timeframe)?
+        {
+            series.merge(self.get_candles_with_count_series(
+                symbol,
+                chunk_start,
+                chunk_end,
+                timeframe.seconds(),
+                -1,
+            )?);
+        }
+        Ok(series)
+    }
+
+    /// Returns a normalized candle series for a range given in any time zone.
+    ///
+    /// `start` and `end` are converted to the API zone before the request; the
+    /// returned timestamps are UTC and can be shown in exchange-local time with
+    /// [`SymbolCandles::zoned`].
+    pub fn get_candles_range_tz<Z: TimeZone>(
+        &self,
+        symbol: &str,
+        start: DateTime<Z>,
+        end: DateTime<Z>,
+        timeframe: Timeframe,
+    ) -> Result<SymbolCandles, TradernetError> {
+        self.get_candles_range(symbol, to_api_time(&start), to_api_time(&end), timeframe)
+    }
+
+    /// Downloads candles for several symbols over one range.
+    ///
+    /// Symbols are requested in groups of [`crate::candles::CANDLES_SYMBOLS_PER_BATCH`]
+    /// and long ranges are split into chunks like in [`Self::get_candles_range`].
+    /// When the API rejects a group because of one of its symbols, the group is
+    /// retried one symbol at a time so a single bad symbol does not fail the others;
+    /// per-symbol failures are returned in [`CandlesBatch::errors`]. Other request
+    /// errors (transport, authentication, ...) fail the whole call. With a candle
+    /// cache attached, only symbols with missing ranges are downloaded, batched by
+    /// the ranges they miss.
+    pub fn get_candles_multi<I, S>(
+        &self,
+        symbols: I,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        timeframe: Timeframe,
+    ) -> Result<CandlesBatch, TradernetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let symbols = client_helpers::unique_candle_symbols(symbols)?;
+        let mut plans = HashMap::new();
+        if let Some(cache) = &self.candle_cache {
+            for symbol in &symbols {
+                plans.insert(symbol.clone(), cache.plan(symbol, timeframe, start, end)?);
+            }
+        }
+        let missing = symbols
+            .iter()
+            .map(|symbol| {
+                let ranges = plans
+                    .get(symbol)
+                    .map_or_else(|| vec![(start, end)], CachePlan::missing_ranges);
+                (symbol.clone(), ranges)
+            })
+            .collect();
+
+        let mut fetched = HashMap::new();
+        for request in client_helpers::group_candle_requests(missing, timeframe)? {
+            match self.fetch_candles_batch(&request.symbols, &request.chunks, timeframe) {
+                Ok(mut series) => {
+                    for symbol in request.symbols {
+                        let candles = series.remove(&symbol).into_iter().collect();
+                        fetched.insert(symbol, Ok(candles));
+                    }
+                }
+                Err(error) if client_helpers::is_symbol_error(&error, &request.symbols) => {
+                    log::warn!("candle batch request failed, retrying symbols one by one: {error}");
+                    for symbol in request.symbols {
+                        let candles = request
+                            .ranges
+                            .iter()
+                            .map(|(range_start, range_end)| {
+                                self.fetch_candles_range(
+                                    &symbol,
+                                    *range_start,
+                                    *range_end,
+                                    timeframe,
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>();
+                        fetched.insert(symbol, candles);
+                    }
+                }
+                Err(error) => {
+                    log::warn!("candle batch request failed: {error}");
+                    return Err(error);
+                }
+            }
+        }
+
+        let mut result = CandlesBatch::default();
+        for symbol in symbols {
+            match (
+                fetched.remove(&symbol),
+                plans.remove(&symbol),
+                &self.candle_cache,
+            ) {
+                (Some(Err(error)), _, _) => result.insert(symbol, Err(error)),
+                (candles, Some(plan), Some(cache)) => {
+                    let candles =
+                        cache.complete(plan, candles.and_then(Result::ok).unwrap_or_default());
+                    result.insert(symbol, candles);
+                }
+                (candles, _, _) => {
+                    result.insert_fetched(symbol, candles.and_then(Result::ok).unwrap_or_default());
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn fetch_candles_batch(
+        &self,
+        symbols: &[String],
+        chunks: &[(NaiveDateTime, NaiveDateTime)],
+        timeframe: Timeframe,
+    ) -> Result<HashMap<String, SymbolCandles>, TradernetError> {
+        let ids = symbols.join(",");
+        let mut series = HashMap::new();
+        for (chunk_start, chunk_end) in chunks {
+            self.get_candles_typed(&ids, *chunk_start, *chunk_end, timeframe.seconds())?
+                .merge_into(symbols, &mut series);
+        }
+        Ok(series)
+    }
+
     /// Returns quote data for a list of symbols.
     pub fn get_quotes<I, S>(&self, symbols: I) -> Result<Value, TradernetError>
     where
@@ -299,6 +536,16 @@ impl Tradernet {
             .authorized_request("getStockQuotesJson", Some(params), Some(2))
     }
 
+    /// Returns typed quotes for a list of symbols.
+    pub fn get_quotes_typed<I, S>(&self, symbols: I) -> Result<Vec<Quote>, TradernetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let response = self.get_quotes(symbols)?;
+        parse_quotes_response(response)
+    }
+
     /// Returns trades history for a given date range.
     pub fn get_trades_history(
         &self,
@@ -365,6 +612,23 @@ impl Tradernet {
         parse_trades_history_response(response)
     }
 
+    /// Iterates over all trades in a date range, following the `tradeId` cursor page by page.
+    ///
+    /// Trades are de-duplicated by id and pages are requested no more often than
+    /// [`TradesHistoryFilters::request_interval`]. Iteration ends after the first error.
+    pub fn trades_history_iter(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        filters: TradesHistoryFilters,
+    ) -> Result<TradesHistoryIter<'_>, TradernetError> {
+        Ok(TradesHistoryIter {
+            client: self,
+            pager: TradesHistoryPager::new(start, end, filters)?,
+            buffer: VecDeque::new(),
+        })
+    }
+
     /// Searches for symbols by text (optionally within a specific exchange).
     pub fn find_symbol(
         &self,
@@ -559,6 +823,67 @@ impl Tradernet {
             .authorized_request("getCpsFiles", Some(params), Some(2))
     }
 
+    /// Returns one typed page of client requests (CPS) history.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_requests_history_typed(
+        &self,
+        doc_id: Option<i64>,
+        exec_id: Option<i64>,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        status: Option<CpsStatus>,
+    ) -> Result<CpsHistory, TradernetError> {
+        let response = self.get_requests_history(
+            doc_id,
+            exec_id,
+            start,
+            end,
+            limit,
+            offset,
+            status.map(CpsStatus::code),
+        )?;
+        parse_cps_history_response(response)
+    }
+
+    /// Returns all client requests in a date range, following `limit`/`offset` pages.
+    ///
+    /// Paging stops once a page brings no new request ids.
+    pub fn get_requests_history_all(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        filters: CpsHistoryFilters,
+    ) -> Result<Vec<CpsRequest>, TradernetError> {
+        let mut pager = CpsHistoryPager::new(filters)?;
+        let mut requests = Vec::new();
+        while let Some(offset) = pager.offset() {
+            let filters = pager.filters();
+            let page = self.get_requests_history_typed(
+                filters.doc_id,
+                filters.exec_id,
+                start,
+                end,
+                Some(filters.page_size),
+                Some(offset),
+                filters.status,
+            )?;
+            requests.extend(pager.absorb(page));
+        }
+        Ok(requests)
+    }
+
+    /// Returns typed files attached to an order; see [`CpsFile::save_to`].
+    pub fn get_order_files_typed(
+        &self,
+        order_id: Option<i64>,
+        internal_id: Option<i64>,
+    ) -> Result<Vec<CpsFile>, TradernetError> {
+        let response = self.get_order_files(order_id, internal_id)?;
+        parse_cps_files_response(response)
+    }
+
     /// Retrieves broker report for a given date range.
     pub fn get_broker_report(
         &self,
@@ -585,6 +910,18 @@ impl Tradernet {
             .authorized_request("getBrokerReport", Some(params), Some(2))
     }
 
+    /// Retrieves a typed broker report for a given date range.
+    pub fn get_broker_report_typed(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        period: NaiveTime,
+        data_block_type: Option<&str>,
+    ) -> Result<BrokerReport, TradernetError> {
+        let response = self.get_broker_report(start, end, period, data_block_type)?;
+        parse_broker_report_response(response)
+    }
+
     /// Returns detailed information about a symbol.
     pub fn symbol(&self, symbol: &str, lang: &str) -> Result<Value, TradernetError> {
         let mut params = Map::new();
@@ -594,6 +931,12 @@ impl Tradernet {
             .authorized_request("getStockData", Some(params), Some(2))
     }
 
+    /// Returns typed stock data for a symbol.
+    pub fn symbol_typed(&self, symbol: &str, lang: &str) -> Result<StockData, TradernetError> {
+        let response = self.symbol(symbol, lang)?;
+        parse_stock_data_response(response)
+    }
+
     /// Returns the list of symbols for an exchange.
     pub fn symbols(&self, exchange: Option<&str>) -> Result<Value, TradernetError> {
         let params = exchange.map(|exchange| {
@@ -613,6 +956,15 @@ impl Tradernet {
             .authorized_request("getPlannedCorpActions", Some(params), Some(2))
     }
 
+    /// Returns typed planned corporate actions for a reception.
+    pub fn corporate_actions_typed(
+        &self,
+        reception: i64,
+    ) -> Result<Vec<CorporateAction>, TradernetError> {
+        let response = self.corporate_actions(reception)?;
+        parse_corporate_actions_response(response)
+    }
+
     /// Places a buy order.
     pub fn buy(
         &self,
@@ -816,3 +1168,39 @@ impl Tradernet {
         client_helpers::parse_refbooks(&content)
     }
 }
+
+/// Blocking iterator over paginated trades history returned by
+/// [`Tradernet::trades_history_iter`].
+pub struct TradesHistoryIter<'a> {
+    client: &'a Tradernet,
+    pager: TradesHistoryPager,
+    buffer: VecDeque<TradeRow>,
+}
+
+impl Iterator for TradesHistoryIter<'_> {
+    type Item = Result<TradeRow, TradernetError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() && !self.pager.is_done() {
+            if let Some(delay) = self.pager.delay() {
+                std::thread::sleep(delay);
+            }
+            let page = self.pager.next_params().and_then(|params| {
+                let response = self.client.core.authorized_request(
+                    "getTradesHistory",
+                    Some(params),
+                    Some(2),
+                )?;
+                parse_trades_history_response(response)
+            });
+            match page {
+                Ok(page) => self.buffer.extend(self.pager.absorb(page)),
+                Err(error) => {
+                    self.pager.finish();
+                    return Some(Err(error));
+                }
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
@@ -1,3 +1,5 @@
+use crate::candles::{CANDLES_SYMBOLS_PER_BATCH, Timeframe};
+use crate::common::string_utils::ticker_words;
 use crate::errors::TradernetError;
 use chrono::{NaiveDate, NaiveDateTime};
 use regex::Regex;
@@ -90,6 +92,8 @@ pub(crate) fn build_trailing_stop_params(symbol: &str, percent: i64) -> Map<Stri
     params
 }
 
+/// Builds `getHloc` params; `start`/`end` are wall-clock times in
+/// [`crate::market_time::API_TIMEZONE`].
 pub(crate) fn build_candles_params(
     symbol: &str,
     start: NaiveDateTime,
@@ -118,12 +122,7 @@ pub(crate) fn build_candles_params(
         ));
     }
 
-    let timeframe_minutes = timeframe_seconds / 60;
-    if !matches!(timeframe_minutes, 1 | 5 | 15 | 60 | 1440) {
-        return Err(TradernetError::InvalidInput(
-            "unsupported timeframe, allowed values: 60, 300, 900, 3600, 86400 seconds".to_string(),
-        ));
-    }
+    let timeframe_minutes = Timeframe::try_from(timeframe_seconds)?.minutes();
 
     let mut params = Map::new();
     params.insert("id".to_string(), Value::String(symbol.to_string()));
@@ -147,6 +146,115 @@ pub(crate) fn build_candles_params(
     Ok(params)
 }
 
+/// Normalizes symbols for a multi-symbol `getHloc` download, keeping the first
+/// occurrence of each.
+pub(crate) fn unique_candle_symbols<I, S>(symbols: I) -> Result<Vec<String>, TradernetError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut unique: Vec<String> = Vec::new();
+    for symbol in symbols {
+        let symbol = symbol.as_ref().trim();
+        if symbol.is_empty() || symbol.contains(',') {
+            return Err(TradernetError::InvalidInput(format!(
+                "invalid symbol for candle batch: {symbol:?}"
+            )));
+        }
+        if !unique.iter().any(|existing| existing == symbol) {
+            unique.push(symbol.to_string());
+        }
+    }
+    if unique.is_empty() {
+        return Err(TradernetError::InvalidInput(
+            "symbols cannot be empty".to_string(),
+        ));
+    }
+    Ok(unique)
+}
+
+type DateRange = (NaiveDateTime, NaiveDateTime);
+
+/// Symbols sharing the same missing ranges, downloaded with one request per chunk.
+pub(crate) struct CandleBatchRequest {
+    pub(crate) symbols: Vec<String>,
+    pub(crate) ranges: Vec<DateRange>,
+    pub(crate) chunks: Vec<DateRange>,
+}
+
+/// Groups symbols by the ranges they still need and splits every group into
+/// batches of [`CANDLES_SYMBOLS_PER_BATCH`]. Symbols with nothing missing are skipped.
+pub(crate) fn group_candle_requests(
+    missing: Vec<(String, Vec<DateRange>)>,
+    timeframe: Timeframe,
+) -> Result<Vec<CandleBatchRequest>, TradernetError> {
+    let mut groups: Vec<(Vec<DateRange>, Vec<String>)> = Vec::new();
+    for (symbol, ranges) in missing {
+        if ranges.is_empty() {
+            continue;
+        }
+        match groups.iter_mut().find(|(group, _)| *group == ranges) {
+            Some((_, symbols)) => symbols.push(symbol),
+            None => groups.push((ranges, vec![symbol])),
+        }
+    }
+
+    let mut requests = Vec::new();
+    for (ranges, symbols) in groups {
+        let mut chunks = Vec::new();
+        for (start, end) in &ranges {
+            chunks.extend(split_candles_range(*start, *end, timeframe)?);
+        }
+        for batch in symbols.chunks(CANDLES_SYMBOLS_PER_BATCH) {
+            requests.push(CandleBatchRequest {
+                symbols: batch.to_vec(),
+                ranges: ranges.clone(),
+                chunks: chunks.clone(),
+            });
+        }
+    }
+    Ok(requests)
+}
+
+/// Returns `true` when a batch request failed because of one of its symbols, i.e.
+/// the API rejected the method with a message naming a requested ticker.
+pub(crate) fn is_symbol_error(error: &TradernetError, symbols: &[String]) -> bool {
+    let TradernetError::ApiMethodError { message, .. } = error else {
+        return false;
+    };
+    let words = ticker_words(message);
+    symbols.iter().any(|symbol| words.contains(symbol.as_str()))
+}
+
+/// Splits `[start, end]` into consecutive chunks of at most
+/// [`Timeframe::chunk_span`]. Adjacent chunks share their boundary timestamp.
+pub(crate) fn split_candles_range(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    timeframe: Timeframe,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime)>, TradernetError> {
+    if end < start {
+        return Err(TradernetError::InvalidInput(
+            "date_to cannot be earlier than date_from".to_string(),
+        ));
+    }
+
+    let span = timeframe.chunk_span();
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+    loop {
+        let chunk_end = chunk_start
+            .checked_add_signed(span)
+            .map_or(end, |chunk_end| chunk_end.min(end));
+        chunks.push((chunk_start, chunk_end));
+        if chunk_end >= end {
+            break;
+        }
+        chunk_start = chunk_end;
+    }
+    Ok(chunks)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn build_trades_history_params(
     start: NaiveDate,
@@ -276,8 +384,13 @@ pub(crate) fn parse_refbooks(content: &str) -> Result<Vec<String>, TradernetErro
 
 #[cfg(test)]
 mod tests {
-    use super::{build_candles_params, build_trades_history_params};
-    use chrono::{NaiveDate, NaiveTime};
+    use super::{
+        build_candles_params, build_trades_history_params, group_candle_requests, is_symbol_error,
+        split_candles_range, unique_candle_symbols,
+    };
+    use crate::candles::Timeframe;
+    use crate::errors::TradernetError;
+    use chrono::{NaiveDate, NaiveTime, TimeDelta};
 
     #[test]
     fn build_candles_params_builds_closed_ray_payload() {
@@ -336,6 +449,87 @@ mod tests {
         assert!(error.to_string().contains("count must be negative"));
     }
 
+    #[test]
+    fn split_candles_range_chunks_long_ranges_with_shared_boundaries() {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .expect("valid date")
+            .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+        let end = start + Timeframe::Minute1.chunk_span() * 2 + TimeDelta::minutes(30);
+
+        let chunks = split_candles_range(start, end, Timeframe::Minute1).expect("valid range");
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].0, start);
+        assert_eq!(chunks[0].1, chunks[1].0);
+        assert_eq!(chunks[1].1, chunks[2].0);
+        assert_eq!(chunks[2].1, end);
+
+        let single = split_candles_range(start, start, Timeframe::Day1).expect("valid range");
+        assert_eq!(single, vec![(start, start)]);
+        assert!(split_candles_range(end, start, Timeframe::Day1).is_err());
+    }
+
+    #[test]
+    fn unique_candle_symbols_dedupes_and_validates() {
+        let symbols = (0..12)
+            .map(|index| format!("S{index}.US"))
+            .chain([" S0.US ".to_string()])
+            .collect::<Vec<_>>();
+
+        let unique = unique_candle_symbols(&symbols).expect("valid symbols");
+
+        assert_eq!(unique.len(), 12);
+        assert_eq!(unique[0], "S0.US");
+        assert!(unique_candle_symbols(Vec::<String>::new()).is_err());
+        assert!(unique_candle_symbols(["AAPL.US,TSLA.US"]).is_err());
+    }
+
+    #[test]
+    fn group_candle_requests_batches_symbols_sharing_missing_ranges() {
+        let day = |day| {
+            NaiveDate::from_ymd_opt(2026, 1, day)
+                .expect("valid date")
+                .and_time(NaiveTime::MIN)
+        };
+        let full = vec![(day(1), day(10))];
+        let tail = vec![(day(9), day(10))];
+        let missing = (0..12)
+            .map(|index| (format!("S{index}.US"), full.clone()))
+            .chain([
+                ("T.US".to_string(), tail.clone()),
+                ("CACHED.US".to_string(), Vec::new()),
+            ])
+            .collect::<Vec<_>>();
+
+        let requests = group_candle_requests(missing, Timeframe::Day1).expect("valid ranges");
+
+        assert_eq!(
+            requests
+                .iter()
+                .map(|request| (request.symbols.len(), request.ranges.clone()))
+                .collect::<Vec<_>>(),
+            vec![(10, full.clone()), (2, full), (1, tail.clone())]
+        );
+        assert_eq!(requests[2].chunks, tail);
+    }
+
+    #[test]
+    fn is_symbol_error_requires_api_error_naming_a_requested_ticker() {
+        let symbols = ["T.US".to_string(), "AAPL.US".to_string()];
+        let api_error = |message: &str| TradernetError::ApiMethodError {
+            code: 1,
+            message: message.to_string(),
+        };
+
+        assert!(is_symbol_error(&api_error("Unknown ticker T.US"), &symbols));
+        assert!(!is_symbol_error(
+            &api_error("Unknown ticker AT.US"),
+            &symbols
+        ));
+        assert!(!is_symbol_error(&api_error("Too many requests"), &symbols));
+        assert!(!is_symbol_error(&TradernetError::MissingKeypair, &symbols));
+    }
+
     #[test]
     fn build_trades_history_params_includes_reception() {
         let start = NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date");
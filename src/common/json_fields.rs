@@ -0,0 +1,158 @@
+use crate::errors::TradernetError;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+/// Returns the method-level error of a response (`error` or `errMsg` with an optional
+/// `code`), or `None` when the response carries no error message.
+pub(crate) fn api_method_error(response: &Value) -> Option<TradernetError> {
+    let message = parse_string(response.get("error").or_else(|| response.get("errMsg")))?;
+    let code = parse_i64(response.get("code")).unwrap_or(0);
+    Some(TradernetError::ApiMethodError { code, message })
+}
+
+/// Removes and returns the first non-null value stored under one of `keys`.
+pub(crate) fn take(row: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    keys.iter()
+        .find_map(|key| row.remove(*key).filter(|value| !value.is_null()))
+}
+
+pub(crate) fn take_string(row: &mut Map<String, Value>, keys: &[&str]) -> Option<String> {
+    match take(row, keys)? {
+        Value::String(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn take_decimal(row: &mut Map<String, Value>, keys: &[&str]) -> Option<Decimal> {
+    match take(row, keys)? {
+        Value::String(value) => decimal_from_str(&value),
+        Value::Number(value) => value
+            .as_i64()
+            .map(Decimal::from)
+            .or_else(|| value.as_f64().and_then(decimal_from_f64)),
+        _ => None,
+    }
+}
+
+pub(crate) fn take_i64(row: &mut Map<String, Value>, keys: &[&str]) -> Option<i64> {
+    match take(row, keys)? {
+        Value::Number(value) => value.as_i64(),
+        Value::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn take_f64(row: &mut Map<String, Value>, keys: &[&str]) -> Option<f64> {
+    match take(row, keys)? {
+        Value::Number(value) => value.as_f64(),
+        Value::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn take_datetime(row: &mut Map<String, Value>, keys: &[&str]) -> Option<NaiveDateTime> {
+    take_string(row, keys).and_then(|value| parse_datetime(&value))
+}
+
+pub(crate) fn take_date(row: &mut Map<String, Value>, keys: &[&str]) -> Option<NaiveDate> {
+    take_string(row, keys).and_then(|value| parse_date(&value))
+}
+
+/// Converts a float into a decimal using its shortest round-trip representation.
+pub(crate) fn decimal_from_f64(value: f64) -> Option<Decimal> {
+    if !value.is_finite() {
+        return None;
+    }
+    Decimal::from_str(&value.to_string())
+        .ok()
+        .or_else(|| Decimal::from_f64_retain(value))
+}
+
+/// Parses a decimal string, accepting surrounding whitespace and scientific notation.
+pub(crate) fn decimal_from_str(value: &str) -> Option<Decimal> {
+    let value = value.trim();
+    Decimal::from_str(value)
+        .or_else(|_| Decimal::from_scientific(value))
+        .ok()
+}
+
+/// Parses API datetimes (`T` or space separated, or a bare date).
+pub(crate) fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| parse_date(value).and_then(|date| date.and_hms_opt(0, 0, 0)))
+}
+
+/// Parses the leading `YYYY-MM-DD` of an API date or datetime.
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let date = value.get(..10).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn parse_i64(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::Number(value) => value
+            .as_i64()
+            .or_else(|| value.as_f64().map(|value| value.trunc() as i64)),
+        Value::String(value) => {
+            let value = value.trim();
+            value
+                .parse::<i64>()
+                .ok()
+                .or_else(|| value.parse::<f64>().ok().map(|value| value.trunc() as i64))
+        }
+        _ => None,
+    }
+}
+
+fn parse_string(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(value) => {
+            let trimmed = value.trim();
+            (!trimmed.is_empty()).then(|| trimmed.to_string())
+        }
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_method_error, take_decimal, take_string};
+    use crate::errors::TradernetError;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    #[test]
+    fn api_method_error_reads_message_and_code_lossily() {
+        let error = api_method_error(&json!({"errMsg": " denied ", "code": "7"}));
+        assert!(matches!(
+            error,
+            Some(TradernetError::ApiMethodError { code: 7, ref message }) if message == "denied"
+        ));
+        assert!(matches!(
+            api_method_error(&json!({"error": "oops", "code": 3.0})),
+            Some(TradernetError::ApiMethodError { code: 3, .. })
+        ));
+        assert!(api_method_error(&json!({"error": "  "})).is_none());
+        assert!(api_method_error(&json!({"result": []})).is_none());
+    }
+
+    #[test]
+    fn take_helpers_remove_first_present_key() {
+        let mut row = json!({"a": null, "b": " x ", "sum": "1.50"})
+            .as_object()
+            .cloned()
+            .expect("object");
+
+        assert_eq!(take_string(&mut row, &["a", "b"]).as_deref(), Some("x"));
+        assert_eq!(take_decimal(&mut row, &["sum"]), Some(Decimal::new(150, 2)));
+        assert!(row.is_empty());
+    }
+}
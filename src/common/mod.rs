@@ -2,6 +2,8 @@
 
 /// Shared builders and parsers for REST clients.
 pub mod client_helpers;
+/// Lossy field extraction and API error checks for untyped JSON responses.
+pub mod json_fields;
 /// HTTP request helpers used by the SDK.
 pub mod net_utils;
 /// Utility functions for signing and query formatting.
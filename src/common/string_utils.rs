@@ -1,7 +1,7 @@
 use hmac::{Hmac, Mac};
 use serde_json::{Map, Value};
 use sha2::Sha256;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -123,6 +123,15 @@ fn value_to_string(value: &Value) -> String {
     }
 }
 
+/// Splits free text (e.g. an API error message) into ticker-like words, so
+/// `T.US` does not match `AT.US` or `T.USD`.
+pub(crate) fn ticker_words(text: &str) -> HashSet<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '+' | '-' | '_' | '/' | '@')))
+        .map(|word| word.trim_end_matches(['.', '-']))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
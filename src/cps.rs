@@ -0,0 +1,538 @@
+use crate::common::json_fields::{api_method_error, take_datetime, take_i64, take_string};
+use crate::errors::TradernetError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Default page size for [`crate::Tradernet::get_requests_history_all`].
+pub const CPS_HISTORY_PAGE_SIZE: i64 = 100;
+
+/// Client request (CPS) status, sent as `cps_status`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(from = "i64", into = "i64")]
+pub enum CpsStatus {
+    Draft,
+    InProgress,
+    Rejected,
+    Executed,
+    /// Status code unknown to the SDK.
+    Other(i64),
+}
+
+impl CpsStatus {
+    /// Numeric status code used by the API.
+    pub fn code(self) -> i64 {
+        match self {
+            CpsStatus::Draft => 0,
+            CpsStatus::InProgress => 1,
+            CpsStatus::Rejected => 2,
+            CpsStatus::Executed => 3,
+            CpsStatus::Other(code) => code,
+        }
+    }
+
+    /// Returns `true` for rejected and executed requests.
+    pub fn is_final(self) -> bool {
+        matches!(self, CpsStatus::Rejected | CpsStatus::Executed)
+    }
+}
+
+impl From<i64> for CpsStatus {
+    fn from(code: i64) -> Self {
+        match code {
+            0 => CpsStatus::Draft,
+            1 => CpsStatus::InProgress,
+            2 => CpsStatus::Rejected,
+            3 => CpsStatus::Executed,
+            code => CpsStatus::Other(code),
+        }
+    }
+}
+
+impl From<CpsStatus> for i64 {
+    fn from(status: CpsStatus) -> Self {
+        status.code()
+    }
+}
+
+/// Single client request from `getClientCpsHistory`.
+///
+/// Unknown fields are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CpsRequest {
+    pub id: Option<i64>,
+    /// Request document type (`cpsDocId`).
+    pub doc_id: Option<i64>,
+    pub name: Option<String>,
+    pub status: Option<CpsStatus>,
+    pub created: Option<NaiveDateTime>,
+    pub updated: Option<NaiveDateTime>,
+    pub comment: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+/// One page of `getClientCpsHistory`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CpsHistory {
+    pub requests: Vec<CpsRequest>,
+    /// Total number of matching requests, when reported.
+    pub total: Option<i64>,
+}
+
+/// Filters for [`crate::Tradernet::get_requests_history_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpsHistoryFilters {
+    pub doc_id: Option<i64>,
+    pub exec_id: Option<i64>,
+    pub status: Option<CpsStatus>,
+    /// `limit` sent with every page.
+    pub page_size: i64,
+}
+
+impl Default for CpsHistoryFilters {
+    fn default() -> Self {
+        Self {
+            doc_id: None,
+            exec_id: None,
+            status: None,
+            page_size: CPS_HISTORY_PAGE_SIZE,
+        }
+    }
+}
+
+impl CpsHistoryFilters {
+    pub fn with_doc_id(mut self, doc_id: i64) -> Self {
+        self.doc_id = Some(doc_id);
+        self
+    }
+
+    pub fn with_exec_id(mut self, exec_id: i64) -> Self {
+        self.exec_id = Some(exec_id);
+        self
+    }
+
+    pub fn with_status(mut self, status: CpsStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: i64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), TradernetError> {
+        if self.page_size <= 0 {
+            return Err(TradernetError::InvalidInput(
+                "page_size must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Pages `getClientCpsHistory` by `limit`/`offset`.
+///
+/// Repeated rows are dropped by id. Paging stops on a short page, once `total`
+/// is reached, or on a page without new request ids.
+#[derive(Debug)]
+pub(crate) struct CpsHistoryPager {
+    filters: CpsHistoryFilters,
+    offset: Option<i64>,
+    seen: HashSet<i64>,
+}
+
+impl CpsHistoryPager {
+    pub(crate) fn new(filters: CpsHistoryFilters) -> Result<Self, TradernetError> {
+        filters.validate()?;
+        Ok(Self {
+            filters,
+            offset: Some(0),
+            seen: HashSet::new(),
+        })
+    }
+
+    pub(crate) fn filters(&self) -> &CpsHistoryFilters {
+        &self.filters
+    }
+
+    /// Offset of the next page, or `None` when paging is done.
+    pub(crate) fn offset(&self) -> Option<i64> {
+        self.offset
+    }
+
+    /// Consumes a page and returns requests not seen before.
+    pub(crate) fn absorb(&mut self, page: CpsHistory) -> Vec<CpsRequest> {
+        let received = page.requests.len() as i64;
+        let next = self.offset.unwrap_or(0) + received;
+        let mut new_ids = 0;
+        let fresh = page
+            .requests
+            .into_iter()
+            .filter(|request| match request.id {
+                Some(id) if self.seen.insert(id) => {
+                    new_ids += 1;
+                    true
+                }
+                Some(_) => false,
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        let exhausted = received < self.filters.page_size
+            || page.total.is_some_and(|total| next >= total)
+            || new_ids == 0;
+        self.offset = (!exhausted).then_some(next);
+        fresh
+    }
+}
+
+/// Attachment returned by `getCpsFiles`.
+///
+/// Content is either inline (base64, optionally as a `data:` URI) or linked by `url`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CpsFile {
+    pub id: Option<i64>,
+    /// Original file name.
+    pub name: Option<String>,
+    pub mime_type: Option<String>,
+    pub size: Option<i64>,
+    /// Base64-encoded content.
+    pub content: Option<String>,
+    pub url: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+impl CpsFile {
+    /// Decodes inline content, or returns `None` when the file is only linked.
+    pub fn bytes(&self) -> Result<Option<Vec<u8>>, TradernetError> {
+        let Some(content) = self.content.as_deref() else {
+            return Ok(None);
+        };
+        let encoded = match content.split_once(";base64,") {
+            Some((prefix, data)) if prefix.starts_with("data:") => data,
+            _ => content,
+        };
+        let encoded = encoded
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+        STANDARD
+            .decode(encoded)
+            .map(Some)
+            .map_err(|error| TradernetError::InvalidInput(format!("invalid file content: {error}")))
+    }
+
+    /// MIME type from the response, or from a `data:` URI prefix.
+    pub fn content_type(&self) -> Option<&str> {
+        self.mime_type.as_deref().or_else(|| {
+            self.content
+                .as_deref()?
+                .strip_prefix("data:")?
+                .split_once(";base64,")
+                .map(|(mime, _)| mime)
+        })
+    }
+
+    /// File name safe to create on disk: the original name without directories,
+    /// or `file-<id>` with an extension derived from the MIME type.
+    pub fn file_name(&self) -> String {
+        let original = self
+            .name
+            .as_deref()
+            .and_then(|name| name.rsplit(['/', '\\']).next())
+            .map(|name| {
+                name.chars()
+                    .filter(|c| {
+                        !c.is_control() && !matches!(c, ':' | '*' | '?' | '"' | '<' | '>' | '|')
+                    })
+                    .collect::<String>()
+            })
+            .filter(|name| !name.trim_matches('.').trim().is_empty());
+        original.unwrap_or_else(|| {
+            let stem = self
+                .id
+                .map_or_else(|| "file".to_string(), |id| format!("file-{id}"));
+            format!("{stem}.{}", extension(self.content_type()))
+        })
+    }
+
+    /// Writes inline content to `dir` under [`CpsFile::file_name`] and returns the path.
+    ///
+    /// Fails with [`TradernetError::InvalidInput`] when the file has no inline content;
+    /// linked files can be fetched from [`CpsFile::url`].
+    pub fn save_to(&self, dir: impl AsRef<Path>) -> Result<PathBuf, TradernetError> {
+        let path = dir.as_ref().join(self.file_name());
+        self.write(&path)?;
+        Ok(path)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), TradernetError> {
+        let bytes = self.bytes()?.ok_or_else(|| {
+            TradernetError::InvalidInput(format!(
+                "file {} has no inline content (url: {})",
+                self.file_name(),
+                self.url.as_deref().unwrap_or("none")
+            ))
+        })?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+/// Saves all files to `dir`, creating it if needed.
+///
+/// Duplicate names get a `-<n>` suffix before the extension.
+pub fn save_cps_files(
+    files: &[CpsFile],
+    dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, TradernetError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut used = HashSet::new();
+    let mut paths = Vec::with_capacity(files.len());
+    for file in files {
+        let name = file.file_name();
+        let mut candidate = name.clone();
+        let mut counter = 1;
+        while !used.insert(candidate.clone()) {
+            candidate = match name.rsplit_once('.') {
+                Some((stem, ext)) if !stem.is_empty() => format!("{stem}-{counter}.{ext}"),
+                _ => format!("{name}-{counter}"),
+            };
+            counter += 1;
+        }
+        let path = dir.join(candidate);
+        file.write(&path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn extension(mime_type: Option<&str>) -> &'static str {
+    match mime_type.map(|mime| mime.split(';').next().unwrap_or(mime).trim()) {
+        Some("application/pdf") => "pdf",
+        Some("image/png") => "png",
+        Some("image/jpeg") => "jpg",
+        Some("image/gif") => "gif",
+        Some("text/plain") => "txt",
+        Some("text/html") => "html",
+        Some("text/csv") => "csv",
+        Some("application/json") => "json",
+        Some("application/xml") | Some("text/xml") => "xml",
+        Some("application/zip") => "zip",
+        _ => "bin",
+    }
+}
+
+/// Parses raw `getClientCpsHistory` JSON into [`CpsHistory`] and surfaces
+/// method-level API errors.
+pub fn parse_cps_history_response(response: Value) -> Result<CpsHistory, TradernetError> {
+    check_error(&response)?;
+    let total = ["total", "count", "totalCount"]
+        .iter()
+        .find_map(|key| response.get(*key))
+        .and_then(|value| match value {
+            Value::Number(value) => value.as_i64(),
+            Value::String(value) => value.trim().parse().ok(),
+            _ => None,
+        });
+    let requests = rows(response, &["cps", "requests", "history", "result", "data"])
+        .into_iter()
+        .map(parse_request)
+        .collect();
+    Ok(CpsHistory { requests, total })
+}
+
+/// Parses raw `getCpsFiles` JSON into [`CpsFile`]s and surfaces method-level API errors.
+pub fn parse_cps_files_response(response: Value) -> Result<Vec<CpsFile>, TradernetError> {
+    check_error(&response)?;
+    Ok(rows(response, &["files", "attachments", "result", "data"])
+        .into_iter()
+        .map(parse_file)
+        .collect())
+}
+
+fn check_error(response: &Value) -> Result<(), TradernetError> {
+    match api_method_error(response) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Finds row objects in an array, under one of `containers`, or in an id-keyed map.
+///
+/// Only object or array values count as containers, so a row with a scalar field
+/// named like a container (e.g. inline file `data`) is kept as a row.
+fn rows(value: Value, containers: &[&str]) -> Vec<Map<String, Value>> {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Object(row) => Some(row),
+                other => {
+                    log::warn!("cps row expected object, got: {other}");
+                    None
+                }
+            })
+            .collect(),
+        Value::Object(mut map) => {
+            let container = containers.iter().find(|key| {
+                map.get(**key)
+                    .is_some_and(|inner| inner.is_object() || inner.is_array())
+            });
+            if let Some(inner) = container.and_then(|key| map.remove(*key)) {
+                return rows(inner, containers);
+            }
+            if map.values().all(Value::is_object) {
+                map.into_iter()
+                    .filter_map(|(_, row)| match row {
+                        Value::Object(row) => Some(row),
+                        _ => None,
+                    })
+                    .collect()
+            } else {
+                vec![map]
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn parse_request(mut row: Map<String, Value>) -> CpsRequest {
+    CpsRequest {
+        id: take_i64(&mut row, &["id", "exec_id"]),
+        doc_id: take_i64(&mut row, &["cpsDocId", "doc_id", "type_id"]),
+        name: take_string(&mut row, &["name", "title", "doc_name", "type_name"]),
+        status: take_i64(&mut row, &["cps_status", "status", "stat"]).map(CpsStatus::from),
+        created: take_datetime(
+            &mut row,
+            &["date_crt", "created_at", "date_created", "date"],
+        ),
+        updated: take_datetime(&mut row, &["date_upd", "updated_at", "date_updated"]),
+        comment: take_string(&mut row, &["comment", "reason"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn parse_file(mut row: Map<String, Value>) -> CpsFile {
+    CpsFile {
+        id: take_i64(&mut row, &["id", "file_id"]),
+        name: take_string(
+            &mut row,
+            &["name", "file_name", "filename", "original_name"],
+        ),
+        mime_type: take_string(&mut row, &["mime_type", "mime", "content_type", "type"]),
+        size: take_i64(&mut row, &["size", "file_size"]),
+        content: take_string(&mut row, &["content", "data", "body", "file", "base64"]),
+        url: take_string(&mut row, &["url", "link", "href"]),
+        extra: row.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CpsFile, CpsHistory, CpsHistoryFilters, CpsHistoryPager, CpsRequest, CpsStatus,
+        parse_cps_files_response, parse_cps_history_response, save_cps_files,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn cps_history_parses_requests_and_pages() {
+        let page = parse_cps_history_response(json!({
+            "cps": [
+                {"id": "7", "cpsDocId": 186, "name": "Withdrawal", "status": 3,
+                 "date_crt": "2026-01-02 10:00:00", "amount": "100"},
+                {"id": 8, "status": "9"}
+            ],
+            "total": 5
+        }))
+        .expect("history must parse");
+
+        assert_eq!(page.requests[0].id, Some(7));
+        assert_eq!(page.requests[0].status, Some(CpsStatus::Executed));
+        assert!(page.requests[0].created.is_some());
+        assert_eq!(page.requests[0].extra.get("amount"), Some(&json!("100")));
+        assert_eq!(page.requests[1].status, Some(CpsStatus::Other(9)));
+
+        let filters = CpsHistoryFilters::default().with_page_size(2);
+        let mut pager = CpsHistoryPager::new(filters.clone()).expect("valid filters");
+        assert_eq!(pager.absorb(page.clone()).len(), 2);
+        assert_eq!(pager.offset(), Some(2));
+        let last = CpsHistory {
+            requests: vec![
+                CpsRequest {
+                    id: Some(9),
+                    ..CpsRequest::default()
+                },
+                CpsRequest::default(),
+            ],
+            total: Some(4),
+        };
+        assert_eq!(pager.absorb(last).len(), 2);
+        assert_eq!(pager.offset(), None);
+
+        let mut repeating = CpsHistoryPager::new(filters).expect("valid filters");
+        repeating.absorb(page.clone());
+        assert!(repeating.absorb(page).is_empty());
+        assert_eq!(repeating.offset(), None);
+        assert!(CpsHistoryPager::new(CpsHistoryFilters::default().with_page_size(0)).is_err());
+        assert!(parse_cps_history_response(json!({"errMsg": "denied"})).is_err());
+    }
+
+    #[test]
+    fn cps_files_decode_and_save_with_original_names() {
+        let files = parse_cps_files_response(json!({"files": [
+            {"id": 1, "name": "../report.pdf", "mime_type": "application/pdf", "content": "JVBERg=="},
+            {"id": 2, "name": "report.pdf", "content": "data:application/pdf;base64,JVBERg=="},
+            {"id": 3, "content": "data:image/png;base64,iVBORw=="},
+            {"id": 4, "url": "https://example.com/doc.pdf"}
+        ]}))
+        .expect("files must parse");
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(
+            files[0].bytes().expect("valid base64"),
+            Some(b"%PDF".to_vec())
+        );
+        assert_eq!(files[1].content_type(), Some("application/pdf"));
+        assert_eq!(files[2].file_name(), "file-3.png");
+        assert_eq!(files[3].bytes().expect("no content"), None);
+
+        let dir = std::env::temp_dir().join(format!("tradernet-cps-{}", std::process::id()));
+        let paths = save_cps_files(&files[..3], &dir).expect("files must be saved");
+        assert_eq!(paths[0], dir.join("report.pdf"));
+        assert_eq!(paths[1], dir.join("report-1.pdf"));
+        assert_eq!(std::fs::read(&paths[1]).expect("saved file"), b"%PDF");
+        assert!(files[3].save_to(&dir).is_err());
+        assert!(
+            CpsFile {
+                content: Some("not base64!".to_string()),
+                ..CpsFile::default()
+            }
+            .bytes()
+            .is_err()
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn cps_files_keep_inline_data_field_as_content() {
+        let file = json!({"id": 1, "name": "a.pdf", "data": "JVBERg=="});
+
+        for response in [file.clone(), json!({"files": file})] {
+            let files = parse_cps_files_response(response).expect("files must parse");
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].name.as_deref(), Some("a.pdf"));
+            assert_eq!(
+                files[0].bytes().expect("valid base64"),
+                Some(b"%PDF".to_vec())
+            );
+        }
+    }
+}
@@ -36,9 +36,27 @@ pub enum TradernetError {
     /// URL parsing error.
     #[error("url parse error: {0}")]
     Url(#[from] Box<url::ParseError>),
+    /// Operation did not complete within the requested timeout.
+    #[error("timeout: {0}")]
+    Timeout(String),
+    /// WebSocket server rejected a subscription.
+    #[error("websocket subscription error: {0}")]
+    WsSubscription(Box<crate::ws_types::WsError>),
     /// Zip archive error.
     #[error("zip error: {0}")]
     Zip(#[from] Box<zip::result::ZipError>),
+    /// CSV export error.
+    #[cfg(feature = "csv")]
+    #[error("csv error: {0}")]
+    Csv(#[from] Box<csv::Error>),
+    /// Arrow conversion error.
+    #[cfg(feature = "arrow")]
+    #[error("arrow error: {0}")]
+    Arrow(#[from] Box<arrow_schema::ArrowError>),
+    /// Parquet export error.
+    #[cfg(feature = "parquet")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] Box<parquet::errors::ParquetError>),
 }
 
 impl From<reqwest::Error> for TradernetError {
@@ -76,3 +94,24 @@ impl From<zip::result::ZipError> for TradernetError {
         Self::Zip(Box::new(error))
     }
 }
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for TradernetError {
+    fn from(error: csv::Error) -> Self {
+        Self::Csv(Box::new(error))
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for TradernetError {
+    fn from(error: arrow_schema::ArrowError) -> Self {
+        Self::Arrow(Box::new(error))
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for TradernetError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        Self::Parquet(Box::new(error))
+    }
+}
@@ -0,0 +1,531 @@
+use crate::candles::SymbolCandles;
+use crate::common::json_fields::decimal_from_f64;
+#[cfg(any(feature = "csv", feature = "arrow"))]
+use crate::errors::TradernetError;
+use crate::trades_history::{TradeRow, TradesHistoryResponse, TypedTradeRow};
+use crate::user_data::{PortfolioPosition, PortfolioSummary};
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+
+/// Scale of decimal columns in Arrow output (`Decimal128(38, 10)`).
+pub const DECIMAL_SCALE: u32 = 10;
+
+/// Logical type of an exported column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Instant in Unix seconds, UTC.
+    TimestampUtc,
+    /// Wall-clock datetime without zone, as returned by the API.
+    DateTime,
+    /// Calendar date.
+    Date,
+    Int64,
+    Float64,
+    /// Exact decimal (prices and amounts).
+    Decimal,
+    Utf8,
+}
+
+/// Single exported value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Null,
+    /// Unix seconds for [`ColumnType::TimestampUtc`] columns.
+    Timestamp(i64),
+    DateTime(NaiveDateTime),
+    Date(NaiveDate),
+    Int(i64),
+    Float(f64),
+    Decimal(Decimal),
+    Text(String),
+}
+
+impl Cell {
+    fn opt_int(value: Option<i64>) -> Self {
+        value.map_or(Cell::Null, Cell::Int)
+    }
+
+    pub(crate) fn opt_text(value: Option<&str>) -> Self {
+        value.map_or(Cell::Null, |text| Cell::Text(text.to_string()))
+    }
+
+    fn opt_decimal_f64(value: Option<f64>) -> Self {
+        value
+            .and_then(decimal_from_f64)
+            .map_or(Cell::Null, Cell::Decimal)
+    }
+
+    pub(crate) fn opt_decimal(value: Option<Decimal>) -> Self {
+        value.map_or(Cell::Null, Cell::Decimal)
+    }
+}
+
+/// Column-oriented view of SDK data with stable column names and types.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    /// Column names and types.
+    pub columns: Vec<(&'static str, ColumnType)>,
+    /// Rows with one cell per column.
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// Conversion of SDK data into an exportable [`Table`].
+pub trait ToTable {
+    fn to_table(&self) -> Table;
+}
+
+const CANDLE_COLUMNS: [(&str, ColumnType); 7] = [
+    ("symbol", ColumnType::Utf8),
+    ("ts", ColumnType::TimestampUtc),
+    ("open", ColumnType::Float64),
+    ("high", ColumnType::Float64),
+    ("low", ColumnType::Float64),
+    ("close", ColumnType::Float64),
+    ("volume", ColumnType::Int64),
+];
+
+impl ToTable for SymbolCandles {
+    fn to_table(&self) -> Table {
+        let rows = self
+            .items
+            .iter()
+            .map(|item| {
+                vec![
+                    Cell::Text(self.symbol.clone()),
+                    Cell::Timestamp(item.ts),
+                    Cell::Float(item.open),
+                    Cell::Float(item.high),
+                    Cell::Float(item.low),
+                    Cell::Float(item.close),
+                    Cell::Int(item.volume),
+                ]
+            })
+            .collect();
+        Table {
+            columns: CANDLE_COLUMNS.to_vec(),
+            rows,
+        }
+    }
+}
+
+const TRADE_COLUMNS: [(&str, ColumnType); 16] = [
+    ("id", ColumnType::Int64),
+    ("order_id", ColumnType::Int64),
+    ("date", ColumnType::DateTime),
+    ("symbol", ColumnType::Utf8),
+    ("type", ColumnType::Int64),
+    ("price", ColumnType::Decimal),
+    ("quantity", ColumnType::Decimal),
+    ("amount", ColumnType::Decimal),
+    ("currency", ColumnType::Utf8),
+    ("profit", ColumnType::Decimal),
+    ("commission", ColumnType::Decimal),
+    ("settlement_date", ColumnType::Date),
+    ("exchange_date", ColumnType::Date),
+    ("market_id", ColumnType::Int64),
+    ("instrument_id", ColumnType::Int64),
+    ("comment", ColumnType::Utf8),
+];
+
+impl ToTable for [TypedTradeRow] {
+    fn to_table(&self) -> Table {
+        let rows = self
+            .iter()
+            .map(|trade| {
+                vec![
+                    Cell::opt_int(trade.id),
+                    Cell::opt_int(trade.order_id),
+                    trade.date.map_or(Cell::Null, Cell::DateTime),
+                    Cell::opt_text(trade.instr_nm.as_deref()),
+                    Cell::opt_int(trade.trade_type),
+                    Cell::opt_decimal(trade.p),
+                    Cell::opt_decimal(trade.q),
+                    Cell::opt_decimal(trade.v.or(trade.summ)),
+                    Cell::opt_text(trade.curr_c.as_deref()),
+                    Cell::opt_decimal(trade.profit),
+                    Cell::opt_decimal(trade.commiss_exchange),
+                    trade.pay_d.map_or(Cell::Null, Cell::Date),
+                    trade.trade_d_exch.map_or(Cell::Null, Cell::Date),
+                    Cell::opt_int(trade.mkt_id),
+                    Cell::opt_int(trade.instr_id),
+                    Cell::opt_text(trade.comment.as_deref()),
+                ]
+            })
+            .collect();
+        Table {
+            columns: TRADE_COLUMNS.to_vec(),
+            rows,
+        }
+    }
+}
+
+impl ToTable for [TradeRow] {
+    fn to_table(&self) -> Table {
+        self.iter()
+            .map(TypedTradeRow::from)
+            .collect::<Vec<_>>()
+            .to_table()
+    }
+}
+
+impl ToTable for TradesHistoryResponse {
+    fn to_table(&self) -> Table {
+        self.trades.trade.as_slice().to_table()
+    }
+}
+
+const POSITION_COLUMNS: [(&str, ColumnType); 14] = [
+    ("symbol", ColumnType::Utf8),
+    ("name", ColumnType::Utf8),
+    ("currency", ColumnType::Utf8),
+    ("instrument_type", ColumnType::Int64),
+    ("quantity", ColumnType::Int64),
+    ("open_balance", ColumnType::Decimal),
+    ("average_price", ColumnType::Decimal),
+    ("market_price", ColumnType::Decimal),
+    ("close_price", ColumnType::Decimal),
+    ("market_value", ColumnType::Decimal),
+    ("profit_close", ColumnType::Decimal),
+    ("profit_price", ColumnType::Decimal),
+    ("currency_rate", ColumnType::Decimal),
+    ("instrument_id", ColumnType::Int64),
+];
+
+impl ToTable for [PortfolioPosition] {
+    fn to_table(&self) -> Table {
+        let rows = self
+            .iter()
+            .map(|position| {
+                vec![
+                    Cell::Text(position.i.clone()),
+                    Cell::Text(position.name.clone()),
+                    Cell::Text(position.curr.clone()),
+                    Cell::Int(position.t),
+                    Cell::Int(position.q),
+                    Cell::opt_decimal_f64(Some(position.open_bal)),
+                    Cell::opt_decimal_f64(Some(position.bal_price_a)),
+                    Cell::opt_decimal_f64(Some(position.mkt_price)),
+                    Cell::opt_decimal_f64(Some(position.close_price)),
+                    Cell::opt_decimal_f64(Some(position.market_value)),
+                    Cell::opt_decimal_f64(Some(position.profit_close)),
+                    Cell::opt_decimal_f64(Some(position.profit_price)),
+                    Cell::opt_decimal_f64(Some(position.currval)),
+                    Cell::opt_int(position.instr_id),
+                ]
+            })
+            .collect();
+        Table {
+            columns: POSITION_COLUMNS.to_vec(),
+            rows,
+        }
+    }
+}
+
+impl ToTable for PortfolioSummary {
+    fn to_table(&self) -> Table {
+        self.pos.as_slice().to_table()
+    }
+}
+
+#[cfg(feature = "csv")]
+fn csv_field(cell: &Cell) -> String {
+    match cell {
+        Cell::Null => String::new(),
+        Cell::Timestamp(ts) => chrono::DateTime::from_timestamp(*ts, 0)
+            .map(|utc| utc.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .unwrap_or_default(),
+        Cell::DateTime(datetime) => datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        Cell::Date(date) => date.format("%Y-%m-%d").to_string(),
+        Cell::Int(value) => value.to_string(),
+        Cell::Float(value) => value.to_string(),
+        Cell::Decimal(value) => value.normalize().to_string(),
+        Cell::Text(value) => value.clone(),
+    }
+}
+
+/// Writes a table as CSV with a header row.
+///
+/// Timestamps are ISO 8601 (`Z` suffix for UTC columns), decimals are written
+/// without trailing zeros and nulls as empty fields.
+#[cfg(feature = "csv")]
+pub fn write_csv<W: std::io::Write>(table: &Table, writer: W) -> Result<(), TradernetError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(table.columns.iter().map(|(name, _)| *name))?;
+    for row in &table.rows {
+        writer.write_record(row.iter().map(csv_field))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts a table into an Arrow record batch.
+///
+/// Column types: UTC timestamps as `Timestamp(Second, "UTC")`, API datetimes as
+/// `Timestamp(Second, None)`, dates as `Date32` and decimals as
+/// `Decimal128(38, DECIMAL_SCALE)`. Cells that do not match the column type are
+/// written as null.
+#[cfg(feature = "arrow")]
+pub fn to_record_batch(table: &Table) -> Result<arrow_array::RecordBatch, TradernetError> {
+    use arrow_array::{
+        ArrayRef, Date32Array, Decimal128Array, Float64Array, Int64Array, RecordBatch, StringArray,
+        TimestampSecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch date");
+    let mut fields = Vec::with_capacity(table.columns.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(table.columns.len());
+
+    for (index, (name, column_type)) in table.columns.iter().enumerate() {
+        let cells = table.rows.iter().map(|row| row.get(index));
+        let (data_type, array): (DataType, ArrayRef) = match column_type {
+            ColumnType::TimestampUtc => (
+                DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Timestamp(ts)) => Some(*ts),
+                            _ => None,
+                        })
+                        .collect::<TimestampSecondArray>()
+                        .with_timezone("UTC"),
+                ),
+            ),
+            ColumnType::DateTime => (
+                DataType::Timestamp(TimeUnit::Second, None),
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::DateTime(datetime)) => Some(datetime.and_utc().timestamp()),
+                            _ => None,
+                        })
+                        .collect::<TimestampSecondArray>(),
+                ),
+            ),
+            ColumnType::Date => (
+                DataType::Date32,
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Date(date)) => {
+                                i32::try_from((*date - epoch).num_days()).ok()
+                            }
+                            _ => None,
+                        })
+                        .collect::<Date32Array>(),
+                ),
+            ),
+            ColumnType::Int64 => (
+                DataType::Int64,
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Int(value)) => Some(*value),
+                            _ => None,
+                        })
+                        .collect::<Int64Array>(),
+                ),
+            ),
+            ColumnType::Float64 => (
+                DataType::Float64,
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Float(value)) => Some(*value),
+                            _ => None,
+                        })
+                        .collect::<Float64Array>(),
+                ),
+            ),
+            ColumnType::Decimal => (
+                DataType::Decimal128(38, DECIMAL_SCALE as i8),
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Decimal(value)) => {
+                                let mut value = *value;
+                                value.rescale(DECIMAL_SCALE);
+                                (value.scale() == DECIMAL_SCALE).then(|| value.mantissa())
+                            }
+                            _ => None,
+                        })
+                        .collect::<Decimal128Array>()
+                        .with_precision_and_scale(38, DECIMAL_SCALE as i8)?,
+                ),
+            ),
+            ColumnType::Utf8 => (
+                DataType::Utf8,
+                Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Some(Cell::Text(value)) => Some(value.as_str()),
+                            _ => None,
+                        })
+                        .collect::<StringArray>(),
+                ),
+            ),
+        };
+        fields.push(Field::new(*name, data_type, true));
+        arrays.push(array);
+    }
+
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+}
+
+/// Writes a table as a Parquet file using the schema of [`to_record_batch`].
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W>(table: &Table, writer: W) -> Result<(), TradernetError>
+where
+    W: std::io::Write + Send,
+{
+    let batch = to_record_batch(table)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, ColumnType, ToTable};
+    use crate::candles::{CandlePoint, SymbolCandles};
+    use crate::trades_history::TradeRow;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn candles() -> SymbolCandles {
+        SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![CandlePoint {
+                ts: 1_704_067_200,
+                open: 1.5,
+                high: 2.0,
+                low: 1.0,
+                close: 1.75,
+                volume: 10,
+            }],
+        }
+    }
+
+    fn trade() -> TradeRow {
+        TradeRow {
+            id: Some(7),
+            p: Some(191.1),
+            q: Some(3.0),
+            v: Some(573.3),
+            date: Some("2024-02-01T10:20:30".to_string()),
+            instr_nm: Some("AAPL.US".to_string()),
+            profit: Some("12.5".to_string()),
+            pay_d: Some("2024-02-05".to_string()),
+            ..TradeRow::default()
+        }
+    }
+
+    #[test]
+    fn candle_table_has_stable_columns() {
+        let table = candles().to_table();
+
+        assert_eq!(
+            table
+                .columns
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            ["symbol", "ts", "open", "high", "low", "close", "volume"]
+        );
+        assert_eq!(table.rows[0][1], Cell::Timestamp(1_704_067_200));
+    }
+
+    #[test]
+    fn trades_table_has_typed_cells() {
+        let table = [trade()].to_table();
+        let column = |name: &str| {
+            table
+                .columns
+                .iter()
+                .position(|(column, _)| *column == name)
+                .expect("column must exist")
+        };
+        let row = &table.rows[0];
+
+        assert_eq!(table.columns[column("price")].1, ColumnType::Decimal);
+        assert_eq!(
+            row[column("price")],
+            Cell::Decimal(Decimal::from_str("191.1").expect("valid decimal"))
+        );
+        assert_eq!(
+            row[column("date")],
+            Cell::DateTime(
+                NaiveDate::from_ymd_opt(2024, 2, 1)
+                    .and_then(|date| date.and_hms_opt(10, 20, 30))
+                    .expect("valid datetime")
+            )
+        );
+        assert_eq!(
+            row[column("settlement_date")],
+            Cell::Date(NaiveDate::from_ymd_opt(2024, 2, 5).expect("valid date"))
+        );
+        assert_eq!(row[column("commission")], Cell::Null);
+        assert!(
+            table
+                .rows
+                .iter()
+                .all(|row| row.len() == table.columns.len())
+        );
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_uses_stable_header_and_iso_timestamps() {
+        let mut output = Vec::new();
+        super::write_csv(&candles().to_table(), &mut output).expect("csv export");
+
+        assert_eq!(
+            String::from_utf8(output).expect("utf-8 csv"),
+            "symbol,ts,open,high,low,close,volume\nAAPL.US,2024-01-01T00:00:00Z,1.5,2,1,1.75,10\n"
+        );
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn record_batch_uses_typed_columns() {
+        use arrow_array::{Array, Decimal128Array};
+        use arrow_schema::{DataType, TimeUnit};
+
+        let batch = super::to_record_batch(&[trade()].to_table()).expect("record batch");
+        let schema = batch.schema();
+
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(
+            schema.field_with_name("date").expect("date").data_type(),
+            &DataType::Timestamp(TimeUnit::Second, None)
+        );
+        let price = batch
+            .column_by_name("price")
+            .and_then(|column| column.as_any().downcast_ref::<Decimal128Array>())
+            .expect("decimal price column");
+        assert_eq!(price.value_as_string(0), "191.1000000000");
+        assert!(
+            batch
+                .column_by_name("commission")
+                .expect("commission")
+                .is_null(0)
+        );
+
+        let candles = super::to_record_batch(&candles().to_table()).expect("record batch");
+        assert_eq!(
+            candles.schema().field(1).data_type(),
+            &DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_writes_non_empty_file() {
+        let mut output = Vec::new();
+        super::write_parquet(&candles().to_table(), &mut output).expect("parquet export");
+        assert!(output.starts_with(b"PAR1"));
+    }
+}
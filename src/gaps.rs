@@ -0,0 +1,512 @@
+use crate::candles::{CandlePoint, CandlesResponse, SymbolCandles, Timeframe};
+use crate::errors::TradernetError;
+use crate::market_time::API_TIMEZONE;
+use crate::user_data::Market;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Trading hours used to decide which bars are expected.
+///
+/// Intraday bars are expected when their start falls into the session on a
+/// trading day. Daily bars are expected on every trading day; a daily bar
+/// belongs to its date in [`API_TIMEZONE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingCalendar {
+    zone: Tz,
+    open: NaiveTime,
+    close: NaiveTime,
+    weekdays: HashSet<Weekday>,
+    holidays: HashSet<NaiveDate>,
+}
+
+impl Default for TradingCalendar {
+    fn default() -> Self {
+        Self::always_open()
+    }
+}
+
+impl TradingCalendar {
+    /// Creates a calendar with a daily session in `zone`, Monday to Friday.
+    ///
+    /// When `close` is earlier than `open`, the session spans midnight.
+    pub fn new(zone: Tz, open: NaiveTime, close: NaiveTime) -> Self {
+        Self {
+            zone,
+            open,
+            close,
+            weekdays: [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]
+            .into_iter()
+            .collect(),
+            holidays: HashSet::new(),
+        }
+    }
+
+    /// Creates a calendar trading around the clock every day (e.g. crypto, FX).
+    pub fn always_open() -> Self {
+        Self {
+            zone: chrono_tz::UTC,
+            open: NaiveTime::MIN,
+            close: NaiveTime::MIN,
+            weekdays: [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]
+            .into_iter()
+            .collect(),
+            holidays: HashSet::new(),
+        }
+    }
+
+    /// Builds a calendar from a `getMarketStatus` market entry.
+    ///
+    /// Session times `o`/`c` are read as [`API_TIMEZONE`] wall-clock times and
+    /// `date` entries with `dayoff = 1` become holidays.
+    pub fn from_market(market: &Market) -> Result<Self, TradernetError> {
+        let open = parse_session_time(&market.o)?;
+        let close = parse_session_time(&market.c)?;
+        let mut calendar = Self::new(API_TIMEZONE, open, close);
+        for day in market.date.iter().flatten() {
+            if day.dayoff != 1 {
+                continue;
+            }
+            let (Some(from), Some(to)) = (parse_date(&day.from), parse_date(&day.to)) else {
+                log::warn!(
+                    "unparseable market holiday skipped: {} - {}",
+                    day.from,
+                    day.to
+                );
+                continue;
+            };
+            for date in from.iter_days().take_while(|date| *date <= to) {
+                calendar.holidays.insert(date);
+            }
+        }
+        Ok(calendar)
+    }
+
+    /// Sets trading weekdays.
+    pub fn with_weekdays<I>(mut self, weekdays: I) -> Self
+    where
+        I: IntoIterator<Item = Weekday>,
+    {
+        self.weekdays = weekdays.into_iter().collect();
+        self
+    }
+
+    /// Adds non-trading dates.
+    pub fn with_holidays<I>(mut self, holidays: I) -> Self
+    where
+        I: IntoIterator<Item = NaiveDate>,
+    {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    /// Returns `true` when `date` is a trading day.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.weekdays.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Returns `true` when a bar of `timeframe` starting at `ts` is expected.
+    pub fn expects_bar(&self, ts: i64, timeframe: Timeframe) -> bool {
+        let Some(utc) = DateTime::from_timestamp(ts, 0) else {
+            return false;
+        };
+        if timeframe == Timeframe::Day1 {
+            return self.is_trading_day(utc.with_timezone(&API_TIMEZONE).date_naive());
+        }
+
+        let local = utc.with_timezone(&self.zone);
+        let time = local.time();
+        let date = local.date_naive();
+        if self.open == self.close {
+            return self.is_trading_day(date);
+        }
+        if self.open < self.close {
+            return self.is_trading_day(date) && time >= self.open && time < self.close;
+        }
+        // Overnight session: the part after midnight belongs to the previous day.
+        if time >= self.open {
+            self.is_trading_day(date)
+        } else {
+            time < self.close && date.pred_opt().is_some_and(|day| self.is_trading_day(day))
+        }
+    }
+}
+
+/// Missing bars between two present bars.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct CandleGap {
+    /// Timestamp of the first missing bar.
+    pub start: i64,
+    /// Timestamp of the last missing bar.
+    pub end: i64,
+    /// Number of missing bars.
+    pub missing: usize,
+}
+
+/// How [`SymbolCandles::fill_gaps`] treats missing bars.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum FillStrategy {
+    /// Insert flat bars at the previous close with zero volume.
+    #[default]
+    ForwardFill,
+    /// Keep gaps as they are.
+    Leave,
+}
+
+/// OHLC consistency problem of a single bar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OhlcIssue {
+    /// A price is NaN or infinite.
+    NonFinite,
+    /// `high` is below `low`.
+    HighBelowLow,
+    /// `open` is outside `low..=high`.
+    OpenOutsideRange,
+    /// `close` is outside `low..=high`.
+    CloseOutsideRange,
+    /// `volume` is negative.
+    NegativeVolume,
+}
+
+/// Bar with an OHLC inconsistency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InvalidBar {
+    /// Index of the bar in the series.
+    pub index: usize,
+    /// Bar timestamp.
+    pub ts: i64,
+    pub issue: OhlcIssue,
+}
+
+/// Lengths of the per-symbol arrays in a `getHloc` response.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct SeriesLengths {
+    pub x_series: usize,
+    pub hloc: usize,
+    pub vl: usize,
+}
+
+/// Result of [`SymbolCandles::validate`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Indices of bars whose timestamp is earlier than the previous one.
+    pub out_of_order: Vec<usize>,
+    /// Timestamps present more than once.
+    pub duplicates: Vec<i64>,
+    /// Bars with inconsistent OHLC values.
+    pub invalid_bars: Vec<InvalidBar>,
+    /// Source array lengths when they differ and bars were truncated.
+    pub length_mismatch: Option<SeriesLengths>,
+}
+
+impl ValidationReport {
+    /// Returns `true` when no problem was found.
+    pub fn is_clean(&self) -> bool {
+        self.out_of_order.is_empty()
+            && self.duplicates.is_empty()
+            && self.invalid_bars.is_empty()
+            && self.length_mismatch.is_none()
+    }
+}
+
+impl SymbolCandles {
+    /// Lists missing bars between present bars, skipping closed market hours.
+    ///
+    /// Bars are expected on the `timeframe` grid starting from each present bar.
+    /// Gaps before the first or after the last bar are not reported.
+    pub fn gaps(&self, timeframe: Timeframe, calendar: &TradingCalendar) -> Vec<CandleGap> {
+        let mut timestamps = self.items.iter().map(|item| item.ts).collect::<Vec<_>>();
+        timestamps.sort_unstable();
+        timestamps.dedup();
+
+        let mut gaps = Vec::new();
+        for pair in timestamps.windows(2) {
+            let mut current: Option<CandleGap> = None;
+            for ts in expected_between(pair[0], pair[1], timeframe) {
+                if calendar.expects_bar(ts, timeframe) {
+                    match current.as_mut() {
+                        Some(gap) => {
+                            gap.end = ts;
+                            gap.missing += 1;
+                        }
+                        None => {
+                            current = Some(CandleGap {
+                                start: ts,
+                                end: ts,
+                                missing: 1,
+                            })
+                        }
+                    }
+                } else if let Some(gap) = current.take() {
+                    gaps.push(gap);
+                }
+            }
+            gaps.extend(current);
+        }
+        gaps
+    }
+
+    /// Returns a sorted, deduplicated copy of the series with gaps handled by `strategy`.
+    pub fn fill_gaps(
+        &self,
+        timeframe: Timeframe,
+        calendar: &TradingCalendar,
+        strategy: FillStrategy,
+    ) -> SymbolCandles {
+        let mut filled = SymbolCandles {
+            symbol: self.symbol.clone(),
+            items: Vec::new(),
+        };
+        filled.merge(self.clone());
+        if strategy == FillStrategy::Leave {
+            return filled;
+        }
+
+        let mut items: Vec<CandlePoint> = Vec::with_capacity(filled.items.len());
+        for item in filled.items {
+            if let Some(previous) = items.last().cloned() {
+                for ts in expected_between(previous.ts, item.ts, timeframe) {
+                    if calendar.expects_bar(ts, timeframe) {
+                        items.push(CandlePoint {
+                            ts,
+                            open: previous.close,
+                            high: previous.close,
+                            low: previous.close,
+                            close: previous.close,
+                            volume: 0,
+                        });
+                    }
+                }
+            }
+            items.push(item);
+        }
+        SymbolCandles {
+            symbol: filled.symbol,
+            items,
+        }
+    }
+
+    /// Reports out-of-order timestamps, duplicates and OHLC inconsistencies.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut seen = HashMap::new();
+
+        for (index, item) in self.items.iter().enumerate() {
+            if index > 0 && item.ts < self.items[index - 1].ts {
+                report.out_of_order.push(index);
+            }
+            let count = seen.entry(item.ts).or_insert(0usize);
+            *count += 1;
+            if *count == 2 {
+                report.duplicates.push(item.ts);
+            }
+            if let Some(issue) = ohlc_issue(item) {
+                report.invalid_bars.push(InvalidBar {
+                    index,
+                    ts: item.ts,
+                    issue,
+                });
+            }
+        }
+        report
+    }
+}
+
+impl CandlesResponse {
+    /// Validates the series of `symbol`, including source array length mismatches
+    /// that [`CandlesResponse::series_for_symbol`] silently truncates.
+    pub fn validate_symbol(&self, symbol: &str) -> ValidationReport {
+        let lengths = SeriesLengths {
+            x_series: self.x_series.get(symbol).map_or(0, Vec::len),
+            hloc: self.hloc.get(symbol).map_or(0, Vec::len),
+            vl: self.vl.get(symbol).map_or(0, Vec::len),
+        };
+        let mut report = self.series_for_symbol(symbol).validate();
+        if lengths.x_series != lengths.hloc || lengths.x_series != lengths.vl {
+            report.length_mismatch = Some(lengths);
+        }
+        report
+    }
+}
+
+/// Yields grid timestamps strictly between `from` and `to`.
+fn expected_between(from: i64, to: i64, timeframe: Timeframe) -> impl Iterator<Item = i64> {
+    let step = timeframe.seconds();
+    (1..)
+        .map(move |index| from + index * step)
+        .take_while(move |ts| *ts < to)
+}
+
+fn ohlc_issue(item: &CandlePoint) -> Option<OhlcIssue> {
+    let prices = [item.open, item.high, item.low, item.close];
+    if prices.iter().any(|price| !price.is_finite()) {
+        return Some(OhlcIssue::NonFinite);
+    }
+    if item.high < item.low {
+        return Some(OhlcIssue::HighBelowLow);
+    }
+    if item.open < item.low || item.open > item.high {
+        return Some(OhlcIssue::OpenOutsideRange);
+    }
+    if item.close < item.low || item.close > item.high {
+        return Some(OhlcIssue::CloseOutsideRange);
+    }
+    if item.volume < 0 {
+        return Some(OhlcIssue::NegativeVolume);
+    }
+    None
+}
+
+fn parse_session_time(value: &str) -> Result<NaiveTime, TradernetError> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .map_err(|_| TradernetError::InvalidInput(format!("invalid session time: {value:?}")))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let date = value.get(..10).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FillStrategy, OhlcIssue, TradingCalendar};
+    use crate::candles::{CandlePoint, SymbolCandles, Timeframe, parse_candles_response};
+    use crate::user_data::Market;
+    use chrono::{NaiveDate, NaiveTime};
+    use serde_json::json;
+
+    fn bar(ts: i64, close: f64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+        }
+    }
+
+    fn series(items: Vec<CandlePoint>) -> SymbolCandles {
+        SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items,
+        }
+    }
+
+    fn new_york() -> TradingCalendar {
+        TradingCalendar::new(
+            chrono_tz::America::New_York,
+            NaiveTime::from_hms_opt(9, 30, 0).expect("valid time"),
+            NaiveTime::from_hms_opt(16, 0, 0).expect("valid time"),
+        )
+    }
+
+    // 2024-01-05 (Friday) 15:55 New York, 2024-01-08 (Monday) 09:30 New York.
+    const FRIDAY_LAST: i64 = 1_704_488_100;
+    const MONDAY_OPEN: i64 = 1_704_724_200;
+
+    #[test]
+    fn gaps_skip_closed_hours_and_weekends() {
+        let candles = series(vec![
+            bar(FRIDAY_LAST - 600, 1.0),
+            bar(FRIDAY_LAST, 2.0),
+            bar(MONDAY_OPEN, 3.0),
+            bar(MONDAY_OPEN + 900, 4.0),
+        ]);
+
+        let gaps = candles.gaps(Timeframe::Minute5, &new_york());
+
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(
+            (gaps[0].start, gaps[0].end, gaps[0].missing),
+            (FRIDAY_LAST - 300, FRIDAY_LAST - 300, 1)
+        );
+        assert_eq!((gaps[1].start, gaps[1].missing), (MONDAY_OPEN + 300, 2));
+        assert_eq!(
+            candles
+                .gaps(Timeframe::Minute5, &TradingCalendar::always_open())
+                .iter()
+                .map(|gap| gap.missing)
+                .sum::<usize>(),
+            (MONDAY_OPEN - FRIDAY_LAST) as usize / 300 + 2
+        );
+    }
+
+    #[test]
+    fn forward_fill_inserts_flat_bars_with_zero_volume() {
+        let candles = series(vec![bar(FRIDAY_LAST - 600, 1.0), bar(FRIDAY_LAST, 2.0)]);
+
+        let filled = candles.fill_gaps(Timeframe::Minute5, &new_york(), FillStrategy::ForwardFill);
+        assert_eq!(filled.items.len(), 3);
+        assert_eq!(filled.items[1].ts, FRIDAY_LAST - 300);
+        assert_eq!(filled.items[1].close, 1.0);
+        assert_eq!(filled.items[1].volume, 0);
+
+        let left = candles.fill_gaps(Timeframe::Minute5, &new_york(), FillStrategy::Leave);
+        assert_eq!(left.items.len(), 2);
+    }
+
+    #[test]
+    fn market_holidays_are_not_expected() {
+        let market: Market = serde_json::from_value(json!({
+            "n": "NYSE", "n2": "NYSE", "s": "CLOSE", "o": "17:30:00", "c": "00:00:00", "dt": "0",
+            "date": [{"from": "2024-01-01", "to": "2024-01-01", "dayoff": 1, "desc": "New Year"}]
+        }))
+        .expect("market must parse");
+        let calendar = TradingCalendar::from_market(&market).expect("valid calendar");
+
+        assert!(!calendar.is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date")));
+        assert!(calendar.is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 2).expect("valid date")));
+        // 2024-01-02 18:00 MSK is inside the session.
+        assert!(calendar.expects_bar(1_704_207_600, Timeframe::Minute1));
+        // 2024-01-02 12:00 MSK is before the open.
+        assert!(!calendar.expects_bar(1_704_186_000, Timeframe::Minute1));
+    }
+
+    #[test]
+    fn validation_reports_order_duplicates_and_ohlc_issues() {
+        let mut broken = bar(120, 1.0);
+        broken.high = 0.5;
+        let candles = series(vec![bar(60, 1.0), bar(0, 1.0), bar(60, 1.0), broken]);
+
+        let report = candles.validate();
+
+        assert_eq!(report.out_of_order, vec![1]);
+        assert_eq!(report.duplicates, vec![60]);
+        assert_eq!(report.invalid_bars.len(), 1);
+        assert_eq!(report.invalid_bars[0].issue, OhlcIssue::HighBelowLow);
+        assert!(!report.is_clean());
+        assert!(
+            series(vec![bar(0, 1.0), bar(60, 1.0)])
+                .validate()
+                .is_clean()
+        );
+
+        let response = parse_candles_response(json!({
+            "hloc": {"AAPL.US": [[1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 1.0]]},
+            "vl": {"AAPL.US": [1]},
+            "xSeries": {"AAPL.US": [0, 60]}
+        }))
+        .expect("must parse candles");
+        let report = response.validate_symbol("AAPL.US");
+        assert_eq!(report.length_mismatch.map(|lengths| lengths.vl), Some(1));
+    }
+}
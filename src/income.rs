@@ -0,0 +1,491 @@
+use crate::broker_report::{BrokerReport, ReportCorporateAction};
+use crate::errors::TradernetError;
+use crate::export::{Cell, ColumnType, Table, ToTable};
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Kind of an income statement line.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IncomeKind {
+    Dividend,
+    Coupon,
+    /// Tax withheld by the broker or at source.
+    WithholdingTax,
+    /// Non-trading commissions and fees.
+    Fee,
+}
+
+impl IncomeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            IncomeKind::Dividend => "dividend",
+            IncomeKind::Coupon => "coupon",
+            IncomeKind::WithholdingTax => "withholding_tax",
+            IncomeKind::Fee => "fee",
+        }
+    }
+}
+
+/// Single income, tax or fee line.
+///
+/// Income is positive, taxes and fees are negative.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IncomeEvent {
+    pub date: NaiveDate,
+    pub kind: IncomeKind,
+    pub symbol: Option<String>,
+    pub isin: Option<String>,
+    pub currency: String,
+    pub amount: Decimal,
+    /// Amount in [`IncomeReport::base_currency`] after FX conversion.
+    pub base_amount: Option<Decimal>,
+    pub comment: Option<String>,
+}
+
+/// Reporting period used to group income.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum IncomePeriod {
+    Month,
+    Quarter,
+    #[default]
+    Year,
+}
+
+impl IncomePeriod {
+    /// Returns the first day of the period containing `date`.
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        let month = match self {
+            IncomePeriod::Month => date.month(),
+            IncomePeriod::Quarter => (date.month0() / 3) * 3 + 1,
+            IncomePeriod::Year => 1,
+        };
+        NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
+    }
+}
+
+/// Source of FX rates for converting income into a base currency.
+pub trait FxRates {
+    /// Units of the base currency per unit of `currency` on `date`.
+    fn rate(&self, currency: &str, date: NaiveDate) -> Option<Decimal>;
+}
+
+/// In-memory FX rates with optional daily fixings.
+///
+/// A daily rate applies from its date until the next fixing; the fixed rate set by
+/// [`FxTable::with_rate`] is used before the first fixing or when there is none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FxTable {
+    fixed: HashMap<String, Decimal>,
+    daily: HashMap<String, BTreeMap<NaiveDate, Decimal>>,
+}
+
+impl FxTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a rate used for every date.
+    pub fn with_rate(mut self, currency: impl Into<String>, rate: Decimal) -> Self {
+        self.fixed.insert(currency.into(), rate);
+        self
+    }
+
+    /// Adds a fixing effective from `date`.
+    pub fn with_daily_rate(
+        mut self,
+        currency: impl Into<String>,
+        date: NaiveDate,
+        rate: Decimal,
+    ) -> Self {
+        self.daily
+            .entry(currency.into())
+            .or_default()
+            .insert(date, rate);
+        self
+    }
+}
+
+impl FxRates for FxTable {
+    fn rate(&self, currency: &str, date: NaiveDate) -> Option<Decimal> {
+        self.daily
+            .get(currency)
+            .and_then(|fixings| fixings.range(..=date).next_back())
+            .map(|(_, rate)| *rate)
+            .or_else(|| self.fixed.get(currency).copied())
+    }
+}
+
+/// Income totals for one group of [`IncomeEvent`]s.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct IncomeSummary {
+    /// First day of the period, for [`IncomeReport::by_period`].
+    pub period_start: Option<NaiveDate>,
+    /// Instrument, for [`IncomeReport::by_instrument`].
+    pub symbol: Option<String>,
+    pub currency: String,
+    pub dividends: Decimal,
+    pub coupons: Decimal,
+    /// Withheld taxes (negative).
+    pub withholding_tax: Decimal,
+    /// Fees (negative).
+    pub fees: Decimal,
+    pub net: Decimal,
+    /// Net amount in the base currency when every event of the group was converted.
+    pub base_net: Option<Decimal>,
+}
+
+/// Dividend, coupon, withholding tax and fee lines extracted from broker reports.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct IncomeReport {
+    /// Currency of [`IncomeEvent::base_amount`], set by [`IncomeReport::convert`].
+    pub base_currency: Option<String>,
+    /// Events ordered by date.
+    pub events: Vec<IncomeEvent>,
+}
+
+impl IncomeReport {
+    /// Extracts income from the corporate actions and commissions blocks of a report.
+    ///
+    /// Rows without a date, currency or amount are skipped with a warning.
+    pub fn from_broker_report(report: &BrokerReport) -> Self {
+        Self::from_broker_reports(std::slice::from_ref(report))
+    }
+
+    /// Extracts income from several reports, e.g. one per year.
+    pub fn from_broker_reports(reports: &[BrokerReport]) -> Self {
+        let mut events = Vec::new();
+        for report in reports {
+            for action in &report.corporate_actions {
+                push_corporate_action(&mut events, action);
+            }
+            for commission in &report.commissions {
+                let date = commission.date.map(|date| date.date());
+                match (date, commission.currency.as_ref(), commission.amount) {
+                    (Some(date), Some(currency), Some(amount)) if !amount.is_zero() => {
+                        events.push(IncomeEvent {
+                            date,
+                            kind: IncomeKind::Fee,
+                            symbol: None,
+                            isin: None,
+                            currency: currency.clone(),
+                            amount: -amount.abs(),
+                            base_amount: None,
+                            comment: commission.comment.clone(),
+                        });
+                    }
+                    (_, _, Some(amount)) if amount.is_zero() => {}
+                    _ => log::warn!("skipping incomplete commission row: {commission:?}"),
+                }
+            }
+        }
+        events.sort_by_key(|event| event.date);
+        Self {
+            base_currency: None,
+            events,
+        }
+    }
+
+    /// Keeps events dated within `start..=end`.
+    pub fn between(mut self, start: NaiveDate, end: NaiveDate) -> Self {
+        self.events
+            .retain(|event| event.date >= start && event.date <= end);
+        self
+    }
+
+    /// Converts every event into `base` using `rates`.
+    ///
+    /// Events already in `base` use a rate of 1. Fails with
+    /// [`TradernetError::InvalidInput`] when a rate is missing.
+    pub fn convert(
+        mut self,
+        base: impl Into<String>,
+        rates: &impl FxRates,
+    ) -> Result<Self, TradernetError> {
+        let base = base.into();
+        for event in &mut self.events {
+            let rate = if event.currency == base {
+                Decimal::ONE
+            } else {
+                rates.rate(&event.currency, event.date).ok_or_else(|| {
+                    TradernetError::InvalidInput(format!(
+                        "missing {}/{base} rate for {}",
+                        event.currency, event.date
+                    ))
+                })?
+            };
+            event.base_amount = Some(event.amount * rate);
+        }
+        self.base_currency = Some(base);
+        Ok(self)
+    }
+
+    /// Totals per instrument and currency. Fees have no instrument.
+    pub fn by_instrument(&self) -> Vec<IncomeSummary> {
+        self.summarize(|event| (None, event.symbol.clone()))
+    }
+
+    /// Totals per currency.
+    pub fn by_currency(&self) -> Vec<IncomeSummary> {
+        self.summarize(|_| (None, None))
+    }
+
+    /// Totals per period and currency.
+    pub fn by_period(&self, period: IncomePeriod) -> Vec<IncomeSummary> {
+        self.summarize(|event| (Some(period.start(event.date)), None))
+    }
+
+    fn summarize(
+        &self,
+        key: impl Fn(&IncomeEvent) -> (Option<NaiveDate>, Option<String>),
+    ) -> Vec<IncomeSummary> {
+        let mut groups = BTreeMap::<_, IncomeSummary>::new();
+        for event in &self.events {
+            let (period_start, symbol) = key(event);
+            let summary = groups
+                .entry((period_start, symbol.clone(), event.currency.clone()))
+                .or_insert_with(|| IncomeSummary {
+                    period_start,
+                    symbol,
+                    currency: event.currency.clone(),
+                    base_net: Some(Decimal::ZERO),
+                    ..IncomeSummary::default()
+                });
+            match event.kind {
+                IncomeKind::Dividend => summary.dividends += event.amount,
+                IncomeKind::Coupon => summary.coupons += event.amount,
+                IncomeKind::WithholdingTax => summary.withholding_tax += event.amount,
+                IncomeKind::Fee => summary.fees += event.amount,
+            }
+            summary.net += event.amount;
+            summary.base_net = summary
+                .base_net
+                .zip(event.base_amount)
+                .map(|(total, amount)| total + amount);
+        }
+        groups.into_values().collect()
+    }
+
+    /// Writes the events as CSV.
+    #[cfg(feature = "csv")]
+    pub fn write_csv<W: std::io::Write>(&self, writer: W) -> Result<(), TradernetError> {
+        crate::export::write_csv(&self.to_table(), writer)
+    }
+}
+
+fn push_corporate_action(events: &mut Vec<IncomeEvent>, action: &ReportCorporateAction) {
+    let kind = if action.is_dividend() {
+        IncomeKind::Dividend
+    } else if action
+        .kind
+        .as_deref()
+        .is_some_and(|kind| kind.to_lowercase().contains("coupon"))
+    {
+        IncomeKind::Coupon
+    } else {
+        return;
+    };
+    let Some(date) = action.date.map(|date| date.date()).or(action.ex_date) else {
+        log::warn!("skipping corporate action without date: {action:?}");
+        return;
+    };
+    let Some(currency) = action.currency.clone() else {
+        log::warn!("skipping corporate action without currency: {action:?}");
+        return;
+    };
+
+    let event = |kind, currency: String, amount: Decimal| IncomeEvent {
+        date,
+        kind,
+        symbol: action.symbol.clone(),
+        isin: action.isin.clone(),
+        currency,
+        amount,
+        base_amount: None,
+        comment: action.comment.clone(),
+    };
+    if let Some(amount) = action.amount {
+        events.push(event(kind, currency.clone(), amount.abs()));
+    }
+    for (tax_currency, tax) in action.taxes() {
+        let tax_currency = tax_currency.map_or_else(|| currency.clone(), str::to_string);
+        events.push(event(IncomeKind::WithholdingTax, tax_currency, -tax));
+    }
+}
+
+const EVENT_COLUMNS: [(&str, ColumnType); 8] = [
+    ("date", ColumnType::Date),
+    ("kind", ColumnType::Utf8),
+    ("symbol", ColumnType::Utf8),
+    ("isin", ColumnType::Utf8),
+    ("currency", ColumnType::Utf8),
+    ("amount", ColumnType::Decimal),
+    ("base_amount", ColumnType::Decimal),
+    ("comment", ColumnType::Utf8),
+];
+
+impl ToTable for [IncomeEvent] {
+    fn to_table(&self) -> Table {
+        let rows = self
+            .iter()
+            .map(|event| {
+                vec![
+                    Cell::Date(event.date),
+                    Cell::Text(event.kind.as_str().to_string()),
+                    Cell::opt_text(event.symbol.as_deref()),
+                    Cell::opt_text(event.isin.as_deref()),
+                    Cell::Text(event.currency.clone()),
+                    Cell::Decimal(event.amount),
+                    Cell::opt_decimal(event.base_amount),
+                    Cell::opt_text(event.comment.as_deref()),
+                ]
+            })
+            .collect();
+        Table {
+            columns: EVENT_COLUMNS.to_vec(),
+            rows,
+        }
+    }
+}
+
+impl ToTable for IncomeReport {
+    fn to_table(&self) -> Table {
+        self.events.as_slice().to_table()
+    }
+}
+
+const SUMMARY_COLUMNS: [(&str, ColumnType); 9] = [
+    ("period_start", ColumnType::Date),
+    ("symbol", ColumnType::Utf8),
+    ("currency", ColumnType::Utf8),
+    ("dividends", ColumnType::Decimal),
+    ("coupons", ColumnType::Decimal),
+    ("withholding_tax", ColumnType::Decimal),
+    ("fees", ColumnType::Decimal),
+    ("net", ColumnType::Decimal),
+    ("base_net", ColumnType::Decimal),
+];
+
+impl ToTable for [IncomeSummary] {
+    fn to_table(&self) -> Table {
+        let rows = self
+            .iter()
+            .map(|summary| {
+                vec![
+                    summary.period_start.map_or(Cell::Null, Cell::Date),
+                    Cell::opt_text(summary.symbol.as_deref()),
+                    Cell::Text(summary.currency.clone()),
+                    Cell::Decimal(summary.dividends),
+                    Cell::Decimal(summary.coupons),
+                    Cell::Decimal(summary.withholding_tax),
+                    Cell::Decimal(summary.fees),
+                    Cell::Decimal(summary.net),
+                    Cell::opt_decimal(summary.base_net),
+                ]
+            })
+            .collect();
+        Table {
+            columns: SUMMARY_COLUMNS.to_vec(),
+            rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FxTable, IncomeKind, IncomePeriod, IncomeReport};
+    use crate::broker_report::parse_broker_report_response;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    fn income() -> IncomeReport {
+        let report = parse_broker_report_response(json!({"report": {
+            "commissions": {"detailed": [
+                {"datetime": "2025-03-31 00:00:00", "type": "custody", "sum": "2", "currency": "USD"}
+            ]},
+            "corporate_actions": {"detailed": [
+                {"date": "2025-02-15", "type": "dividend", "ticker": "AAPL.US", "currency": "USD",
+                 "amount": "10", "tax_amount": "-1.5"},
+                {"date": "2025-05-15", "type": "dividend", "ticker": "AAPL.US", "currency": "USD",
+                 "amount": "10", "tax_amount": "1.5"},
+                {"date": "2025-06-01", "type": "coupon", "ticker": "BOND.EU", "currency": "EUR",
+                 "amount": "20"},
+                {"date": "2025-06-02", "type": "split", "ticker": "AAPL.US", "currency": "USD"}
+            ]}
+        }}))
+        .expect("report must parse");
+        IncomeReport::from_broker_report(&report)
+    }
+
+    #[test]
+    fn income_extracts_dividends_taxes_coupons_and_fees() {
+        let income = income();
+
+        assert_eq!(income.events.len(), 6);
+        assert_eq!(income.events[1].kind, IncomeKind::WithholdingTax);
+        assert_eq!(income.events[1].amount, Decimal::new(-15, 1));
+        assert_eq!(income.events[2].kind, IncomeKind::Fee);
+        assert_eq!(income.events[2].amount, Decimal::from(-2));
+
+        let by_instrument = income.by_instrument();
+        let apple = by_instrument
+            .iter()
+            .find(|summary| summary.symbol.as_deref() == Some("AAPL.US"))
+            .expect("AAPL summary");
+        assert_eq!(apple.dividends, Decimal::from(20));
+        assert_eq!(apple.withholding_tax, Decimal::from(-3));
+        assert_eq!(apple.net, Decimal::from(17));
+        assert_eq!(apple.base_net, None);
+
+        let quarters = income.by_period(IncomePeriod::Quarter);
+        assert_eq!(quarters[0].period_start, Some(date(2025, 1, 1)));
+        assert_eq!(quarters[0].net, Decimal::new(65, 1));
+        assert_eq!(income.by_currency().len(), 2);
+    }
+
+    #[test]
+    fn income_converts_to_base_currency() {
+        let rates = FxTable::new()
+            .with_rate("USD", Decimal::new(9, 1))
+            .with_daily_rate("USD", date(2025, 5, 1), Decimal::new(8, 1));
+
+        let income = income()
+            .convert("EUR", &rates)
+            .expect("all rates are known");
+
+        assert_eq!(income.base_currency.as_deref(), Some("EUR"));
+        assert_eq!(income.events[0].base_amount, Some(Decimal::from(9)));
+        assert_eq!(income.events[3].base_amount, Some(Decimal::from(8)));
+        let years = income.by_period(IncomePeriod::Year);
+        let eur = years
+            .iter()
+            .find(|summary| summary.currency == "EUR")
+            .expect("EUR summary");
+        assert_eq!(eur.base_net, Some(Decimal::from(20)));
+
+        assert!(self::income().convert("CHF", &FxTable::new()).is_err());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn income_exports_csv() {
+        let mut output = Vec::new();
+        income()
+            .between(date(2025, 1, 1), date(2025, 2, 28))
+            .write_csv(&mut output)
+            .expect("csv export");
+
+        assert_eq!(
+            String::from_utf8(output).expect("utf-8 csv"),
+            "date,kind,symbol,isin,currency,amount,base_amount,comment\n\
+             2025-02-15,dividend,AAPL.US,,USD,10,,\n\
+             2025-02-15,withholding_tax,AAPL.US,,USD,-1.5,,\n"
+        );
+    }
+}
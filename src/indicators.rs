@@ -0,0 +1,772 @@
+use crate::candles::CandlePoint;
+use crate::errors::TradernetError;
+use crate::resample::{ResampleTarget, TradingSession};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Incremental technical indicator over candles.
+///
+/// Indicators are fed one bar at a time with [`update`](Self::update), which
+/// returns `None` until enough bars were seen. The same state machine backs
+/// [`batch`](Self::batch), so backtests and live feeds produce identical values.
+pub trait Indicator {
+    /// Value produced for each bar.
+    type Output;
+
+    /// Feeds the next bar and returns the indicator value for it.
+    fn update(&mut self, bar: &CandlePoint) -> Option<Self::Output>;
+
+    /// Clears accumulated state.
+    fn reset(&mut self);
+
+    /// Resets the indicator and computes values for a whole series.
+    ///
+    /// The result has one entry per input bar.
+    fn batch(&mut self, candles: &[CandlePoint]) -> Vec<Option<Self::Output>> {
+        self.reset();
+        candles.iter().map(|bar| self.update(bar)).collect()
+    }
+}
+
+/// Bar price used by single-input indicators.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    #[default]
+    Close,
+    /// `(high + low) / 2`.
+    Median,
+    /// `(high + low + close) / 3`.
+    Typical,
+}
+
+impl PriceSource {
+    /// Returns the selected price of a bar.
+    pub fn price(self, bar: &CandlePoint) -> f64 {
+        match self {
+            PriceSource::Open => bar.open,
+            PriceSource::High => bar.high,
+            PriceSource::Low => bar.low,
+            PriceSource::Close => bar.close,
+            PriceSource::Median => (bar.high + bar.low) / 2.0,
+            PriceSource::Typical => (bar.high + bar.low + bar.close) / 3.0,
+        }
+    }
+}
+
+fn validate_period(name: &str, period: usize) -> Result<(), TradernetError> {
+    if period == 0 {
+        return Err(TradernetError::InvalidInput(format!(
+            "{name} period must be greater than 0"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct SmaState {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl SmaState {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+    }
+}
+
+/// EMA seeded with the SMA of the first `period` values.
+#[derive(Debug, Clone)]
+struct EmaState {
+    alpha: f64,
+    seed: SmaState,
+    value: Option<f64>,
+}
+
+impl EmaState {
+    fn new(period: usize) -> Self {
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: SmaState::new(period),
+            value: None,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.seed.reset();
+        self.value = None;
+    }
+}
+
+/// Wilder smoothing seeded with the mean of the first `period` values.
+#[derive(Debug, Clone)]
+struct WilderState {
+    period: usize,
+    seed: SmaState,
+    value: Option<f64>,
+}
+
+impl WilderState {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            seed: SmaState::new(period),
+            value: None,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let period = self.period as f64;
+        self.value = match self.value {
+            Some(previous) => Some((previous * (period - 1.0) + value) / period),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.seed.reset();
+        self.value = None;
+    }
+}
+
+/// Simple moving average.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    source: PriceSource,
+    state: SmaState,
+}
+
+impl Sma {
+    /// Creates an SMA over closes.
+    pub fn new(period: usize) -> Result<Self, TradernetError> {
+        validate_period("SMA", period)?;
+        Ok(Self {
+            source: PriceSource::default(),
+            state: SmaState::new(period),
+        })
+    }
+
+    /// Sets the bar price the average is computed from.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        self.state.update(self.source.price(bar))
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+/// Exponential moving average seeded with the SMA of the first `period` bars.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    source: PriceSource,
+    state: EmaState,
+}
+
+impl Ema {
+    /// Creates an EMA over closes.
+    pub fn new(period: usize) -> Result<Self, TradernetError> {
+        validate_period("EMA", period)?;
+        Ok(Self {
+            source: PriceSource::default(),
+            state: EmaState::new(period),
+        })
+    }
+
+    /// Sets the bar price the average is computed from.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        self.state.update(self.source.price(bar))
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+/// Linearly weighted moving average; the newest bar has weight `period`.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    source: PriceSource,
+    period: usize,
+    window: VecDeque<f64>,
+}
+
+impl Wma {
+    /// Creates a WMA over closes.
+    pub fn new(period: usize) -> Result<Self, TradernetError> {
+        validate_period("WMA", period)?;
+        Ok(Self {
+            source: PriceSource::default(),
+            period,
+            window: VecDeque::with_capacity(period),
+        })
+    }
+
+    /// Sets the bar price the average is computed from.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Wma {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        self.window.push_back(self.source.price(bar));
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+        let weighted = self
+            .window
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (index + 1) as f64 * value)
+            .sum::<f64>();
+        let weights = (self.period * (self.period + 1)) as f64 / 2.0;
+        Some(weighted / weights)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Relative strength index with Wilder smoothing, in the `0..=100` range.
+///
+/// The first value is produced on bar `period + 1`.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    gains: WilderState,
+    losses: WilderState,
+    previous_close: Option<f64>,
+}
+
+impl Rsi {
+    /// Creates an RSI over closes.
+    pub fn new(period: usize) -> Result<Self, TradernetError> {
+        validate_period("RSI", period)?;
+        Ok(Self {
+            gains: WilderState::new(period),
+            losses: WilderState::new(period),
+            previous_close: None,
+        })
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        let previous = self.previous_close.replace(bar.close)?;
+        let change = bar.close - previous;
+        let gain = self.gains.update(change.max(0.0));
+        let loss = self.losses.update((-change).max(0.0));
+        let (gain, loss) = (gain?, loss?);
+        Some(if loss == 0.0 {
+            if gain == 0.0 { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        })
+    }
+
+    fn reset(&mut self) {
+        self.gains.reset();
+        self.losses.reset();
+        self.previous_close = None;
+    }
+}
+
+/// MACD line, signal line and histogram.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct MacdValue {
+    /// Fast EMA minus slow EMA.
+    pub macd: f64,
+    /// EMA of the MACD line.
+    pub signal: f64,
+    /// MACD line minus signal line.
+    pub histogram: f64,
+}
+
+/// Moving average convergence/divergence over closes.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: EmaState,
+    slow: EmaState,
+    signal: EmaState,
+}
+
+impl Macd {
+    /// Creates a MACD, e.g. `Macd::new(12, 26, 9)`.
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Result<Self, TradernetError> {
+        validate_period("MACD fast", fast)?;
+        validate_period("MACD slow", slow)?;
+        validate_period("MACD signal", signal)?;
+        if fast >= slow {
+            return Err(TradernetError::InvalidInput(
+                "MACD fast period must be less than slow period".to_string(),
+            ));
+        }
+        Ok(Self {
+            fast: EmaState::new(fast),
+            slow: EmaState::new(slow),
+            signal: EmaState::new(signal),
+        })
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<MacdValue> {
+        let fast = self.fast.update(bar.close);
+        let slow = self.slow.update(bar.close);
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+    }
+}
+
+/// Bollinger Bands value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct BollingerValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Bollinger Bands: SMA plus/minus a multiple of the population standard deviation.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    source: PriceSource,
+    multiplier: f64,
+    period: usize,
+    window: VecDeque<f64>,
+}
+
+impl Bollinger {
+    /// Creates bands over closes, e.g. `Bollinger::new(20, 2.0)`.
+    pub fn new(period: usize, multiplier: f64) -> Result<Self, TradernetError> {
+        validate_period("Bollinger", period)?;
+        if !multiplier.is_finite() || multiplier < 0.0 {
+            return Err(TradernetError::InvalidInput(
+                "Bollinger multiplier must be a non-negative number".to_string(),
+            ));
+        }
+        Ok(Self {
+            source: PriceSource::default(),
+            multiplier,
+            period,
+            window: VecDeque::with_capacity(period),
+        })
+    }
+
+    /// Sets the bar price the bands are computed from.
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerValue;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<BollingerValue> {
+        self.window.push_back(self.source.price(bar));
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+        let count = self.period as f64;
+        let middle = self.window.iter().sum::<f64>() / count;
+        let variance = self
+            .window
+            .iter()
+            .map(|value| (value - middle).powi(2))
+            .sum::<f64>()
+            / count;
+        let width = self.multiplier * variance.sqrt();
+        Some(BollingerValue {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Average true range with Wilder smoothing.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    state: WilderState,
+    previous_close: Option<f64>,
+}
+
+impl Atr {
+    /// Creates an ATR.
+    pub fn new(period: usize) -> Result<Self, TradernetError> {
+        validate_period("ATR", period)?;
+        Ok(Self {
+            state: WilderState::new(period),
+            previous_close: None,
+        })
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        let range = bar.high - bar.low;
+        let true_range = match self.previous_close.replace(bar.close) {
+            Some(previous) => range
+                .max((bar.high - previous).abs())
+                .max((bar.low - previous).abs()),
+            None => range,
+        };
+        self.state.update(true_range)
+    }
+
+    fn reset(&mut self) {
+        self.state.reset();
+        self.previous_close = None;
+    }
+}
+
+/// Volume weighted average of the typical price.
+///
+/// Accumulates over the whole series by default; with
+/// [`with_session`](Self::with_session) it restarts on every trading day.
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    session: Option<TradingSession>,
+    day: Option<i64>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    /// Creates a cumulative VWAP.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restarts accumulation at each trading day of `session`.
+    pub fn with_session(mut self, session: TradingSession) -> Self {
+        self.session = Some(session);
+        self
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<f64> {
+        if let Some(session) = &self.session {
+            let day = session.bucket_timestamp(bar.ts, ResampleTarget::Daily);
+            if day != self.day {
+                self.day = day;
+                self.price_volume = 0.0;
+                self.volume = 0.0;
+            }
+        }
+        let volume = bar.volume as f64;
+        self.price_volume += PriceSource::Typical.price(bar) * volume;
+        self.volume += volume;
+        (self.volume > 0.0).then(|| self.price_volume / self.volume)
+    }
+
+    fn reset(&mut self) {
+        self.day = None;
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+    }
+}
+
+/// On-balance volume, starting from zero at the first bar.
+#[derive(Debug, Clone, Default)]
+pub struct Obv {
+    previous_close: Option<f64>,
+    value: i64,
+}
+
+impl Obv {
+    /// Creates an OBV.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Indicator for Obv {
+    type Output = i64;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<i64> {
+        if let Some(previous) = self.previous_close.replace(bar.close) {
+            if bar.close > previous {
+                self.value = self.value.saturating_add(bar.volume);
+            } else if bar.close < previous {
+                self.value = self.value.saturating_sub(bar.volume);
+            }
+        }
+        Some(self.value)
+    }
+
+    fn reset(&mut self) {
+        self.previous_close = None;
+        self.value = 0;
+    }
+}
+
+/// Stochastic oscillator value in the `0..=100` range.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct StochasticValue {
+    /// Position of the close within the high/low range.
+    pub k: f64,
+    /// SMA of `%K`.
+    pub d: f64,
+}
+
+/// Stochastic oscillator (`%K` and `%D`).
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    k_period: usize,
+    bars: VecDeque<(f64, f64)>,
+    d: SmaState,
+}
+
+impl Stochastic {
+    /// Creates a stochastic oscillator, e.g. `Stochastic::new(14, 3)`.
+    pub fn new(k_period: usize, d_period: usize) -> Result<Self, TradernetError> {
+        validate_period("stochastic %K", k_period)?;
+        validate_period("stochastic %D", d_period)?;
+        Ok(Self {
+            k_period,
+            bars: VecDeque::with_capacity(k_period),
+            d: SmaState::new(d_period),
+        })
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticValue;
+
+    fn update(&mut self, bar: &CandlePoint) -> Option<StochasticValue> {
+        self.bars.push_back((bar.high, bar.low));
+        if self.bars.len() > self.k_period {
+            self.bars.pop_front();
+        }
+        if self.bars.len() < self.k_period {
+            return None;
+        }
+        let highest = self
+            .bars
+            .iter()
+            .map(|(high, _)| *high)
+            .fold(f64::MIN, f64::max);
+        let lowest = self
+            .bars
+            .iter()
+            .map(|(_, low)| *low)
+            .fold(f64::MAX, f64::min);
+        let k = if highest > lowest {
+            100.0 * (bar.close - lowest) / (highest - lowest)
+        } else {
+            50.0
+        };
+        let d = self.d.update(k)?;
+        Some(StochasticValue { k, d })
+    }
+
+    fn reset(&mut self) {
+        self.bars.clear();
+        self.d.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Atr, Bollinger, Ema, Indicator, Macd, Obv, Rsi, Sma, Stochastic, Vwap, Wma};
+    use crate::candles::CandlePoint;
+    use crate::errors::TradernetError;
+    use crate::resample::TradingSession;
+
+    fn closes(values: &[f64]) -> Vec<CandlePoint> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, close)| CandlePoint {
+                ts: index as i64 * 60,
+                open: *close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close: *close,
+                volume: 10,
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("indicator value must be ready");
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn moving_averages_match_reference_values() {
+        let candles = closes(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let sma = Sma::new(3).expect("valid period").batch(&candles);
+        assert_eq!(sma[..2], [None, None]);
+        assert_close(sma[2], 2.0);
+        assert_close(sma[4], 4.0);
+
+        let ema = Ema::new(3).expect("valid period").batch(&candles);
+        assert_close(ema[2], 2.0);
+        assert_close(ema[3], 3.0);
+        assert_close(ema[4], 4.0);
+
+        let wma = Wma::new(3).expect("valid period").batch(&candles);
+        assert_close(wma[2], (1.0 + 4.0 + 9.0) / 6.0);
+    }
+
+    #[test]
+    fn streaming_updates_match_batch() {
+        let candles = closes(&[10.0, 11.0, 10.5, 12.0, 11.0, 13.0, 12.5, 14.0]);
+        let mut streaming = Macd::new(2, 3, 2).expect("valid periods");
+        let live = candles
+            .iter()
+            .map(|bar| streaming.update(bar))
+            .collect::<Vec<_>>();
+
+        assert_eq!(live, Macd::new(2, 3, 2).expect("valid").batch(&candles));
+        assert!(live[2].is_none());
+        assert!(live[3].is_some());
+    }
+
+    #[test]
+    fn rsi_and_atr_use_wilder_smoothing() {
+        let candles = closes(&[1.0, 2.0, 1.0, 2.0, 3.0]);
+
+        let rsi = Rsi::new(2).expect("valid period").batch(&candles);
+        assert_eq!(rsi[..2], [None, None]);
+        assert_close(rsi[2], 50.0);
+        // avg gain (0.5 + 1) / 2 = 0.75, avg loss (0.5 + 0) / 2 = 0.25
+        assert_close(rsi[3], 75.0);
+
+        let rising = closes(&[1.0, 2.0, 3.0]);
+        assert_close(Rsi::new(2).expect("valid").batch(&rising)[2], 100.0);
+
+        let atr = Atr::new(2).expect("valid period").batch(&candles);
+        assert_eq!(atr[0], None);
+        assert_close(atr[1], 2.0);
+        assert_close(atr[2], 2.0);
+    }
+
+    #[test]
+    fn bollinger_stochastic_vwap_and_obv() {
+        let candles = closes(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+
+        let bands = Bollinger::new(8, 2.0).expect("valid").batch(&candles);
+        let last = bands[7].expect("bands ready");
+        assert_eq!((last.lower, last.middle, last.upper), (1.0, 5.0, 9.0));
+
+        let stochastic = Stochastic::new(3, 2).expect("valid").batch(&candles);
+        assert!(stochastic[2].is_none());
+        let value = stochastic[7].expect("stochastic ready");
+        // window highs/lows: 10/4 -> k = 100 * (9 - 4) / 6
+        assert!((value.k - 500.0 / 6.0).abs() < 1e-9);
+
+        let mut trending = closes(&[1.0, 2.0, 2.0, 1.0]);
+        trending[1].volume = 5;
+        trending[3].volume = 20;
+        let obv = Obv::new().batch(&trending);
+        assert_eq!(obv, vec![Some(0), Some(5), Some(5), Some(-15)]);
+
+        let vwap = Vwap::new().batch(&trending);
+        assert_close(vwap[1], (10.0 + 2.0 * 5.0) / 15.0);
+
+        let mut next_day = trending.clone();
+        next_day[3].ts = 86_400;
+        let session_vwap = Vwap::new()
+            .with_session(TradingSession::default())
+            .batch(&next_day);
+        assert_close(session_vwap[3], 1.0);
+    }
+
+    #[test]
+    fn zero_period_is_rejected() {
+        assert!(matches!(Sma::new(0), Err(TradernetError::InvalidInput(_))));
+        assert!(matches!(
+            Macd::new(26, 12, 9),
+            Err(TradernetError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,614 @@
+use crate::async_client::AsyncTradernet;
+use crate::client::Tradernet;
+use crate::common::json_fields::api_method_error;
+use crate::errors::TradernetError;
+use crate::trades_history::{TradeRow, TradesHistoryFilters};
+use crate::ws_types::OrderDataRow;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Where an order state was observed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OrderSource {
+    /// `getOrdersHistory` REST response.
+    Rest,
+    /// WebSocket `orders` channel.
+    Stream,
+}
+
+/// Order state persisted by a [`LedgerStore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerOrder {
+    pub source: OrderSource,
+    pub order: OrderDataRow,
+}
+
+/// Records loaded from a [`LedgerStore`].
+#[derive(Debug, Clone, Default)]
+pub struct LedgerSnapshot {
+    pub trades: Vec<TradeRow>,
+    /// Order states in the order they were appended; later states win.
+    pub orders: Vec<LedgerOrder>,
+}
+
+/// Append-only storage backend for [`Ledger`].
+pub trait LedgerStore {
+    /// Loads all stored records.
+    fn load(&mut self) -> Result<LedgerSnapshot, TradernetError>;
+    /// Appends new trades.
+    fn append_trades(&mut self, trades: &[TradeRow]) -> Result<(), TradernetError>;
+    /// Appends new order states.
+    fn append_orders(&mut self, orders: &[LedgerOrder]) -> Result<(), TradernetError>;
+}
+
+/// In-memory [`LedgerStore`], e.g. for tests or short-lived processes.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLedgerStore {
+    snapshot: LedgerSnapshot,
+}
+
+impl LedgerStore for MemoryLedgerStore {
+    fn load(&mut self) -> Result<LedgerSnapshot, TradernetError> {
+        Ok(self.snapshot.clone())
+    }
+
+    fn append_trades(&mut self, trades: &[TradeRow]) -> Result<(), TradernetError> {
+        self.snapshot.trades.extend_from_slice(trades);
+        Ok(())
+    }
+
+    fn append_orders(&mut self, orders: &[LedgerOrder]) -> Result<(), TradernetError> {
+        self.snapshot.orders.extend_from_slice(orders);
+        Ok(())
+    }
+}
+
+/// [`LedgerStore`] writing `trades.jsonl` and `orders.jsonl` into a directory.
+///
+/// Lines that fail to parse on load are skipped with a warning.
+#[derive(Debug, Clone)]
+pub struct JsonLinesStore {
+    dir: PathBuf,
+}
+
+impl JsonLinesStore {
+    /// Creates a store in `dir`; the directory is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the store directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(&self, name: &str) -> Result<Vec<T>, TradernetError> {
+        let path = self.dir.join(name);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        let mut records = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(error) => {
+                    log::warn!(
+                        "{}:{}: skipped ledger line: {error}",
+                        path.display(),
+                        index + 1
+                    )
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    fn append<T: Serialize>(&self, name: &str, records: &[T]) -> Result<(), TradernetError> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(name))?;
+        let mut writer = BufWriter::new(file);
+        for record in records {
+            serde_json::to_writer(&mut writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl LedgerStore for JsonLinesStore {
+    fn load(&mut self) -> Result<LedgerSnapshot, TradernetError> {
+        Ok(LedgerSnapshot {
+            trades: self.read("trades.jsonl")?,
+            orders: self.read("orders.jsonl")?,
+        })
+    }
+
+    fn append_trades(&mut self, trades: &[TradeRow]) -> Result<(), TradernetError> {
+        self.append("trades.jsonl", trades)
+    }
+
+    fn append_orders(&mut self, orders: &[LedgerOrder]) -> Result<(), TradernetError> {
+        self.append("orders.jsonl", orders)
+    }
+}
+
+/// Difference between the streamed and the REST view of an order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Discrepancy {
+    /// Order seen on the WebSocket but not returned by `getOrdersHistory`.
+    OrderMissingFromRest { order_id: i64 },
+    /// Latest streamed status differs from the REST status.
+    StatusMismatch {
+        order_id: i64,
+        streamed: i64,
+        rest: i64,
+    },
+    /// Latest streamed remaining quantity differs from the REST one.
+    LeavesQtyMismatch {
+        order_id: i64,
+        streamed: f64,
+        rest: f64,
+    },
+    /// Fill reported on the WebSocket but missing from `getTradesHistory`.
+    TradeMissingFromRest { order_id: i64, trade_id: i64 },
+}
+
+/// Counts of records added by a ledger synchronization.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct LedgerSyncReport {
+    pub new_trades: usize,
+    pub updated_orders: usize,
+}
+
+/// Local copy of trades and orders kept in sync with the API.
+///
+/// Trades are keyed by trade id and orders by order id. REST and WebSocket order
+/// states are kept separately so they can be compared with
+/// [`Ledger::discrepancies`].
+#[derive(Debug)]
+pub struct Ledger<S: LedgerStore = JsonLinesStore> {
+    store: S,
+    trades: BTreeMap<i64, TradeRow>,
+    rest_orders: BTreeMap<i64, OrderDataRow>,
+    streamed_orders: BTreeMap<i64, OrderDataRow>,
+}
+
+impl<S: LedgerStore> Ledger<S> {
+    /// Opens a ledger and loads the stored records.
+    pub fn open(mut store: S) -> Result<Self, TradernetError> {
+        let snapshot = store.load()?;
+        let mut ledger = Self {
+            store,
+            trades: BTreeMap::new(),
+            rest_orders: BTreeMap::new(),
+            streamed_orders: BTreeMap::new(),
+        };
+        for trade in snapshot.trades {
+            if let Some(id) = trade.id {
+                ledger.trades.insert(id, trade);
+            }
+        }
+        for record in snapshot.orders {
+            ledger
+                .orders_mut(record.source)
+                .insert(record.order.order_id, record.order);
+        }
+        Ok(ledger)
+    }
+
+    /// Returns the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the highest stored trade id, used to resume trade downloads.
+    pub fn max_trade_id(&self) -> Option<i64> {
+        self.trades.keys().next_back().copied()
+    }
+
+    /// Returns stored trades ordered by trade id.
+    pub fn trades(&self) -> impl Iterator<Item = &TradeRow> {
+        self.trades.values()
+    }
+
+    /// Returns the latest order states, preferring REST over streamed ones.
+    pub fn orders(&self) -> impl Iterator<Item = &OrderDataRow> {
+        self.rest_orders.values().chain(
+            self.streamed_orders
+                .iter()
+                .filter(|(id, _)| !self.rest_orders.contains_key(id))
+                .map(|(_, order)| order),
+        )
+    }
+
+    /// Returns the latest REST or, failing that, streamed state of an order.
+    pub fn order(&self, order_id: i64) -> Option<&OrderDataRow> {
+        self.rest_orders
+            .get(&order_id)
+            .or_else(|| self.streamed_orders.get(&order_id))
+    }
+
+    /// Stores trades not seen before and returns how many were added.
+    ///
+    /// Trades without an id are skipped.
+    pub fn record_trades<I>(&mut self, trades: I) -> Result<usize, TradernetError>
+    where
+        I: IntoIterator<Item = TradeRow>,
+    {
+        let mut fresh = Vec::new();
+        for trade in trades {
+            let Some(id) = trade.id else {
+                log::warn!("trade without id skipped by ledger");
+                continue;
+            };
+            if let Entry::Vacant(entry) = self.trades.entry(id) {
+                entry.insert(trade.clone());
+                fresh.push(trade);
+            }
+        }
+        self.store.append_trades(&fresh)?;
+        Ok(fresh.len())
+    }
+
+    /// Stores REST order states that are new or changed and returns how many were stored.
+    pub fn record_orders<I>(&mut self, orders: I) -> Result<usize, TradernetError>
+    where
+        I: IntoIterator<Item = OrderDataRow>,
+    {
+        self.record(OrderSource::Rest, orders)
+    }
+
+    /// Applies a WebSocket `orders` update; returns `true` when the state changed.
+    pub fn apply_order_update(&mut self, order: OrderDataRow) -> Result<bool, TradernetError> {
+        Ok(self.record(OrderSource::Stream, [order])? > 0)
+    }
+
+    /// Applies updates from a stream such as [`crate::TradernetWsSession::order_stream`]
+    /// until it ends, returning the number of changed orders.
+    pub async fn merge_order_stream<St>(&mut self, mut orders: St) -> Result<usize, TradernetError>
+    where
+        St: Stream<Item = OrderDataRow> + Unpin,
+    {
+        let mut changed = 0;
+        while let Some(order) = orders.next().await {
+            changed += usize::from(self.apply_order_update(order)?);
+        }
+        Ok(changed)
+    }
+
+    /// Downloads trades after [`Ledger::max_trade_id`] and orders for the date range.
+    pub fn sync(
+        &mut self,
+        client: &Tradernet,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<LedgerSyncReport, TradernetError> {
+        let trades = client
+            .trades_history_iter(start, end, self.trades_filters())?
+            .collect::<Result<Vec<_>, _>>()?;
+        let new_trades = self.record_trades(trades)?;
+
+        let (from, till) = local_range(start, end)?;
+        let orders = parse_orders_history_response(client.get_historical(from, till)?)?;
+        let updated_orders = self.record_orders(orders)?;
+        Ok(LedgerSyncReport {
+            new_trades,
+            updated_orders,
+        })
+    }
+
+    /// Asynchronous variant of [`Ledger::sync`].
+    pub async fn sync_async(
+        &mut self,
+        client: &AsyncTradernet,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<LedgerSyncReport, TradernetError> {
+        let mut trades = Vec::new();
+        let mut stream = client.trades_history_iter(start, end, self.trades_filters())?;
+        while let Some(trade) = stream.next().await {
+            trades.push(trade?);
+        }
+        drop(stream);
+        let new_trades = self.record_trades(trades)?;
+
+        let (from, till) = local_range(start, end)?;
+        let orders = parse_orders_history_response(client.get_historical(from, till).await?)?;
+        let updated_orders = self.record_orders(orders)?;
+        Ok(LedgerSyncReport {
+            new_trades,
+            updated_orders,
+        })
+    }
+
+    /// Compares streamed order states and fills with the REST view.
+    pub fn discrepancies(&self) -> Vec<Discrepancy> {
+        let mut found = Vec::new();
+        for (order_id, streamed) in &self.streamed_orders {
+            let order_id = *order_id;
+            match self.rest_orders.get(&order_id) {
+                None => found.push(Discrepancy::OrderMissingFromRest { order_id }),
+                Some(rest) => {
+                    if streamed.stat != rest.stat {
+                        found.push(Discrepancy::StatusMismatch {
+                            order_id,
+                            streamed: streamed.stat,
+                            rest: rest.stat,
+                        });
+                    }
+                    if streamed.leaves_qty != rest.leaves_qty {
+                        found.push(Discrepancy::LeavesQtyMismatch {
+                            order_id,
+                            streamed: streamed.leaves_qty,
+                            rest: rest.leaves_qty,
+                        });
+                    }
+                }
+            }
+            let trade_ids = streamed
+                .trade
+                .iter()
+                .map(|trade| trade.id)
+                .filter(|id| *id > 0)
+                .collect::<BTreeSet<_>>();
+            for trade_id in trade_ids {
+                if !self.trades.contains_key(&trade_id) {
+                    found.push(Discrepancy::TradeMissingFromRest { order_id, trade_id });
+                }
+            }
+        }
+        found
+    }
+
+    fn trades_filters(&self) -> TradesHistoryFilters {
+        match self.max_trade_id() {
+            Some(trade_id) => TradesHistoryFilters::default().with_from_trade_id(trade_id),
+            None => TradesHistoryFilters::default(),
+        }
+    }
+
+    fn orders_mut(&mut self, source: OrderSource) -> &mut BTreeMap<i64, OrderDataRow> {
+        match source {
+            OrderSource::Rest => &mut self.rest_orders,
+            OrderSource::Stream => &mut self.streamed_orders,
+        }
+    }
+
+    fn record<I>(&mut self, source: OrderSource, orders: I) -> Result<usize, TradernetError>
+    where
+        I: IntoIterator<Item = OrderDataRow>,
+    {
+        let mut changed = Vec::new();
+        for order in orders {
+            if order.order_id <= 0 {
+                log::warn!("order without id skipped by ledger");
+                continue;
+            }
+            let known = self.orders_mut(source);
+            if known
+                .get(&order.order_id)
+                .is_some_and(|current| same_state(current, &order))
+            {
+                continue;
+            }
+            known.insert(order.order_id, order.clone());
+            changed.push(LedgerOrder { source, order });
+        }
+        self.store.append_orders(&changed)?;
+        Ok(changed.len())
+    }
+}
+
+/// Parses raw `getOrdersHistory` JSON into order rows and surfaces method-level API errors.
+///
+/// Orders are read from `result.orders.order`, `orders.order`, `orders` or a
+/// top-level array; REST `id` is used as `order_id`. Rows that fail to deserialize
+/// are skipped with a warning.
+pub fn parse_orders_history_response(response: Value) -> Result<Vec<OrderDataRow>, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let mut rows = response.get("result").unwrap_or(&response).clone();
+    if let Some(orders) = rows.get_mut("orders").map(Value::take) {
+        rows = orders;
+    }
+    if let Some(order) = rows.get_mut("order").map(Value::take) {
+        rows = order;
+    }
+    let rows = match rows {
+        Value::Array(rows) => rows,
+        Value::Object(map) => vec![Value::Object(map)],
+        _ => Vec::new(),
+    };
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let Value::Object(mut map) = row else {
+                log::warn!("order row expected object, got: {row}");
+                return None;
+            };
+            if !map.contains_key("order_id")
+                && let Some(id) = map.get("id").cloned()
+            {
+                map.insert("order_id".to_string(), id);
+            }
+            serde_json::from_value(Value::Object(map))
+                .map_err(|error| log::warn!("order row parse failed: {error}"))
+                .ok()
+        })
+        .collect())
+}
+
+fn same_state(current: &OrderDataRow, update: &OrderDataRow) -> bool {
+    current.stat == update.stat
+        && current.stat_d == update.stat_d
+        && current.leaves_qty == update.leaves_qty
+        && current.curr_q == update.curr_q
+        && current.trade.len() == update.trade.len()
+}
+
+fn local_range(
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<(DateTime<Local>, DateTime<Local>), TradernetError> {
+    let local = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|datetime| Local.from_local_datetime(&datetime).earliest())
+    };
+    match (local(start), end.succ_opt().and_then(local)) {
+        (Some(from), Some(till)) => Ok((from, till)),
+        _ => Err(TradernetError::InvalidInput(format!(
+            "invalid orders history range: {start} - {end}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Discrepancy, JsonLinesStore, Ledger, MemoryLedgerStore, parse_orders_history_response,
+    };
+    use crate::trades_history::TradeRow;
+    use crate::ws_types::{OrderDataRow, OrderTradeInfo};
+    use serde_json::json;
+
+    fn trade(id: i64) -> TradeRow {
+        TradeRow {
+            id: Some(id),
+            instr_nm: Some("AAPL.US".to_string()),
+            ..TradeRow::default()
+        }
+    }
+
+    fn order(order_id: i64, stat: i64, trades: &[i64]) -> OrderDataRow {
+        OrderDataRow {
+            order_id,
+            stat,
+            trade: trades
+                .iter()
+                .map(|id| OrderTradeInfo {
+                    id: *id,
+                    ..OrderTradeInfo::default()
+                })
+                .collect(),
+            ..OrderDataRow::default()
+        }
+    }
+
+    #[test]
+    fn json_lines_store_persists_and_resumes() {
+        let dir = std::env::temp_dir().join(format!("tradernet-ledger-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut ledger = Ledger::open(JsonLinesStore::new(&dir)).expect("ledger opens");
+        assert_eq!(ledger.max_trade_id(), None);
+        assert_eq!(
+            ledger
+                .record_trades([trade(2), trade(1), trade(2)])
+                .expect("trades stored"),
+            2
+        );
+        assert_eq!(ledger.record_orders([order(7, 1, &[])]).expect("stored"), 1);
+        assert_eq!(ledger.record_orders([order(7, 1, &[])]).expect("stored"), 0);
+        assert_eq!(ledger.record_orders([order(7, 2, &[])]).expect("stored"), 1);
+        std::fs::write(
+            dir.join("trades.jsonl"),
+            std::fs::read_to_string(dir.join("trades.jsonl")).expect("readable") + "not json\n",
+        )
+        .expect("writable");
+
+        let reopened = Ledger::open(JsonLinesStore::new(&dir)).expect("ledger reopens");
+        assert_eq!(reopened.max_trade_id(), Some(2));
+        assert_eq!(reopened.trades().count(), 2);
+        assert_eq!(reopened.order(7).map(|order| order.stat), Some(2));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn discrepancies_compare_streamed_and_rest_views() {
+        let mut ledger = Ledger::open(MemoryLedgerStore::default()).expect("ledger opens");
+        ledger.record_trades([trade(100)]).expect("stored");
+        ledger
+            .record_orders([order(1, 2, &[]), order(2, 21, &[])])
+            .expect("stored");
+
+        assert!(
+            ledger
+                .apply_order_update(order(1, 2, &[100]))
+                .expect("applied")
+        );
+        assert!(
+            !ledger
+                .apply_order_update(order(1, 2, &[100]))
+                .expect("applied")
+        );
+        ledger
+            .apply_order_update(order(2, 31, &[101]))
+            .expect("applied");
+        ledger
+            .apply_order_update(order(3, 1, &[]))
+            .expect("applied");
+
+        assert_eq!(
+            ledger.discrepancies(),
+            vec![
+                Discrepancy::StatusMismatch {
+                    order_id: 2,
+                    streamed: 31,
+                    rest: 21
+                },
+                Discrepancy::TradeMissingFromRest {
+                    order_id: 2,
+                    trade_id: 101
+                },
+                Discrepancy::OrderMissingFromRest { order_id: 3 },
+            ]
+        );
+        assert_eq!(ledger.orders().count(), 3);
+    }
+
+    #[test]
+    fn orders_history_response_is_parsed_from_nested_shapes() {
+        let orders = parse_orders_history_response(json!({
+            "orders": {"key": "x", "order": [
+                {"id": 11, "stat": 21, "instr": "AAPL.US", "q": "2"},
+                {"order_id": 12, "stat": 10},
+                "bad"
+            ]}
+        }))
+        .expect("orders must parse");
+        assert_eq!(orders.len(), 2);
+        assert_eq!((orders[0].order_id, orders[0].q), (11, 2.0));
+
+        let single = parse_orders_history_response(json!({
+            "result": {"orders": {"order": {"id": 5}}}
+        }))
+        .expect("single order must parse");
+        assert_eq!(single[0].order_id, 5);
+
+        assert!(parse_orders_history_response(json!({"errMsg": "denied"})).is_err());
+    }
+}
@@ -15,10 +15,19 @@
 //! # }
 //! ```
 //!
-//! See [`TradernetWebsocket`] for streaming market data.
+//! See [`TradernetWebsocket`] for streaming market data and [`BlockingWsSession`]
+//! for streaming from synchronous code.
 
+/// Split- and dividend-adjusted candle history.
+pub mod adjustments;
 /// Async REST API client built on top of [`Tradernet`].
 pub mod async_client;
+/// Live candle building from WebSocket quote ticks.
+pub mod bar_builder;
+/// Typed `getBrokerReport` models.
+pub mod broker_report;
+/// Persistent on-disk cache for candle history.
+pub mod candle_cache;
 /// Typed responses for getHloc candles endpoint.
 pub mod candles;
 /// REST API client built on top of [`Core`].
@@ -27,8 +36,32 @@ pub mod client;
 pub mod common;
 /// Core authentication and request utilities.
 pub mod core;
+/// Typed client requests (CPS) history and order file attachments.
+pub mod cps;
 /// Error types returned by the SDK.
 pub mod errors;
+/// Tabular export of candles, trades and positions (CSV, Arrow, Parquet).
+pub mod export;
+/// Candle gap detection, gap filling and series validation.
+pub mod gaps;
+/// Dividend, coupon, withholding tax and fee income reports.
+pub mod income;
+/// Streaming and batch technical indicators over candles.
+pub mod indicators;
+/// Local ledger of trades and orders synchronized from REST and WebSocket.
+pub mod ledger;
+/// Tax-lot matching and realized/unrealized P&L over trade history.
+pub mod lots;
+/// Exchange time zones and API time conversion.
+pub mod market_time;
+/// Option pricing (Black-Scholes, Black-76), implied volatility and Greeks.
+pub mod option_pricing;
+/// Quote snapshot cache for incremental WebSocket updates.
+pub mod quote_cache;
+/// Candle resampling into larger bars.
+pub mod resample;
+/// Typed `getSecurityInfo` and `getStockData` models.
+pub mod security_info;
 /// Symbols and options helpers.
 pub mod symbols;
 /// Typed responses for getTradesHistory.
@@ -37,28 +70,85 @@ pub mod trades_history;
 pub mod user_data;
 /// WebSocket streaming client.
 pub mod ws;
+/// Blocking facade over the WebSocket session.
+pub mod ws_blocking;
+/// Recording and offline replay of WebSocket sessions.
+pub mod ws_record;
 /// Typed WebSocket events and payloads.
 pub mod ws_types;
 
+pub use crate::adjustments::{
+    AdjustedCandles, AdjustmentFactor, AdjustmentMode, CorporateAction, CorporateActionKind,
+    parse_corporate_actions_response,
+};
 pub use crate::async_client::AsyncTradernet;
+pub use crate::bar_builder::{BarBuilder, BarUpdate, stitch_candles};
+pub use crate::broker_report::{
+    BrokerReport, CashFlow, ReportBlock, ReportCommission, ReportCorporateAction, ReportDividend,
+    ReportPosition, ReportTrade, parse_broker_report_response,
+};
+pub use crate::candle_cache::CandleCache;
 pub use crate::candles::{
-    CandleInfo, CandleOhlc, CandlePoint, CandlesApiError, CandlesResponse, SymbolCandles,
+    CANDLES_PER_CHUNK, CANDLES_SYMBOLS_PER_BATCH, CandleInfo, CandleOhlc, CandlePoint,
+    CandlesApiError, CandlesBatch, CandlesResponse, SymbolCandles, Timeframe, ZonedCandle,
     parse_candles_api_error, parse_candles_response,
 };
-pub use crate::client::Tradernet;
+pub use crate::client::{Tradernet, TradesHistoryIter};
 pub use crate::core::{Core, WsCredentials};
+pub use crate::cps::{
+    CPS_HISTORY_PAGE_SIZE, CpsFile, CpsHistory, CpsHistoryFilters, CpsRequest, CpsStatus,
+    parse_cps_files_response, parse_cps_history_response, save_cps_files,
+};
 pub use crate::errors::TradernetError;
+#[cfg(feature = "arrow")]
+pub use crate::export::to_record_batch;
+#[cfg(feature = "csv")]
+pub use crate::export::write_csv;
+#[cfg(feature = "parquet")]
+pub use crate::export::write_parquet;
+pub use crate::export::{Cell, ColumnType, Table, ToTable};
+pub use crate::gaps::{
+    CandleGap, FillStrategy, InvalidBar, OhlcIssue, SeriesLengths, TradingCalendar,
+    ValidationReport,
+};
+pub use crate::income::{
+    FxRates, FxTable, IncomeEvent, IncomeKind, IncomePeriod, IncomeReport, IncomeSummary,
+};
+pub use crate::indicators::{
+    Atr, Bollinger, BollingerValue, Ema, Indicator, Macd, MacdValue, Obv, PriceSource, Rsi, Sma,
+    Stochastic, StochasticValue, Vwap, Wma,
+};
+pub use crate::ledger::{
+    Discrepancy, JsonLinesStore, Ledger, LedgerOrder, LedgerSnapshot, LedgerStore,
+    LedgerSyncReport, MemoryLedgerStore, OrderSource, parse_orders_history_response,
+};
+pub use crate::lots::{ClosedLot, LotEngine, LotMethod, LotTrade, OpenLot, SymbolPnl, YearlyPnl};
+pub use crate::market_time::{API_TIMEZONE, exchange_timezone, to_api_time};
+pub use crate::option_pricing::{DAYS_PER_YEAR, Greeks, OptionPricer, PricingInputs, PricingModel};
+pub use crate::quote_cache::{QuoteCache, parse_quotes_response};
+pub use crate::resample::{ResampleTarget, TradingSession};
+pub use crate::security_info::{
+    Fundamentals, MarginParams, SecurityInfo, StockData, parse_security_info_response,
+    parse_stock_data_response,
+};
+pub use crate::symbols::option_chain::{
+    OptionChain, OptionChainFilter, OptionContract, OptionExpiry, OptionRight, OptionStrike,
+    parse_option_chain_response,
+};
 pub use crate::symbols::tradernet_option::TradernetOption;
 pub use crate::symbols::tradernet_symbol::TradernetSymbol;
 pub use crate::trades_history::{
-    MaxTradeIdRow, TradeRow, TradesHistoryApiError, TradesHistoryResponse,
+    MaxTradeIdRow, TRADES_HISTORY_PAGE_SIZE, TRADES_HISTORY_REQUEST_INTERVAL, TradeRow, TradeSide,
+    TradesHistoryApiError, TradesHistoryFilters, TradesHistoryResponse, TypedTradeRow,
     parse_trades_history_api_error, parse_trades_history_response,
 };
 pub use crate::user_data::UserDataResponse;
 pub use crate::ws::{TradernetWebsocket, TradernetWsSession};
+pub use crate::ws_blocking::{BlockingWsEvents, BlockingWsSession};
+pub use crate::ws_record::{RecordedFrame, ReplaySession, ReplaySpeed, WsRecordConfig};
 pub use crate::ws_types::{
     MarketDepthEvent, MarketDepthRow, MarketDepthSide, MarketDepthUpdate, MarketInfoRow,
     MarketsEvent, MarketsUpdate, OrderDataRow, OrderTradeInfo, OrdersEvent, PortfolioAccountRow,
     PortfolioEvent, PortfolioPositionRow, PortfolioTradeRow, PortfolioUpdate, QuoteEvent,
-    SubscribeRequest, UnsubscribeRequest, WsEvent, WsReconnectConfig,
+    SubscribeRequest, UnsubscribeRequest, WsChannel, WsError, WsEvent, WsReconnectConfig,
 };
@@ -0,0 +1,510 @@
+use crate::trades_history::{TradeRow, TradeSide, TypedTradeRow};
+use chrono::{Datelike, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// How closing trades are matched against open lots.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum LotMethod {
+    /// Oldest lot first.
+    #[default]
+    Fifo,
+    /// Newest lot first.
+    Lifo,
+    /// Open lots are merged into a single lot at the average cost.
+    AverageCost,
+}
+
+/// Trade consumed by [`LotEngine`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LotTrade {
+    pub trade_id: Option<i64>,
+    pub symbol: String,
+    pub currency: String,
+    pub side: TradeSide,
+    /// Unsigned traded quantity.
+    pub quantity: Decimal,
+    pub price: Decimal,
+    /// Commission paid for the whole trade.
+    pub commission: Decimal,
+    pub time: NaiveDateTime,
+}
+
+impl LotTrade {
+    /// Converts a `getTradesHistory` row, see [`LotTrade::from_typed`].
+    pub fn from_trade_row(row: &TradeRow) -> Option<Self> {
+        Self::from_typed(&row.typed())
+    }
+
+    /// Converts a typed trades history row.
+    ///
+    /// Returns `None` when the symbol, side, a positive quantity, the price or the
+    /// date is missing. The commission is read from `commiss_exchange`.
+    pub fn from_typed(row: &TypedTradeRow) -> Option<Self> {
+        Some(Self {
+            trade_id: row.id,
+            symbol: row.instr_nm.clone()?,
+            currency: row.curr_c.clone().unwrap_or_default(),
+            side: row.side?,
+            quantity: row.q.filter(|quantity| *quantity > Decimal::ZERO)?,
+            price: row.p?,
+            commission: row
+                .commiss_exchange
+                .map_or(Decimal::ZERO, |value| value.abs()),
+            time: row.date?,
+        })
+    }
+}
+
+/// Open position lot.
+///
+/// `quantity` is positive for long and negative for short lots. `cost` is the cash
+/// paid to open the lot including commission, negative for short lots where cash
+/// was received.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenLot {
+    /// Opening trade id; `None` for merged average-cost lots.
+    pub trade_id: Option<i64>,
+    pub symbol: String,
+    pub currency: String,
+    pub opened: NaiveDateTime,
+    pub quantity: Decimal,
+    pub cost: Decimal,
+    /// Opening commission still attributed to the lot.
+    pub commission: Decimal,
+}
+
+impl OpenLot {
+    /// Returns `true` for short lots.
+    pub fn is_short(&self) -> bool {
+        self.quantity < Decimal::ZERO
+    }
+
+    /// Returns the cost per unit including commission.
+    pub fn unit_cost(&self) -> Decimal {
+        if self.quantity.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.cost / self.quantity
+        }
+    }
+
+    /// Returns the unrealized P&L at `mark`.
+    pub fn unrealized(&self, mark: Decimal) -> Decimal {
+        mark * self.quantity - self.cost
+    }
+}
+
+/// Lot (or part of a lot) closed by a trade.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClosedLot {
+    pub symbol: String,
+    pub currency: String,
+    pub open_trade_id: Option<i64>,
+    pub close_trade_id: Option<i64>,
+    pub opened: NaiveDateTime,
+    pub closed: NaiveDateTime,
+    /// Closed quantity, negative for short lots.
+    pub quantity: Decimal,
+    /// Opening cash flow allocated to the closed quantity, including commission.
+    pub cost: Decimal,
+    /// Closing cash flow net of commission, negative when buying to cover.
+    pub proceeds: Decimal,
+    /// Opening and closing commission allocated to the closed quantity.
+    pub commission: Decimal,
+    pub realized: Decimal,
+}
+
+/// Realized and unrealized P&L of one symbol.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SymbolPnl {
+    pub symbol: String,
+    pub currency: String,
+    /// Net open quantity, negative when short.
+    pub quantity: Decimal,
+    /// Cost of open lots.
+    pub cost: Decimal,
+    pub realized: Decimal,
+    /// Unrealized P&L at the supplied mark price, if any.
+    pub unrealized: Option<Decimal>,
+    /// Commission of closed and open lots.
+    pub commission: Decimal,
+}
+
+/// Realized P&L of one calendar year in one currency.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct YearlyPnl {
+    pub year: i32,
+    pub currency: String,
+    pub realized: Decimal,
+    /// Sum of profitable closed lots.
+    pub gains: Decimal,
+    /// Sum of losing closed lots (negative).
+    pub losses: Decimal,
+    pub commission: Decimal,
+    /// Number of closed lots.
+    pub closed_lots: usize,
+}
+
+/// Tax-lot engine matching trades into open and closed lots.
+///
+/// Trades must be pushed in chronological order; [`LotEngine::from_rows`] sorts
+/// them by date and trade id.
+#[derive(Debug, Clone, Default)]
+pub struct LotEngine {
+    method: LotMethod,
+    open: BTreeMap<String, VecDeque<OpenLot>>,
+    closed: Vec<ClosedLot>,
+}
+
+impl LotEngine {
+    /// Creates an empty engine.
+    pub fn new(method: LotMethod) -> Self {
+        Self {
+            method,
+            open: BTreeMap::new(),
+            closed: Vec::new(),
+        }
+    }
+
+    /// Builds an engine from trades history rows, skipping rows that cannot be
+    /// converted with [`LotTrade::from_trade_row`].
+    pub fn from_rows<'a, I>(method: LotMethod, rows: I) -> Self
+    where
+        I: IntoIterator<Item = &'a TradeRow>,
+    {
+        let mut trades = rows
+            .into_iter()
+            .filter_map(|row| {
+                let trade = LotTrade::from_trade_row(row);
+                if trade.is_none() {
+                    log::warn!("trade row skipped by lot engine: {:?}", row.id);
+                }
+                trade
+            })
+            .collect::<Vec<_>>();
+        trades.sort_by_key(|trade| (trade.time, trade.trade_id));
+
+        let mut engine = Self::new(method);
+        for trade in trades {
+            engine.push(trade);
+        }
+        engine
+    }
+
+    /// Returns the matching method.
+    pub fn method(&self) -> LotMethod {
+        self.method
+    }
+
+    /// Applies a trade, closing opposite lots first and opening a lot with the rest.
+    pub fn push(&mut self, trade: LotTrade) {
+        if trade.quantity <= Decimal::ZERO {
+            return;
+        }
+        let sign = match trade.side {
+            TradeSide::Buy => Decimal::ONE,
+            TradeSide::Sell => Decimal::NEGATIVE_ONE,
+        };
+        let lots = self.open.entry(trade.symbol.clone()).or_default();
+        let mut remaining = trade.quantity;
+
+        while remaining > Decimal::ZERO {
+            let lot = match self.method {
+                LotMethod::Lifo => lots.back_mut(),
+                LotMethod::Fifo | LotMethod::AverageCost => lots.front_mut(),
+            };
+            let Some(lot) = lot.filter(|lot| lot.is_short() == (trade.side == TradeSide::Buy))
+            else {
+                break;
+            };
+
+            let lot_size = lot.quantity.abs();
+            let matched = remaining.min(lot_size);
+            let (cost, open_commission) = if matched == lot_size {
+                (lot.cost, lot.commission)
+            } else {
+                let share = matched / lot_size;
+                (lot.cost * share, lot.commission * share)
+            };
+            let close_commission = allocate(trade.commission, matched, trade.quantity);
+            let proceeds = -sign * trade.price * matched - close_commission;
+
+            self.closed.push(ClosedLot {
+                symbol: trade.symbol.clone(),
+                currency: trade.currency.clone(),
+                open_trade_id: lot.trade_id,
+                close_trade_id: trade.trade_id,
+                opened: lot.opened,
+                closed: trade.time,
+                quantity: -sign * matched,
+                cost,
+                proceeds,
+                commission: open_commission + close_commission,
+                realized: proceeds - cost,
+            });
+
+            lot.quantity += sign * matched;
+            lot.cost -= cost;
+            lot.commission -= open_commission;
+            if lot.quantity.is_zero() {
+                match self.method {
+                    LotMethod::Lifo => lots.pop_back(),
+                    LotMethod::Fifo | LotMethod::AverageCost => lots.pop_front(),
+                };
+            }
+            remaining -= matched;
+        }
+
+        if remaining > Decimal::ZERO {
+            let commission = allocate(trade.commission, remaining, trade.quantity);
+            lots.push_back(OpenLot {
+                trade_id: trade.trade_id,
+                symbol: trade.symbol,
+                currency: trade.currency,
+                opened: trade.time,
+                quantity: sign * remaining,
+                cost: sign * trade.price * remaining + commission,
+                commission,
+            });
+            if self.method == LotMethod::AverageCost && lots.len() > 1 {
+                let merged = lots
+                    .drain(..)
+                    .reduce(|mut merged, lot| {
+                        merged.trade_id = None;
+                        merged.opened = merged.opened.min(lot.opened);
+                        merged.quantity += lot.quantity;
+                        merged.cost += lot.cost;
+                        merged.commission += lot.commission;
+                        merged
+                    })
+                    .expect("at least two lots");
+                lots.push_back(merged);
+            }
+        }
+    }
+
+    /// Returns open lots of all symbols.
+    pub fn open_lots(&self) -> impl Iterator<Item = &OpenLot> {
+        self.open.values().flatten()
+    }
+
+    /// Returns open lots of `symbol` in matching order.
+    pub fn open_lots_for(&self, symbol: &str) -> impl Iterator<Item = &OpenLot> {
+        self.open.get(symbol).into_iter().flatten()
+    }
+
+    /// Returns closed lots in closing order.
+    pub fn closed_lots(&self) -> &[ClosedLot] {
+        &self.closed
+    }
+
+    /// Returns per-symbol P&L; unrealized P&L is computed for symbols found in `marks`.
+    pub fn symbol_pnl(&self, marks: &HashMap<String, Decimal>) -> Vec<SymbolPnl> {
+        let mut pnl = BTreeMap::<&str, SymbolPnl>::new();
+        for lot in &self.closed {
+            let entry = pnl.entry(&lot.symbol).or_insert_with(|| SymbolPnl {
+                symbol: lot.symbol.clone(),
+                currency: lot.currency.clone(),
+                ..SymbolPnl::default()
+            });
+            entry.realized += lot.realized;
+            entry.commission += lot.commission;
+        }
+        for lot in self.open_lots() {
+            let entry = pnl.entry(&lot.symbol).or_insert_with(|| SymbolPnl {
+                symbol: lot.symbol.clone(),
+                currency: lot.currency.clone(),
+                ..SymbolPnl::default()
+            });
+            entry.quantity += lot.quantity;
+            entry.cost += lot.cost;
+            entry.commission += lot.commission;
+        }
+        pnl.into_values()
+            .map(|mut entry| {
+                entry.unrealized = marks
+                    .get(&entry.symbol)
+                    .map(|mark| *mark * entry.quantity - entry.cost);
+                entry
+            })
+            .collect()
+    }
+
+    /// Returns realized P&L grouped by closing year and currency.
+    pub fn yearly_report(&self) -> Vec<YearlyPnl> {
+        let mut report = BTreeMap::<(i32, &str), YearlyPnl>::new();
+        for lot in &self.closed {
+            let year = lot.closed.year();
+            let entry = report
+                .entry((year, &lot.currency))
+                .or_insert_with(|| YearlyPnl {
+                    year,
+                    currency: lot.currency.clone(),
+                    ..YearlyPnl::default()
+                });
+            entry.realized += lot.realized;
+            if lot.realized >= Decimal::ZERO {
+                entry.gains += lot.realized;
+            } else {
+                entry.losses += lot.realized;
+            }
+            entry.commission += lot.commission;
+            entry.closed_lots += 1;
+        }
+        report.into_values().collect()
+    }
+}
+
+fn allocate(total: Decimal, part: Decimal, whole: Decimal) -> Decimal {
+    if part == whole {
+        total
+    } else {
+        total * part / whole
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LotEngine, LotMethod, LotTrade};
+    use crate::trades_history::{TradeRow, TradeSide};
+    use chrono::{NaiveDate, NaiveDateTime};
+    use rust_decimal::Decimal;
+    use std::collections::HashMap;
+
+    fn at(year: i32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, 1, day)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .expect("valid datetime")
+    }
+
+    fn trade(
+        id: i64,
+        side: TradeSide,
+        quantity: i64,
+        price: i64,
+        commission: &str,
+        time: NaiveDateTime,
+    ) -> LotTrade {
+        LotTrade {
+            trade_id: Some(id),
+            symbol: "AAPL.US".to_string(),
+            currency: "USD".to_string(),
+            side,
+            quantity: Decimal::from(quantity),
+            price: Decimal::from(price),
+            commission: commission.parse().expect("valid decimal"),
+            time,
+        }
+    }
+
+    fn engine(method: LotMethod) -> LotEngine {
+        let mut engine = LotEngine::new(method);
+        engine.push(trade(1, TradeSide::Buy, 10, 100, "1", at(2024, 1)));
+        engine.push(trade(2, TradeSide::Buy, 10, 120, "1", at(2024, 2)));
+        engine.push(trade(3, TradeSide::Sell, 15, 130, "1.5", at(2025, 3)));
+        engine
+    }
+
+    fn realized(engine: &LotEngine) -> Decimal {
+        engine.closed_lots().iter().map(|lot| lot.realized).sum()
+    }
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().expect("valid decimal")
+    }
+
+    #[test]
+    fn matching_methods_allocate_cost_and_commission() {
+        let fifo = engine(LotMethod::Fifo);
+        assert_eq!(realized(&fifo), dec("347"));
+        assert_eq!(fifo.closed_lots()[0].open_trade_id, Some(1));
+        let open = fifo.open_lots().collect::<Vec<_>>();
+        assert_eq!((open[0].trade_id, open[0].cost), (Some(2), dec("600.5")));
+
+        let lifo = engine(LotMethod::Lifo);
+        assert_eq!(realized(&lifo), dec("247"));
+        assert_eq!(
+            lifo.open_lots().next().map(|lot| lot.cost),
+            Some(dec("500.5"))
+        );
+
+        let average = engine(LotMethod::AverageCost);
+        assert_eq!(realized(&average), dec("297"));
+        let open = average.open_lots().collect::<Vec<_>>();
+        assert_eq!(open.len(), 1);
+        assert_eq!((open[0].quantity, open[0].cost), (dec("5"), dec("550.5")));
+    }
+
+    #[test]
+    fn shorts_are_covered_and_flipped() {
+        let mut engine = LotEngine::new(LotMethod::Fifo);
+        engine.push(trade(1, TradeSide::Sell, 10, 50, "1", at(2024, 1)));
+        assert!(engine.open_lots().all(|lot| lot.is_short()));
+
+        engine.push(trade(2, TradeSide::Buy, 4, 40, "0.4", at(2024, 2)));
+        engine.push(trade(3, TradeSide::Buy, 10, 45, "1", at(2024, 3)));
+
+        let closed = engine.closed_lots();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].quantity, dec("-4"));
+        assert_eq!(closed[0].realized, dec("39.2"));
+        assert_eq!(closed[1].realized, dec("28.8"));
+
+        let marks = HashMap::from([("AAPL.US".to_string(), dec("50"))]);
+        let pnl = engine.symbol_pnl(&marks);
+        assert_eq!(pnl.len(), 1);
+        assert_eq!(pnl[0].quantity, dec("4"));
+        assert_eq!(pnl[0].realized, dec("68.0"));
+        assert_eq!(pnl[0].unrealized, Some(dec("19.6")));
+        assert_eq!(pnl[0].commission, dec("2.4"));
+    }
+
+    #[test]
+    fn yearly_report_groups_by_year_and_currency() {
+        let mut engine = engine(LotMethod::Fifo);
+        let mut euro = trade(4, TradeSide::Buy, 1, 10, "0", at(2025, 4));
+        euro.symbol = "SAP.EU".to_string();
+        euro.currency = "EUR".to_string();
+        engine.push(euro.clone());
+        euro.side = TradeSide::Sell;
+        euro.price = dec("8");
+        euro.time = at(2025, 5);
+        engine.push(euro);
+
+        let report = engine.yearly_report();
+        assert_eq!(report.len(), 2);
+        assert_eq!((report[0].year, report[0].currency.as_str()), (2025, "EUR"));
+        assert_eq!(report[0].losses, dec("-2"));
+        assert_eq!(report[1].currency, "USD");
+        assert_eq!(report[1].gains, dec("347"));
+        assert_eq!(report[1].closed_lots, 2);
+    }
+
+    #[test]
+    fn engine_reads_trade_rows_in_chronological_order() {
+        let row = |id: i64, kind: i64, date: &str| TradeRow {
+            id: Some(id),
+            p: Some(10.5),
+            q: Some(2.0),
+            date: Some(date.to_string()),
+            instr_nm: Some("AAPL.US".to_string()),
+            curr_c: Some("USD".to_string()),
+            trade_type: Some(kind),
+            commiss_exchange: Some("0.1".to_string()),
+            ..TradeRow::default()
+        };
+        let rows = [
+            row(2, 2, "2024-02-01 10:00:00"),
+            row(1, 1, "2024-01-01T10:00:00"),
+            TradeRow::default(),
+        ];
+
+        let engine = LotEngine::from_rows(LotMethod::Fifo, &rows);
+
+        assert_eq!(engine.closed_lots().len(), 1);
+        assert_eq!(engine.closed_lots()[0].realized, dec("-0.2"));
+        assert_eq!(engine.open_lots().count(), 0);
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Time zone of naive datetimes accepted by the Tradernet API (Moscow time).
+///
+/// `getHloc` reads `date_from`/`date_to` as wall-clock times in this zone.
+pub const API_TIMEZONE: Tz = chrono_tz::Europe::Moscow;
+
+/// Returns the local time zone of the market a symbol is listed on.
+///
+/// The market is derived from the ticker suffix (`AAPL.US`, `SAP.EU`, `KAP.KZ`,
+/// ...). Option tickers (`+FRHC.16SEP2022.C55`) are US-listed. Unknown suffixes
+/// fall back to [`API_TIMEZONE`].
+pub fn exchange_timezone(symbol: &str) -> Tz {
+    let symbol = symbol.trim();
+    if symbol.starts_with('+') {
+        return chrono_tz::America::New_York;
+    }
+    let suffix = symbol
+        .rsplit_once('.')
+        .map(|(_, suffix)| suffix.to_ascii_uppercase())
+        .unwrap_or_default();
+    match suffix.as_str() {
+        "US" => chrono_tz::America::New_York,
+        "EU" => chrono_tz::Europe::Berlin,
+        "UK" => chrono_tz::Europe::London,
+        "KZ" | "AIX" => chrono_tz::Asia::Almaty,
+        "HK" => chrono_tz::Asia::Hong_Kong,
+        "UZ" => chrono_tz::Asia::Tashkent,
+        _ => API_TIMEZONE,
+    }
+}
+
+/// Converts a zone-aware datetime to the naive wall-clock time expected by the API.
+pub fn to_api_time<Z: TimeZone>(datetime: &DateTime<Z>) -> NaiveDateTime {
+    datetime.with_timezone(&API_TIMEZONE).naive_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{API_TIMEZONE, exchange_timezone, to_api_time};
+    use crate::candles::CandlePoint;
+    use chrono::{NaiveDate, TimeZone, Timelike, Utc};
+
+    #[test]
+    fn api_time_is_moscow_wall_clock() {
+        let start = Utc
+            .with_ymd_and_hms(2026, 1, 1, 0, 0, 0)
+            .single()
+            .expect("valid time");
+        let expected = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .and_then(|date| date.and_hms_opt(3, 0, 0))
+            .expect("valid datetime");
+        assert_eq!(to_api_time(&start), expected);
+
+        let new_york = chrono_tz::America::New_York
+            .with_ymd_and_hms(2026, 7, 1, 9, 30, 0)
+            .single()
+            .expect("valid time");
+        assert_eq!(to_api_time(&new_york).hour(), 16);
+    }
+
+    #[test]
+    fn exchange_timezone_follows_symbol_suffix() {
+        assert_eq!(exchange_timezone("AAPL.US"), chrono_tz::America::New_York);
+        assert_eq!(exchange_timezone("kap.kz"), chrono_tz::Asia::Almaty);
+        assert_eq!(
+            exchange_timezone("+FRHC.16SEP2022.C55"),
+            chrono_tz::America::New_York
+        );
+        assert_eq!(exchange_timezone("SBER"), API_TIMEZONE);
+    }
+
+    #[test]
+    fn candle_local_time_respects_dst() {
+        // 2026-01-15 14:30 UTC and 2026-07-15 13:30 UTC are both 09:30 in New York.
+        let winter = CandlePoint {
+            ts: 1_768_487_400,
+            ..CandlePoint::default()
+        };
+        let summer = CandlePoint {
+            ts: 1_784_122_200,
+            ..CandlePoint::default()
+        };
+        let zone = exchange_timezone("AAPL.US");
+
+        for candle in [winter, summer] {
+            let local = candle.local_time(zone).expect("valid timestamp");
+            assert_eq!((local.hour(), local.minute()), (9, 30));
+            assert_eq!(candle.utc().map(|utc| utc.timestamp()), Some(candle.ts));
+        }
+    }
+}
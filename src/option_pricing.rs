@@ -0,0 +1,572 @@
+use crate::errors::TradernetError;
+use crate::symbols::option_chain::OptionRight;
+use crate::symbols::tradernet_option::TradernetOption;
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Days per year used for time to expiry (ACT/365).
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+const IV_MIN: f64 = 1e-6;
+const IV_MAX: f64 = 10.0;
+const IV_TOLERANCE: f64 = 1e-10;
+const IV_MAX_ITERATIONS: usize = 100;
+
+/// Option pricing model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PricingModel {
+    /// Black-Scholes-Merton on spot with a continuous dividend yield.
+    #[default]
+    BlackScholes,
+    /// Black-76 on a futures price; the dividend yield is ignored.
+    Black76,
+}
+
+/// Market inputs for a single valuation.
+///
+/// Rates, yields and volatility are annualized and continuously compounded, e.g.
+/// `0.05` for 5%. `time_to_expiry` is in years.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PricingInputs {
+    /// Underlying spot price, or the futures price for [`PricingModel::Black76`].
+    pub spot: f64,
+    pub strike: f64,
+    pub rate: f64,
+    pub dividend_yield: f64,
+    pub volatility: f64,
+    pub time_to_expiry: f64,
+}
+
+/// Option sensitivities.
+///
+/// `vega` and `rho` are per 1.00 change (divide by 100 for one point), `theta` is
+/// per year (divide by [`DAYS_PER_YEAR`] for one day). At expiry or zero volatility
+/// only `delta` is non-zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Greeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub vega: f64,
+    pub theta: f64,
+    pub rho: f64,
+}
+
+/// Returns the option price.
+pub fn price(
+    model: PricingModel,
+    right: OptionRight,
+    inputs: &PricingInputs,
+) -> Result<f64, TradernetError> {
+    Ok(Terms::new(model, inputs)?.price(right))
+}
+
+/// Returns delta, gamma, vega, theta and rho.
+pub fn greeks(
+    model: PricingModel,
+    right: OptionRight,
+    inputs: &PricingInputs,
+) -> Result<Greeks, TradernetError> {
+    Ok(Terms::new(model, inputs)?.greeks(right))
+}
+
+/// Solves for the volatility that reproduces `market_price`; `inputs.volatility` is ignored.
+///
+/// Uses Newton steps safeguarded by bisection. Fails with
+/// [`TradernetError::InvalidInput`] when the option has expired or the price is outside
+/// the no-arbitrage bounds.
+pub fn implied_volatility(
+    model: PricingModel,
+    right: OptionRight,
+    market_price: f64,
+    inputs: &PricingInputs,
+) -> Result<f64, TradernetError> {
+    if inputs.time_to_expiry <= 0.0 {
+        return Err(TradernetError::InvalidInput(
+            "implied volatility requires time to expiry".to_string(),
+        ));
+    }
+    let with_vol = |volatility| PricingInputs {
+        volatility,
+        ..*inputs
+    };
+    let lower = price(model, right, &with_vol(0.0))?;
+    let upper = price(model, right, &with_vol(IV_MAX))?;
+    if !market_price.is_finite() || market_price < lower || market_price > upper {
+        return Err(TradernetError::InvalidInput(format!(
+            "price {market_price} is outside [{lower}, {upper}]"
+        )));
+    }
+
+    let (mut low, mut high) = (IV_MIN, IV_MAX);
+    let mut volatility = 0.3;
+    for _ in 0..IV_MAX_ITERATIONS {
+        let terms = Terms::new(model, &with_vol(volatility))?;
+        let diff = terms.price(right) - market_price;
+        if diff.abs() < IV_TOLERANCE {
+            return Ok(volatility);
+        }
+        if diff > 0.0 {
+            high = volatility;
+        } else {
+            low = volatility;
+        }
+        let vega = terms.greeks(right).vega;
+        let newton = volatility - diff / vega;
+        volatility = if vega > f64::EPSILON && newton > low && newton < high {
+            newton
+        } else {
+            (low + high) / 2.0
+        };
+        if high - low < IV_TOLERANCE {
+            return Ok(volatility);
+        }
+    }
+    Ok(volatility)
+}
+
+/// Returns the year fraction between `valuation_date` and `maturity_date` (ACT/365),
+/// floored at zero.
+pub fn time_to_expiry(valuation_date: NaiveDate, maturity_date: NaiveDate) -> f64 {
+    ((maturity_date - valuation_date).num_days().max(0) as f64) / DAYS_PER_YEAR
+}
+
+/// Prices [`TradernetOption`]s from shared market inputs, deriving strike, right and
+/// time to expiry from the option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionPricer {
+    pub model: PricingModel,
+    pub spot: f64,
+    pub rate: f64,
+    pub dividend_yield: f64,
+    pub valuation_date: NaiveDate,
+}
+
+impl OptionPricer {
+    /// Creates a Black-Scholes pricer with zero rate and dividend yield.
+    pub fn new(spot: f64, valuation_date: NaiveDate) -> Self {
+        Self {
+            model: PricingModel::default(),
+            spot,
+            rate: 0.0,
+            dividend_yield: 0.0,
+            valuation_date,
+        }
+    }
+
+    pub fn with_model(mut self, model: PricingModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_dividend_yield(mut self, dividend_yield: f64) -> Self {
+        self.dividend_yield = dividend_yield;
+        self
+    }
+
+    /// Returns the inputs for `option` at `volatility`.
+    pub fn inputs(
+        &self,
+        option: &TradernetOption,
+        volatility: f64,
+    ) -> Result<PricingInputs, TradernetError> {
+        let strike = option.strike().to_f64().ok_or_else(|| {
+            TradernetError::InvalidInput(format!("strike out of range: {}", option.strike()))
+        })?;
+        Ok(PricingInputs {
+            spot: self.spot,
+            strike,
+            rate: self.rate,
+            dividend_yield: self.dividend_yield,
+            volatility,
+            time_to_expiry: time_to_expiry(self.valuation_date, option.maturity_date()),
+        })
+    }
+
+    pub fn price(&self, option: &TradernetOption, volatility: f64) -> Result<f64, TradernetError> {
+        price(
+            self.model,
+            OptionRight::of(option),
+            &self.inputs(option, volatility)?,
+        )
+    }
+
+    pub fn greeks(
+        &self,
+        option: &TradernetOption,
+        volatility: f64,
+    ) -> Result<Greeks, TradernetError> {
+        greeks(
+            self.model,
+            OptionRight::of(option),
+            &self.inputs(option, volatility)?,
+        )
+    }
+
+    pub fn implied_volatility(
+        &self,
+        option: &TradernetOption,
+        market_price: f64,
+    ) -> Result<f64, TradernetError> {
+        implied_volatility(
+            self.model,
+            OptionRight::of(option),
+            market_price,
+            &self.inputs(option, 0.0)?,
+        )
+    }
+}
+
+/// Generalized Black-Scholes terms with cost of carry `b`
+/// (`r - q` for Black-Scholes, `0` for Black-76).
+struct Terms {
+    model: PricingModel,
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    carry: f64,
+    volatility: f64,
+    time: f64,
+}
+
+impl Terms {
+    fn new(model: PricingModel, inputs: &PricingInputs) -> Result<Self, TradernetError> {
+        let values = [
+            inputs.spot,
+            inputs.strike,
+            inputs.rate,
+            inputs.dividend_yield,
+            inputs.volatility,
+            inputs.time_to_expiry,
+        ];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(TradernetError::InvalidInput(
+                "pricing inputs must be finite".to_string(),
+            ));
+        }
+        if inputs.spot <= 0.0 || inputs.strike <= 0.0 {
+            return Err(TradernetError::InvalidInput(
+                "spot and strike must be positive".to_string(),
+            ));
+        }
+        if inputs.volatility < 0.0 || inputs.time_to_expiry < 0.0 {
+            return Err(TradernetError::InvalidInput(
+                "volatility and time to expiry must not be negative".to_string(),
+            ));
+        }
+        let carry = match model {
+            PricingModel::BlackScholes => inputs.rate - inputs.dividend_yield,
+            PricingModel::Black76 => 0.0,
+        };
+        Ok(Self {
+            model,
+            spot: inputs.spot,
+            strike: inputs.strike,
+            rate: inputs.rate,
+            carry,
+            volatility: inputs.volatility,
+            time: inputs.time_to_expiry,
+        })
+    }
+
+    /// `e^((b - r)T)`, the discount applied to the underlying.
+    fn carry_discount(&self) -> f64 {
+        ((self.carry - self.rate) * self.time).exp()
+    }
+
+    fn discount(&self) -> f64 {
+        (-self.rate * self.time).exp()
+    }
+
+    fn forward(&self) -> f64 {
+        self.spot * (self.carry * self.time).exp()
+    }
+
+    /// `(d1, d2)`, or `None` when the distribution is degenerate.
+    fn d(&self) -> Option<(f64, f64)> {
+        let deviation = self.volatility * self.time.sqrt();
+        if deviation <= 0.0 {
+            return None;
+        }
+        let d1 = ((self.forward() / self.strike).ln() + deviation * deviation / 2.0) / deviation;
+        Some((d1, d1 - deviation))
+    }
+
+    fn price(&self, right: OptionRight) -> f64 {
+        let sign = right_sign(right);
+        let spot_leg = self.spot * self.carry_discount();
+        let strike_leg = self.strike * self.discount();
+        match self.d() {
+            Some((d1, d2)) => {
+                sign * (spot_leg * norm_cdf(sign * d1) - strike_leg * norm_cdf(sign * d2))
+            }
+            None => (sign * (spot_leg - strike_leg)).max(0.0),
+        }
+    }
+
+    fn greeks(&self, right: OptionRight) -> Greeks {
+        let sign = right_sign(right);
+        let carry_discount = self.carry_discount();
+        let Some((d1, d2)) = self.d() else {
+            let in_the_money = sign * (self.forward() - self.strike) > 0.0;
+            return Greeks {
+                delta: if in_the_money {
+                    sign * carry_discount
+                } else {
+                    0.0
+                },
+                ..Greeks::default()
+            };
+        };
+        let sqrt_time = self.time.sqrt();
+        let density = norm_pdf(d1);
+        let spot_leg = self.spot * carry_discount;
+        let strike_leg = self.strike * self.discount();
+        let price = sign * (spot_leg * norm_cdf(sign * d1) - strike_leg * norm_cdf(sign * d2));
+        let rho = match self.model {
+            PricingModel::BlackScholes => sign * self.time * strike_leg * norm_cdf(sign * d2),
+            PricingModel::Black76 => -self.time * price,
+        };
+        Greeks {
+            delta: sign * carry_discount * norm_cdf(sign * d1),
+            gamma: carry_discount * density / (self.spot * self.volatility * sqrt_time),
+            vega: spot_leg * density * sqrt_time,
+            theta: -spot_leg * density * self.volatility / (2.0 * sqrt_time)
+                - sign * (self.carry - self.rate) * spot_leg * norm_cdf(sign * d1)
+                - sign * self.rate * strike_leg * norm_cdf(sign * d2),
+            rho,
+        }
+    }
+}
+
+fn right_sign(right: OptionRight) -> f64 {
+    match right {
+        OptionRight::Call => 1.0,
+        OptionRight::Put => -1.0,
+    }
+}
+
+fn norm_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal CDF (Hart's double-precision approximation).
+fn norm_cdf(x: f64) -> f64 {
+    let z = x.abs();
+    let tail = if z > 37.0 {
+        0.0
+    } else {
+        let e = (-z * z / 2.0).exp();
+        if z < 7.071_067_811_865_47 {
+            let numerator = (((((0.035_262_496_599_891_1 * z + 0.700_383_064_443_688) * z
+                + 6.373_962_203_531_65)
+                * z
+                + 33.912_866_078_383)
+                * z
+                + 112.079_291_497_871)
+                * z
+                + 221.213_596_169_931)
+                * z
+                + 220.206_867_912_376;
+            let denominator = ((((((0.088_388_347_648_318_4 * z + 1.755_667_163_182_64) * z
+                + 16.064_177_579_207)
+                * z
+                + 86.780_732_202_946_1)
+                * z
+                + 296.564_248_779_674)
+                * z
+                + 637.333_633_378_831)
+                * z
+                + 793.826_512_519_948)
+                * z
+                + 440.413_735_824_752;
+            e * numerator / denominator
+        } else {
+            let b = z + 1.0 / (z + 2.0 / (z + 3.0 / (z + 4.0 / (z + 0.65))));
+            e / b / 2.506_628_274_631
+        }
+    };
+    if x > 0.0 { 1.0 - tail } else { tail }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        OptionPricer, PricingInputs, PricingModel, greeks, implied_volatility, norm_cdf, price,
+    };
+    use crate::symbols::option_chain::OptionRight;
+    use crate::symbols::tradernet_option::TradernetOption;
+    use chrono::NaiveDate;
+
+    fn inputs() -> PricingInputs {
+        PricingInputs {
+            spot: 100.0,
+            strike: 100.0,
+            rate: 0.05,
+            dividend_yield: 0.0,
+            volatility: 0.2,
+            time_to_expiry: 1.0,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn black_scholes_matches_reference_values() {
+        let model = PricingModel::BlackScholes;
+        assert_close(norm_cdf(1.0), 0.841_344_746_068_543, 1e-14);
+        assert_close(
+            price(model, OptionRight::Call, &inputs()).expect("valid inputs must price"),
+            10.4506,
+            1e-4,
+        );
+        assert_close(
+            price(model, OptionRight::Put, &inputs()).expect("valid inputs must price"),
+            5.5735,
+            1e-4,
+        );
+
+        let call =
+            greeks(model, OptionRight::Call, &inputs()).expect("valid inputs must have greeks");
+        assert_close(call.delta, 0.6368, 1e-4);
+        assert_close(call.gamma, 0.018_762, 1e-6);
+        assert_close(call.vega, 37.524, 1e-3);
+        assert_close(call.theta, -6.414, 1e-3);
+        assert_close(call.rho, 53.232, 1e-3);
+        let put =
+            greeks(model, OptionRight::Put, &inputs()).expect("valid inputs must have greeks");
+        assert_close(put.delta, call.delta - 1.0, 1e-12);
+        assert_close(put.rho, -41.890, 1e-3);
+
+        let expired = PricingInputs {
+            time_to_expiry: 0.0,
+            spot: 110.0,
+            ..inputs()
+        };
+        assert_close(
+            price(model, OptionRight::Call, &expired).expect("valid inputs must price"),
+            10.0,
+            1e-12,
+        );
+        assert_eq!(
+            greeks(model, OptionRight::Put, &expired)
+                .expect("valid inputs must have greeks")
+                .delta,
+            0.0
+        );
+        assert!(
+            price(
+                model,
+                OptionRight::Call,
+                &PricingInputs {
+                    spot: -1.0,
+                    ..inputs()
+                }
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn black_76_matches_reference_value_and_ignores_dividends() {
+        let futures = PricingInputs {
+            spot: 19.0,
+            strike: 19.0,
+            rate: 0.10,
+            dividend_yield: 0.5,
+            volatility: 0.28,
+            time_to_expiry: 0.75,
+        };
+        let model = PricingModel::Black76;
+
+        assert_close(
+            price(model, OptionRight::Call, &futures).expect("valid inputs must price"),
+            1.7011,
+            1e-4,
+        );
+        assert_close(
+            price(model, OptionRight::Put, &futures).expect("valid inputs must price"),
+            1.7011,
+            1e-4,
+        );
+        let call =
+            greeks(model, OptionRight::Call, &futures).expect("valid inputs must have greeks");
+        assert_close(call.rho, -0.75 * 1.7011, 1e-4);
+    }
+
+    #[test]
+    fn implied_volatility_round_trips_prices() {
+        for model in [PricingModel::BlackScholes, PricingModel::Black76] {
+            for right in [OptionRight::Call, OptionRight::Put] {
+                for strike in [60.0, 100.0, 150.0] {
+                    let inputs = PricingInputs {
+                        strike,
+                        volatility: 0.35,
+                        dividend_yield: 0.02,
+                        ..inputs()
+                    };
+                    let target = price(model, right, &inputs).expect("valid inputs must price");
+                    let solved = implied_volatility(model, right, target, &inputs)
+                        .expect("price within bounds must solve");
+                    assert_close(solved, 0.35, 1e-6);
+                }
+            }
+        }
+        assert!(
+            implied_volatility(
+                PricingModel::BlackScholes,
+                OptionRight::Call,
+                0.5,
+                &inputs()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn pricer_derives_strike_right_and_expiry_from_option() {
+        let option = TradernetOption::new("+AAPL.17JAN2025.P200").expect("valid option symbol");
+        let valuation = NaiveDate::from_ymd_opt(2024, 1, 18).expect("valid date");
+        let pricer = OptionPricer::new(190.0, valuation)
+            .with_rate(0.05)
+            .with_dividend_yield(0.005);
+
+        let inputs = pricer
+            .inputs(&option, 0.25)
+            .expect("option inputs must derive");
+        assert_eq!(inputs.strike, 200.0);
+        assert_close(inputs.time_to_expiry, 365.0 / 365.0, 1e-12);
+
+        let value = pricer
+            .price(&option, 0.25)
+            .expect("valid inputs must price");
+        assert_eq!(
+            value,
+            price(PricingModel::BlackScholes, OptionRight::Put, &inputs)
+                .expect("valid inputs must price")
+        );
+        assert!(
+            pricer
+                .greeks(&option, 0.25)
+                .expect("valid inputs must have greeks")
+                .delta
+                < 0.0
+        );
+        assert_close(
+            pricer
+                .implied_volatility(&option, value)
+                .expect("price within bounds must solve"),
+            0.25,
+            1e-6,
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::common::json_fields::api_method_error;
+use crate::errors::TradernetError;
+use crate::user_data::Quote;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Per-symbol quote snapshots assembled from incremental `q` updates.
+///
+/// The WebSocket `q` channel only sends fields that changed since the previous
+/// frame. [`QuoteCache::apply`] merges each delta into the last known snapshot,
+/// so consumers always see full bid/ask/last/volume/change values.
+#[derive(Debug, Clone, Default)]
+pub struct QuoteCache {
+    snapshots: HashMap<String, Quote>,
+}
+
+impl QuoteCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges an incremental update and returns the resulting snapshot.
+    ///
+    /// Updates without a symbol (`c`) cannot be attributed and are ignored.
+    pub fn apply(&mut self, delta: Quote) -> Option<&Quote> {
+        let symbol = delta.c.clone().filter(|symbol| !symbol.trim().is_empty())?;
+        let snapshot = self.snapshots.entry(symbol).or_default();
+        snapshot.merge_from(delta);
+        Some(snapshot)
+    }
+
+    /// Replaces snapshots with full quotes, e.g. from [`crate::Tradernet::get_quotes_typed`].
+    ///
+    /// Returns the number of quotes stored.
+    pub fn seed<I>(&mut self, quotes: I) -> usize
+    where
+        I: IntoIterator<Item = Quote>,
+    {
+        let mut stored = 0;
+        for quote in quotes {
+            let Some(symbol) = quote.c.clone().filter(|symbol| !symbol.trim().is_empty()) else {
+                continue;
+            };
+            self.snapshots.insert(symbol, quote);
+            stored += 1;
+        }
+        stored
+    }
+
+    /// Seeds snapshots from a raw `getStockQuotesJson` response.
+    pub fn seed_from_response(&mut self, response: Value) -> Result<usize, TradernetError> {
+        let quotes = parse_quotes_response(response)?;
+        Ok(self.seed(quotes))
+    }
+
+    /// Returns the snapshot for a symbol, if any update was seen.
+    pub fn get(&self, symbol: &str) -> Option<&Quote> {
+        self.snapshots.get(symbol)
+    }
+
+    /// Removes and returns the snapshot for a symbol.
+    pub fn remove(&mut self, symbol: &str) -> Option<Quote> {
+        self.snapshots.remove(symbol)
+    }
+
+    /// Iterates over all `(symbol, snapshot)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Quote)> {
+        self.snapshots
+            .iter()
+            .map(|(symbol, quote)| (symbol.as_str(), quote))
+    }
+
+    /// Returns the number of cached symbols.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` when no snapshot is cached.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Drops all snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Parses raw `getStockQuotesJson` JSON into quotes and surfaces method-level API errors.
+///
+/// Quotes are read from `result.q`, `q` or a top-level array. Rows that fail to
+/// deserialize are skipped with a warning.
+pub fn parse_quotes_response(response: Value) -> Result<Vec<Quote>, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let rows = match response {
+        Value::Array(rows) => Value::Array(rows),
+        Value::Object(mut map) => match map.remove("result") {
+            Some(Value::Object(mut result)) => result.remove("q").unwrap_or(Value::Null),
+            Some(other) => other,
+            None => map.remove("q").unwrap_or(Value::Null),
+        },
+        _ => Value::Null,
+    };
+
+    Ok(parse_quote_rows(rows))
+}
+
+fn parse_quote_rows(value: Value) -> Vec<Quote> {
+    match value {
+        Value::Array(entries) => entries.into_iter().filter_map(parse_quote_row).collect(),
+        Value::Object(_) => parse_quote_row(value).into_iter().collect(),
+        Value::Null => Vec::new(),
+        other => {
+            log::warn!("quotes expected array/object, got: {other}");
+            Vec::new()
+        }
+    }
+}
+
+fn parse_quote_row(value: Value) -> Option<Quote> {
+    match value {
+        Value::Object(map) => match serde_json::from_value::<Quote>(Value::Object(map)) {
+            Ok(quote) => Some(quote),
+            Err(err) => {
+                log::warn!("quote row parse failed: {err}");
+                None
+            }
+        },
+        Value::Null => None,
+        other => {
+            log::warn!("quote row expected object, got: {other}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuoteCache, parse_quotes_response};
+    use crate::user_data::Quote;
+    use serde_json::json;
+
+    fn quote(payload: serde_json::Value) -> Quote {
+        serde_json::from_value(payload).expect("quote payload must parse")
+    }
+
+    #[test]
+    fn apply_merges_partial_updates_into_snapshot() {
+        let mut cache = QuoteCache::new();
+        cache.apply(quote(json!({
+            "c": "AAPL.US", "bbp": 190.1, "bap": 190.2, "ltp": 190.15, "vol": 1000, "chg": 1.5
+        })));
+        let snapshot = cache
+            .apply(quote(json!({"c": "AAPL.US", "ltp": 190.3})))
+            .expect("snapshot must exist")
+            .clone();
+
+        assert_eq!(snapshot.ltp, Some(190.3));
+        assert_eq!(snapshot.bbp, Some(190.1));
+        assert_eq!(snapshot.bap, Some(190.2));
+        assert_eq!(snapshot.vol, Some(1000.0));
+        assert_eq!(snapshot.chg, Some(1.5));
+    }
+
+    #[test]
+    fn apply_ignores_updates_without_symbol() {
+        let mut cache = QuoteCache::new();
+        assert!(cache.apply(quote(json!({"ltp": 1.0}))).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn seed_from_response_reads_result_q_rows() {
+        let mut cache = QuoteCache::new();
+        let stored = cache
+            .seed_from_response(json!({
+                "result": {"q": [
+                    {"c": "AAPL.US", "bbp": 190.0, "bap": 190.5},
+                    "invalid",
+                    {"c": "TSLA.US", "ltp": 250.0}
+                ]}
+            }))
+            .expect("seed must succeed");
+
+        assert_eq!(stored, 2);
+        cache.apply(quote(json!({"c": "AAPL.US", "ltp": 190.2})));
+        let aapl = cache.get("AAPL.US").expect("AAPL snapshot");
+        assert_eq!(aapl.bbp, Some(190.0));
+        assert_eq!(aapl.ltp, Some(190.2));
+        assert_eq!(cache.get("TSLA.US").and_then(|q| q.ltp), Some(250.0));
+    }
+
+    #[test]
+    fn parse_quotes_response_returns_method_error() {
+        let error = parse_quotes_response(json!({"errMsg": "Bad json", "code": 2}))
+            .expect_err("must return method error");
+        assert!(error.to_string().contains("api method error (2): Bad json"));
+    }
+}
@@ -0,0 +1,392 @@
+use crate::candles::{CandlePoint, SymbolCandles};
+use crate::errors::TradernetError;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Target bar size for [`SymbolCandles::resample`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ResampleTarget {
+    /// Intraday bars of `n` minutes (e.g. `2`, `30`), anchored at the session start.
+    Minutes(u32),
+    /// Intraday bars of `n` hours (e.g. `4`), anchored at the session start.
+    Hours(u32),
+    /// One bar per trading day.
+    Daily,
+    /// One bar per calendar week (Monday to Sunday).
+    Weekly,
+    /// One bar per calendar month.
+    Monthly,
+}
+
+/// Trading day boundaries used to bucket candles.
+///
+/// A trading day starts at `day_start` in the exchange time zone, following its
+/// daylight saving rules; bars before `day_start` belong to the previous trading
+/// day. The default is midnight UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradingSession {
+    /// Exchange time zone, e.g. from [`crate::market_time::exchange_timezone`].
+    pub timezone: Tz,
+    /// Local time at which a trading day starts.
+    pub day_start: NaiveTime,
+}
+
+impl Default for TradingSession {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            day_start: NaiveTime::MIN,
+        }
+    }
+}
+
+impl TradingSession {
+    /// Creates session boundaries from an exchange time zone and local day start.
+    pub fn new(timezone: Tz, day_start: NaiveTime) -> Self {
+        Self {
+            timezone,
+            day_start,
+        }
+    }
+
+    /// Returns the Unix timestamp of the bucket containing `ts`.
+    pub(crate) fn bucket_timestamp(&self, ts: i64, target: ResampleTarget) -> Option<i64> {
+        self.local(ts)
+            .and_then(|local| self.timestamp_of(self.bucket_start(local, target)))
+    }
+
+    fn local(&self, ts: i64) -> Option<NaiveDateTime> {
+        DateTime::from_timestamp(ts, 0).map(|utc| utc.with_timezone(&self.timezone).naive_local())
+    }
+
+    /// Resolves a local bucket start; ambiguous times take the earlier instant and
+    /// times skipped by a DST jump move forward by the gap.
+    fn timestamp_of(&self, local: NaiveDateTime) -> Option<i64> {
+        local
+            .and_local_timezone(self.timezone)
+            .earliest()
+            .or_else(|| {
+                (local + TimeDelta::hours(1))
+                    .and_local_timezone(self.timezone)
+                    .earliest()
+            })
+            .map(|datetime| datetime.timestamp())
+    }
+
+    fn trading_date(&self, local: NaiveDateTime) -> NaiveDate {
+        (local - (self.day_start - NaiveTime::MIN)).date()
+    }
+
+    fn day_open(&self, date: NaiveDate) -> NaiveDateTime {
+        date.and_time(self.day_start)
+    }
+
+    /// Returns the start of the `step`-second bucket anchored at the day open.
+    fn intraday_start(&self, local: NaiveDateTime, date: NaiveDate, step: i64) -> NaiveDateTime {
+        let open = self.day_open(date);
+        let elapsed = (local - open).num_seconds();
+        open + TimeDelta::seconds(elapsed - elapsed.rem_euclid(step))
+    }
+
+    /// Returns the local start of the bucket containing `local`.
+    fn bucket_start(&self, local: NaiveDateTime, target: ResampleTarget) -> NaiveDateTime {
+        let date = self.trading_date(local);
+        match target {
+            ResampleTarget::Minutes(minutes) => {
+                self.intraday_start(local, date, i64::from(minutes) * 60)
+            }
+            ResampleTarget::Hours(hours) => {
+                self.intraday_start(local, date, i64::from(hours) * 3600)
+            }
+            ResampleTarget::Daily => self.day_open(date),
+            ResampleTarget::Weekly => {
+                let monday =
+                    date - TimeDelta::days(i64::from(date.weekday().num_days_from_monday()));
+                self.day_open(monday)
+            }
+            ResampleTarget::Monthly => {
+                let first = date.with_day(1).unwrap_or(date);
+                self.day_open(first)
+            }
+        }
+    }
+}
+
+impl SymbolCandles {
+    /// Aggregates candles into larger bars using midnight-UTC trading days.
+    ///
+    /// See [`resample_with_session`](Self::resample_with_session).
+    pub fn resample(&self, target: ResampleTarget) -> Result<SymbolCandles, TradernetError> {
+        self.resample_with_session(target, TradingSession::default())
+    }
+
+    /// Aggregates candles into larger bars.
+    ///
+    /// Each output bar takes the first open, maximum high, minimum low, last close
+    /// and summed volume of its source bars; `ts` is the bucket start. Intraday
+    /// buckets are anchored at the trading day start, weekly buckets start on
+    /// Monday and monthly buckets on the first day of the month. Input order does
+    /// not matter; empty buckets are not emitted.
+    pub fn resample_with_session(
+        &self,
+        target: ResampleTarget,
+        session: TradingSession,
+    ) -> Result<SymbolCandles, TradernetError> {
+        if matches!(
+            target,
+            ResampleTarget::Minutes(0) | ResampleTarget::Hours(0)
+        ) {
+            return Err(TradernetError::InvalidInput(
+                "resample interval must be positive".to_string(),
+            ));
+        }
+
+        let mut items = self.items.clone();
+        items.sort_by_key(|item| item.ts);
+
+        let mut bars: Vec<CandlePoint> = Vec::new();
+        for item in items {
+            let Some(bucket_ts) = session.bucket_timestamp(item.ts, target) else {
+                log::warn!("candle timestamp out of range, skipped: {}", item.ts);
+                continue;
+            };
+
+            match bars.last_mut() {
+                Some(bar) if bar.ts == bucket_ts => {
+                    bar.high = bar.high.max(item.high);
+                    bar.low = bar.low.min(item.low);
+                    bar.close = item.close;
+                    bar.volume = bar.volume.saturating_add(item.volume);
+                }
+                _ => bars.push(CandlePoint {
+                    ts: bucket_ts,
+                    ..item
+                }),
+            }
+        }
+
+        Ok(SymbolCandles {
+            symbol: self.symbol.clone(),
+            items: bars,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResampleTarget, TradingSession};
+    use crate::candles::{CandlePoint, SymbolCandles, parse_candles_response};
+    use chrono::NaiveTime;
+    use chrono_tz::{America, Europe};
+    use serde_json::json;
+
+    fn bar(ts: i64, open: f64, high: f64, low: f64, close: f64, volume: i64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open,
+            high,
+            low,
+            close,
+            volume,
+        }
+    }
+
+    fn minute_fixture() -> SymbolCandles {
+        // 2023-11-14 22:13:00 UTC .. 22:18:00 UTC, one-minute bars.
+        let payload = json!({
+            "hloc": {"AAPL.US": [
+                [10.5, 9.8, 10.0, 10.2],
+                [10.9, 10.1, 10.2, 10.7],
+                [10.8, 10.3, 10.7, 10.4],
+                [10.6, 10.0, 10.4, 10.1],
+                [11.2, 10.1, 10.1, 11.0],
+                [11.1, 10.9, 11.0, 10.95]
+            ]},
+            "vl": {"AAPL.US": [100, 200, 300, 400, 500, 600]},
+            "xSeries": {"AAPL.US": [
+                1699999980, 1700000040, 1700000100, 1700000160, 1700000220, 1700000280
+            ]}
+        });
+        parse_candles_response(payload)
+            .expect("must parse candles")
+            .series_for_symbol("AAPL.US")
+    }
+
+    #[test]
+    fn resample_two_minutes_aggregates_ohlcv() {
+        let series = minute_fixture()
+            .resample(ResampleTarget::Minutes(2))
+            .expect("valid target");
+
+        assert_eq!(
+            series.items,
+            vec![
+                bar(1_699_999_920, 10.0, 10.5, 9.8, 10.2, 100),
+                bar(1_700_000_040, 10.2, 10.9, 10.1, 10.4, 500),
+                bar(1_700_000_160, 10.4, 11.2, 10.0, 11.0, 900),
+                bar(1_700_000_280, 11.0, 11.1, 10.9, 10.95, 600),
+            ]
+        );
+    }
+
+    #[test]
+    fn resample_is_order_independent_and_rejects_zero_interval() {
+        let mut shuffled = minute_fixture();
+        shuffled.items.reverse();
+
+        assert_eq!(
+            shuffled
+                .resample(ResampleTarget::Minutes(30))
+                .expect("valid target"),
+            minute_fixture()
+                .resample(ResampleTarget::Minutes(30))
+                .expect("valid target")
+        );
+        assert!(minute_fixture().resample(ResampleTarget::Hours(0)).is_err());
+    }
+
+    #[test]
+    fn resample_hours_anchor_at_session_start() {
+        // US session: 09:30 New York (UTC-5). Bars at 09:30, 13:00, 13:30 and 15:59 local.
+        let session = TradingSession::new(
+            America::New_York,
+            NaiveTime::from_hms_opt(9, 30, 0).expect("valid time"),
+        );
+        let open = 1_700_058_600; // 2023-11-15 14:30:00 UTC
+        let series = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![
+                bar(open, 1.0, 2.0, 0.5, 1.5, 10),
+                bar(open + 12_600, 1.5, 3.0, 1.0, 2.5, 20),
+                bar(open + 14_400, 2.5, 2.6, 2.0, 2.1, 30),
+                bar(open + 23_340, 2.1, 2.2, 1.9, 2.0, 40),
+            ],
+        };
+
+        let resampled = series
+            .resample_with_session(ResampleTarget::Hours(4), session)
+            .expect("valid target");
+
+        assert_eq!(
+            resampled.items,
+            vec![
+                bar(open, 1.0, 3.0, 0.5, 2.5, 30),
+                bar(open + 14_400, 2.5, 2.6, 1.9, 2.0, 70),
+            ]
+        );
+    }
+
+    #[test]
+    fn resample_daily_uses_session_boundaries() {
+        // Moscow trading day starting at 07:00 local (UTC+3): a 02:00 local bar
+        // belongs to the previous trading day.
+        let session = TradingSession::new(
+            Europe::Moscow,
+            NaiveTime::from_hms_opt(7, 0, 0).expect("valid time"),
+        );
+        let day_open = 1_700_020_800; // 2023-11-15 04:00:00 UTC = 07:00 MSK
+        let series = SymbolCandles {
+            symbol: "SBER".to_string(),
+            items: vec![
+                bar(day_open - 5 * 3600, 1.0, 1.0, 1.0, 1.0, 1),
+                bar(day_open, 2.0, 2.5, 1.5, 2.2, 2),
+                bar(day_open + 10 * 3600, 2.2, 2.4, 2.1, 2.3, 3),
+            ],
+        };
+
+        let daily = series
+            .resample_with_session(ResampleTarget::Daily, session)
+            .expect("valid target");
+
+        assert_eq!(
+            daily.items,
+            vec![
+                bar(day_open - 86_400, 1.0, 1.0, 1.0, 1.0, 1),
+                bar(day_open, 2.0, 2.5, 1.5, 2.3, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn resample_daily_follows_daylight_saving_time() {
+        // 09:30 New York is 13:30 UTC in summer (EDT) and 14:30 UTC in winter (EST);
+        // DST ended on 2023-11-05.
+        let session = TradingSession::new(
+            America::New_York,
+            NaiveTime::from_hms_opt(9, 30, 0).expect("valid time"),
+        );
+        let friday_open = 1_698_931_800; // 2023-11-03 13:30:00 UTC
+        let monday_open = 1_699_281_000; // 2023-11-06 14:30:00 UTC
+        let series = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            items: vec![
+                bar(friday_open + 60, 1.0, 1.2, 0.9, 1.1, 1),
+                bar(monday_open - 1800, 1.1, 1.3, 1.0, 1.2, 2),
+                bar(monday_open + 60, 1.2, 1.4, 1.1, 1.3, 3),
+            ],
+        };
+
+        let daily = series
+            .resample_with_session(ResampleTarget::Daily, session)
+            .expect("valid target");
+
+        assert_eq!(
+            daily.items,
+            vec![
+                bar(friday_open, 1.0, 1.2, 0.9, 1.1, 1),
+                // 09:00 local on Monday still belongs to the Sunday trading day.
+                bar(monday_open - 86_400, 1.1, 1.3, 1.0, 1.2, 2),
+                bar(monday_open, 1.2, 1.4, 1.1, 1.3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn resample_weekly_and_monthly_align_to_calendar() {
+        let payload = json!({
+            "hloc": {"AAPL.US": [
+                [10.0, 9.0, 9.5, 9.8],
+                [11.0, 9.7, 9.8, 10.5],
+                [12.0, 10.0, 10.5, 11.5],
+                [11.8, 11.0, 11.5, 11.2]
+            ]},
+            "vl": {"AAPL.US": [1, 2, 3, 4]},
+            // 2024-01-31 (Wed), 2024-02-02 (Fri), 2024-02-05 (Mon), 2024-03-01 (Fri)
+            "xSeries": {"AAPL.US": [1706659200, 1706832000, 1707091200, 1709251200]}
+        });
+        let daily = parse_candles_response(payload)
+            .expect("must parse candles")
+            .series_for_symbol("AAPL.US");
+
+        let weekly = daily
+            .resample(ResampleTarget::Weekly)
+            .expect("valid target");
+        assert_eq!(
+            weekly.items,
+            vec![
+                // Monday 2024-01-29
+                bar(1_706_486_400, 9.5, 11.0, 9.0, 10.5, 3),
+                // Monday 2024-02-05
+                bar(1_707_091_200, 10.5, 12.0, 10.0, 11.5, 3),
+                // Monday 2024-02-26
+                bar(1_708_905_600, 11.5, 11.8, 11.0, 11.2, 4),
+            ]
+        );
+
+        let monthly = daily
+            .resample(ResampleTarget::Monthly)
+            .expect("valid target");
+        assert_eq!(
+            monthly.items,
+            vec![
+                // 2024-01-01
+                bar(1_704_067_200, 9.5, 10.0, 9.0, 9.8, 1),
+                // 2024-02-01
+                bar(1_706_745_600, 9.8, 12.0, 9.7, 11.5, 5),
+                // 2024-03-01
+                bar(1_709_251_200, 11.5, 11.8, 11.0, 11.2, 4),
+            ]
+        );
+    }
+}
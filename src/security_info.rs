@@ -0,0 +1,265 @@
+use crate::common::json_fields::{
+    api_method_error, take, take_datetime, take_decimal, take_i64, take_string,
+};
+use crate::errors::TradernetError;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Margin parameters of an instrument.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MarginParams {
+    /// Instrument can be bought on margin.
+    pub marginable: Option<bool>,
+    /// Instrument can be sold short.
+    pub shortable: Option<bool>,
+    /// Margin discount for long positions (`x_dsc1`).
+    pub long_rate: Option<Decimal>,
+    /// Margin discount for short positions (`x_dsc1_reverse`).
+    pub short_rate: Option<Decimal>,
+    /// Initial margin per contract for derivatives.
+    pub initial_margin: Option<Decimal>,
+}
+
+/// Company fundamentals, when the API provides them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Fundamentals {
+    pub market_cap: Option<Decimal>,
+    pub pe: Option<Decimal>,
+    pub eps: Option<Decimal>,
+    pub dividend_yield: Option<Decimal>,
+    pub beta: Option<Decimal>,
+    pub shares_outstanding: Option<Decimal>,
+    pub sector: Option<String>,
+    pub industry: Option<String>,
+}
+
+/// Typed `getSecurityInfo` response.
+///
+/// Parsing is lossy: unparseable values become `None` and unknown fields are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SecurityInfo {
+    pub id: Option<i64>,
+    pub ticker: Option<String>,
+    pub name: Option<String>,
+    pub isin: Option<String>,
+    pub currency: Option<String>,
+    pub lot: Option<Decimal>,
+    pub min_step: Option<Decimal>,
+    /// Price of one `min_step` move.
+    pub step_price: Option<Decimal>,
+    /// API instrument type code.
+    pub instrument_type: Option<i64>,
+    /// API instrument kind code (e.g. common or preferred share).
+    pub instrument_kind: Option<i64>,
+    /// Exchange or market code.
+    pub market: Option<String>,
+    pub trading_status: Option<String>,
+    pub tradable: Option<bool>,
+    pub first_date: Option<NaiveDateTime>,
+    pub margin: MarginParams,
+    pub fundamentals: Fundamentals,
+    pub extra: HashMap<String, Value>,
+}
+
+/// Typed `getStockData` response.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct StockData {
+    /// Fields shared with `getSecurityInfo`; unknown fields end up in `security.extra`.
+    pub security: SecurityInfo,
+    pub description: Option<String>,
+    pub last_price: Option<Decimal>,
+    pub close_price: Option<Decimal>,
+    pub change_pct: Option<Decimal>,
+}
+
+/// Parses raw `getSecurityInfo` JSON into [`SecurityInfo`] and surfaces method-level
+/// API errors.
+pub fn parse_security_info_response(response: Value) -> Result<SecurityInfo, TradernetError> {
+    Ok(parse_security(instrument_row(response)?))
+}
+
+/// Parses raw `getStockData` JSON into [`StockData`] and surfaces method-level API errors.
+pub fn parse_stock_data_response(response: Value) -> Result<StockData, TradernetError> {
+    let mut row = instrument_row(response)?;
+    let description = take_string(&mut row, &["description", "descr", "x_descr"]);
+    let last_price = take_decimal(&mut row, &["ltp", "last_price", "price"]);
+    let close_price = take_decimal(&mut row, &["pp", "close_price", "prev_close"]);
+    let change_pct = take_decimal(&mut row, &["pcp", "change_pct", "chg_pct"]);
+    Ok(StockData {
+        security: parse_security(row),
+        description,
+        last_price,
+        close_price,
+        change_pct,
+    })
+}
+
+/// Unwraps `result`, `info`, `data` and `securities` containers down to the
+/// instrument object; arrays yield their first object.
+fn instrument_row(response: Value) -> Result<Map<String, Value>, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let mut value = response;
+    loop {
+        value = match value {
+            Value::Array(items) => items
+                .into_iter()
+                .find(Value::is_object)
+                .unwrap_or(Value::Null),
+            Value::Object(mut map) => {
+                let container = ["result", "info", "data", "securities", "security"]
+                    .into_iter()
+                    .find(|key| {
+                        map.get(*key)
+                            .is_some_and(|inner| inner.is_object() || inner.is_array())
+                    });
+                match container.and_then(|key| map.remove(key)) {
+                    Some(inner) => inner,
+                    None => return Ok(map),
+                }
+            }
+            other => {
+                if !other.is_null() {
+                    log::warn!("security info expected object, got: {other}");
+                }
+                return Ok(Map::new());
+            }
+        };
+    }
+}
+
+fn parse_security(mut row: Map<String, Value>) -> SecurityInfo {
+    let margin = MarginParams {
+        marginable: take_bool(&mut row, &["mrg", "marginal", "is_marginal", "marginable"]),
+        shortable: take_bool(&mut row, &["shortable", "is_shortable", "short_allowed"]),
+        long_rate: take_decimal(&mut row, &["x_dsc1", "margin_long", "rate_long"]),
+        short_rate: take_decimal(&mut row, &["x_dsc1_reverse", "margin_short", "rate_short"]),
+        initial_margin: take_decimal(&mut row, &["init_margin", "initial_margin", "go"]),
+    };
+    let fundamentals = Fundamentals {
+        market_cap: take_decimal(&mut row, &["market_cap", "marketCap", "mcap"]),
+        pe: take_decimal(&mut row, &["pe", "pe_ratio", "PE"]),
+        eps: take_decimal(&mut row, &["eps", "EPS"]),
+        dividend_yield: take_decimal(&mut row, &["dividend_yield", "dividendYield", "div_yield"]),
+        beta: take_decimal(&mut row, &["beta"]),
+        shares_outstanding: take_decimal(&mut row, &["shares_outstanding", "shares"]),
+        sector: take_string(&mut row, &["sector", "sector_name"]),
+        industry: take_string(&mut row, &["industry", "industry_name"]),
+    };
+    SecurityInfo {
+        id: take_i64(&mut row, &["id", "instr_id"]),
+        ticker: take_string(
+            &mut row,
+            &["ticker", "nt_ticker", "default_ticker", "c", "i"],
+        ),
+        name: take_string(&mut row, &["name", "short_name", "name2"]),
+        isin: take_string(&mut row, &["isin", "issue_nb"]),
+        currency: take_string(&mut row, &["currency", "x_curr", "curr"]),
+        lot: take_decimal(&mut row, &["lot", "lot_size", "x_lot"]),
+        min_step: take_decimal(&mut row, &["min_step", "x_min_step"]),
+        step_price: take_decimal(&mut row, &["step_price"]),
+        instrument_type: take_i64(&mut row, &["type", "instr_type", "instr_type_c"]),
+        instrument_kind: take_i64(&mut row, &["kind", "instr_kind"]),
+        market: take_string(
+            &mut row,
+            &["mkt_short_code", "mkt", "ltr", "market", "mkt_name"],
+        ),
+        trading_status: take_string(
+            &mut row,
+            &["trading_status", "TradingSessionSubID", "status"],
+        ),
+        tradable: take_bool(&mut row, &["istrade", "is_trade", "tradable"]),
+        first_date: take_datetime(&mut row, &["firstDate", "first_date"]),
+        margin,
+        fundamentals,
+        extra: row.into_iter().collect(),
+    }
+}
+
+fn take_bool(row: &mut Map<String, Value>, keys: &[&str]) -> Option<bool> {
+    match take(row, keys)? {
+        Value::Bool(value) => Some(value),
+        Value::Number(value) => value.as_f64().map(|value| value != 0.0),
+        Value::String(value) => match value.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "y" => Some(true),
+            "0" | "false" | "no" | "n" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_security_info_response, parse_stock_data_response};
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    #[test]
+    fn security_info_parses_identifiers_margin_and_extra() {
+        let info = parse_security_info_response(json!({
+            "id": 123,
+            "short_name": "Apple Inc.",
+            "nt_ticker": "AAPL.US",
+            "isin": "US0378331005",
+            "currency": "USD",
+            "lot_size": "1",
+            "min_step": 0.01,
+            "type": 1,
+            "mkt_short_code": "FIX",
+            "istrade": "1",
+            "mrg": "Y",
+            "x_dsc1": "25",
+            "firstDate": "2000-01-03",
+            "code_nm": "AAPL"
+        }))
+        .expect("security info must parse");
+
+        assert_eq!(info.ticker.as_deref(), Some("AAPL.US"));
+        assert_eq!(info.name.as_deref(), Some("Apple Inc."));
+        assert_eq!(info.min_step, Some(Decimal::new(1, 2)));
+        assert_eq!(info.lot, Some(Decimal::ONE));
+        assert_eq!(info.instrument_type, Some(1));
+        assert_eq!(info.tradable, Some(true));
+        assert_eq!(info.margin.marginable, Some(true));
+        assert_eq!(info.margin.long_rate, Some(Decimal::from(25)));
+        assert!(info.first_date.is_some());
+        assert_eq!(info.extra.get("code_nm"), Some(&json!("AAPL")));
+        assert!(parse_security_info_response(json!({"errMsg": "unknown ticker"})).is_err());
+    }
+
+    #[test]
+    fn stock_data_unwraps_containers_and_reads_fundamentals() {
+        let data = parse_stock_data_response(json!({"result": {"securities": [{
+            "c": "AAPL.US",
+            "name": "Apple",
+            "x_curr": "USD",
+            "ltr": "NASDAQ",
+            "ltp": "190.5",
+            "pcp": -1.25,
+            "market_cap": "2900000000000",
+            "pe": "bad",
+            "sector": "Technology"
+        }]}}))
+        .expect("stock data must parse");
+
+        assert_eq!(data.security.ticker.as_deref(), Some("AAPL.US"));
+        assert_eq!(data.security.market.as_deref(), Some("NASDAQ"));
+        assert_eq!(data.last_price, Some(Decimal::new(1905, 1)));
+        assert_eq!(data.change_pct, Some(Decimal::new(-125, 2)));
+        assert_eq!(
+            data.security.fundamentals.market_cap,
+            Some(Decimal::from(2_900_000_000_000_i64))
+        );
+        assert_eq!(data.security.fundamentals.pe, None);
+        assert_eq!(
+            data.security.fundamentals.sector.as_deref(),
+            Some("Technology")
+        );
+    }
+}
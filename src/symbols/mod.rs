@@ -1,5 +1,7 @@
 //! Helpers for working with symbols and options.
 
+/// Option chains grouped by expiry and strike.
+pub mod option_chain;
 /// Parsed option contract properties.
 pub mod option_properties;
 /// Option notation parser and formatter.
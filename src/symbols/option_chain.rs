@@ -0,0 +1,334 @@
+use crate::common::json_fields::{api_method_error, take_decimal, take_string};
+use crate::errors::TradernetError;
+use crate::symbols::tradernet_option::TradernetOption;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Option right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OptionRight {
+    Call,
+    Put,
+}
+
+impl OptionRight {
+    /// Right of a parsed option.
+    pub fn of(option: &TradernetOption) -> Self {
+        if option.right() == -1 {
+            OptionRight::Put
+        } else {
+            OptionRight::Call
+        }
+    }
+}
+
+/// Option contract from `getOptionsByMkt`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionContract {
+    pub option: TradernetOption,
+    /// Underlying units per contract.
+    pub contract_multiplier: Option<Decimal>,
+    /// Remaining response fields.
+    pub extra: HashMap<String, Value>,
+}
+
+/// Call and put legs sharing a strike.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionStrike {
+    pub strike: Decimal,
+    pub call: Option<OptionContract>,
+    pub put: Option<OptionContract>,
+}
+
+/// Strikes of a single expiry, ordered by strike.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionExpiry {
+    pub maturity_date: NaiveDate,
+    pub strikes: Vec<OptionStrike>,
+}
+
+/// Option chain grouped by expiry and strike.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionChain {
+    pub underlying: String,
+    /// Expiries ordered by maturity date.
+    pub expiries: Vec<OptionExpiry>,
+}
+
+/// Filter applied by [`OptionChain::filter`].
+///
+/// Moneyness is `strike / spot`; e.g. `0.9..=1.1` keeps strikes within 10% of spot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionChainFilter {
+    pub expiry_from: Option<NaiveDate>,
+    pub expiry_to: Option<NaiveDate>,
+    /// `(spot, min, max)` moneyness bounds, inclusive.
+    pub moneyness: Option<(Decimal, Decimal, Decimal)>,
+    pub right: Option<OptionRight>,
+}
+
+impl OptionChainFilter {
+    /// Keeps expiries within `from..=to`.
+    pub fn with_expiry_range(mut self, from: NaiveDate, to: NaiveDate) -> Self {
+        self.expiry_from = Some(from);
+        self.expiry_to = Some(to);
+        self
+    }
+
+    /// Keeps strikes with `min <= strike / spot <= max`.
+    pub fn with_moneyness(mut self, spot: Decimal, min: Decimal, max: Decimal) -> Self {
+        self.moneyness = Some((spot, min, max));
+        self
+    }
+
+    pub fn with_right(mut self, right: OptionRight) -> Self {
+        self.right = Some(right);
+        self
+    }
+
+    pub fn calls_only(self) -> Self {
+        self.with_right(OptionRight::Call)
+    }
+
+    pub fn puts_only(self) -> Self {
+        self.with_right(OptionRight::Put)
+    }
+
+    fn keeps_expiry(&self, date: NaiveDate) -> bool {
+        self.expiry_from.is_none_or(|from| date >= from)
+            && self.expiry_to.is_none_or(|to| date <= to)
+    }
+
+    fn keeps_strike(&self, strike: Decimal) -> bool {
+        self.moneyness.is_none_or(|(spot, min, max)| {
+            !spot.is_zero() && (min..=max).contains(&(strike / spot))
+        })
+    }
+}
+
+impl OptionChain {
+    /// Groups contracts by maturity date and strike. Later duplicates replace earlier legs.
+    pub fn from_contracts(
+        underlying: impl Into<String>,
+        contracts: impl IntoIterator<Item = OptionContract>,
+    ) -> Self {
+        let mut grouped = BTreeMap::<NaiveDate, BTreeMap<Decimal, OptionStrike>>::new();
+        for contract in contracts {
+            let strike = contract.option.strike().normalize();
+            let leg = grouped
+                .entry(contract.option.maturity_date())
+                .or_default()
+                .entry(strike)
+                .or_insert_with(|| OptionStrike {
+                    strike,
+                    call: None,
+                    put: None,
+                });
+            match OptionRight::of(&contract.option) {
+                OptionRight::Call => leg.call = Some(contract),
+                OptionRight::Put => leg.put = Some(contract),
+            }
+        }
+        Self {
+            underlying: underlying.into(),
+            expiries: grouped
+                .into_iter()
+                .map(|(maturity_date, strikes)| OptionExpiry {
+                    maturity_date,
+                    strikes: strikes.into_values().collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the expiry for a maturity date.
+    pub fn expiry(&self, maturity_date: NaiveDate) -> Option<&OptionExpiry> {
+        self.expiries
+            .iter()
+            .find(|expiry| expiry.maturity_date == maturity_date)
+    }
+
+    /// Iterates over all contracts ordered by expiry, strike and right (call first).
+    pub fn contracts(&self) -> impl Iterator<Item = &OptionContract> {
+        self.expiries
+            .iter()
+            .flat_map(|expiry| &expiry.strikes)
+            .flat_map(|strike| strike.call.iter().chain(strike.put.iter()))
+    }
+
+    /// Returns the number of contracts.
+    pub fn len(&self) -> usize {
+        self.contracts().count()
+    }
+
+    /// Returns `true` when the chain has no contracts.
+    pub fn is_empty(&self) -> bool {
+        self.expiries.is_empty()
+    }
+
+    /// Returns a chain with the contracts matching `filter`, dropping empty strikes
+    /// and expiries.
+    pub fn filter(&self, filter: &OptionChainFilter) -> OptionChain {
+        let expiries = self
+            .expiries
+            .iter()
+            .filter(|expiry| filter.keeps_expiry(expiry.maturity_date))
+            .map(|expiry| OptionExpiry {
+                maturity_date: expiry.maturity_date,
+                strikes: expiry
+                    .strikes
+                    .iter()
+                    .filter(|strike| filter.keeps_strike(strike.strike))
+                    .map(|strike| OptionStrike {
+                        strike: strike.strike,
+                        call: strike
+                            .call
+                            .clone()
+                            .filter(|_| filter.right != Some(OptionRight::Put)),
+                        put: strike
+                            .put
+                            .clone()
+                            .filter(|_| filter.right != Some(OptionRight::Call)),
+                    })
+                    .filter(|strike| strike.call.is_some() || strike.put.is_some())
+                    .collect::<Vec<_>>(),
+            })
+            .filter(|expiry| !expiry.strikes.is_empty())
+            .collect();
+        OptionChain {
+            underlying: self.underlying.clone(),
+            expiries,
+        }
+    }
+}
+
+/// Parses raw `getOptionsByMkt` JSON into an [`OptionChain`] and surfaces method-level
+/// API errors.
+///
+/// Contracts are read from a top-level array or `result`/`options`/`data`. Rows whose
+/// ticker is not valid Tradernet option notation are skipped with a warning.
+pub fn parse_option_chain_response(
+    underlying: &str,
+    response: Value,
+) -> Result<OptionChain, TradernetError> {
+    if let Some(error) = api_method_error(&response) {
+        return Err(error);
+    }
+
+    let mut rows = response;
+    while let Value::Object(mut map) = rows {
+        rows = match ["result", "options", "data"]
+            .into_iter()
+            .find_map(|key| map.remove(key))
+        {
+            Some(inner) => inner,
+            None => Value::Array(vec![Value::Object(map)]),
+        };
+    }
+    let contracts = match rows {
+        Value::Array(rows) => rows
+            .into_iter()
+            .filter_map(|row| match row {
+                Value::Object(row) => parse_contract(row),
+                other => {
+                    log::warn!("option row expected object, got: {other}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    Ok(OptionChain::from_contracts(underlying, contracts))
+}
+
+fn parse_contract(mut row: Map<String, Value>) -> Option<OptionContract> {
+    let ticker = take_string(&mut row, &["ticker", "c", "symbol", "i"])?;
+    let option = match TradernetOption::new(&ticker) {
+        Ok(option) => option,
+        Err(err) => {
+            log::warn!("option row skipped: {err}");
+            return None;
+        }
+    };
+    Some(OptionContract {
+        option,
+        contract_multiplier: take_decimal(&mut row, &["contract_multiplier", "lot", "x_lot"]),
+        extra: row.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OptionChainFilter, OptionRight, parse_option_chain_response};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn option_chain_groups_legs_by_expiry_and_strike() {
+        let chain = parse_option_chain_response(
+            "AAPL.US",
+            json!({"result": [
+                {"ticker": "+AAPL.17JAN2025.P200", "contract_multiplier": 100},
+                {"ticker": "+AAPL.17JAN2025.C200", "option_type": "CALL"},
+                {"ticker": "+AAPL.20DEC2024.C180"},
+                {"ticker": "+AAPL.17JAN2025.C220.0"},
+                {"ticker": "AAPL.US"}
+            ]}),
+        )
+        .expect("chain must parse");
+
+        assert_eq!(chain.len(), 4);
+        assert_eq!(chain.expiries[0].maturity_date, date(2024, 12, 20));
+        let january = chain.expiry(date(2025, 1, 17)).expect("January expiry");
+        assert_eq!(january.strikes.len(), 2);
+        assert_eq!(january.strikes[0].strike, Decimal::from(200));
+        let put = january.strikes[0].put.as_ref().expect("put leg");
+        assert_eq!(put.contract_multiplier, Some(Decimal::from(100)));
+        assert_eq!(OptionRight::of(&put.option), OptionRight::Put);
+        assert_eq!(
+            january.strikes[0].call.as_ref().expect("call leg").extra["option_type"],
+            json!("CALL")
+        );
+        assert!(parse_option_chain_response("AAPL.US", json!({"errMsg": "denied"})).is_err());
+    }
+
+    #[test]
+    fn option_chain_filters_expiry_moneyness_and_right() {
+        let chain = parse_option_chain_response(
+            "AAPL.US",
+            json!([
+                {"ticker": "+AAPL.20DEC2024.C180"},
+                {"ticker": "+AAPL.17JAN2025.C200"},
+                {"ticker": "+AAPL.17JAN2025.P200"},
+                {"ticker": "+AAPL.17JAN2025.C260"}
+            ]),
+        )
+        .expect("chain must parse");
+
+        let filtered = chain.filter(
+            &OptionChainFilter::default()
+                .with_expiry_range(date(2025, 1, 1), date(2025, 12, 31))
+                .with_moneyness(Decimal::from(200), Decimal::new(9, 1), Decimal::new(11, 1))
+                .puts_only(),
+        );
+
+        assert_eq!(filtered.len(), 1);
+        let leg = filtered.contracts().next().expect("one contract");
+        assert_eq!(leg.option.symbol(), "+AAPL.17JAN2025.P200");
+        assert!(
+            chain
+                .filter(
+                    &OptionChainFilter::default()
+                        .with_expiry_range(date(2026, 1, 1), date(2026, 12, 31))
+                )
+                .is_empty()
+        );
+    }
+}
@@ -1,82 +1,207 @@
+use crate::candles::{CandlePoint, SymbolCandles, Timeframe, ZonedCandle};
 use crate::client::Tradernet;
 use crate::errors::TradernetError;
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use crate::market_time::exchange_timezone;
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 
-/// Helper for downloading and parsing candle data for a symbol.
+/// Helper for downloading candle data for a symbol.
 pub struct TradernetSymbol {
     /// Symbol identifier (e.g. `AAPL.US`).
     pub symbol: String,
     /// Optional REST client instance to reuse.
     pub api: Option<Tradernet>,
-    /// Start date/time for candles.
-    pub start: NaiveDateTime,
-    /// End date/time for candles.
-    pub end: NaiveDateTime,
-    /// Parsed candle timestamps.
-    pub timestamps: Vec<NaiveDateTime>,
-    /// Parsed candle OHLC values.
-    pub candles: Vec<[f64; 4]>,
-    /// Parsed candle volumes.
-    pub volumes: Vec<i64>,
-    /// Candle timeframe in seconds.
-    pub timeframe: i64,
+    /// Start of the candle range.
+    pub start: DateTime<Utc>,
+    /// End of the candle range.
+    pub end: DateTime<Utc>,
+    /// Candle timeframe.
+    pub timeframe: Timeframe,
+    /// Exchange time zone used for local timestamps.
+    pub timezone: Tz,
+    /// Downloaded candles with UTC timestamps.
+    pub candles: SymbolCandles,
 }
 
 impl TradernetSymbol {
-    /// Creates a new symbol helper with an optional API client.
-    pub fn new(
+    /// Creates a new daily-candle helper with an optional API client.
+    ///
+    /// `start` and `end` may be given in any time zone. The exchange time zone is
+    /// derived from the symbol suffix, see [`exchange_timezone`].
+    pub fn new<Z: TimeZone>(
         symbol: &str,
         api: Option<Tradernet>,
-        start: NaiveDateTime,
-        end: NaiveDateTime,
+        start: DateTime<Z>,
+        end: DateTime<Z>,
     ) -> Self {
         Self {
             symbol: symbol.to_string(),
             api,
-            start,
-            end,
-            timestamps: Vec::new(),
-            candles: Vec::new(),
-            volumes: Vec::new(),
-            timeframe: 86_400,
+            start: start.with_timezone(&Utc),
+            end: end.with_timezone(&Utc),
+            timeframe: Timeframe::Day1,
+            timezone: exchange_timezone(symbol),
+            candles: SymbolCandles {
+                symbol: symbol.to_string(),
+                items: Vec::new(),
+            },
         }
     }
 
-    /// Downloads candles and populates `timestamps`, `candles`, and `volumes`.
+    /// Sets the candle timeframe.
+    pub fn with_timeframe(mut self, timeframe: Timeframe) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    /// Overrides the exchange time zone derived from the symbol.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Downloads candles into `candles`.
     pub fn get_data(&mut self) -> Result<&mut Self, TradernetError> {
         if self.api.is_none() {
             self.api = Some(Tradernet::new(None, None)?);
         }
 
-        let response = self
+        self.candles = self
             .api
             .as_ref()
             .expect("Tradernet instance should be available")
-            .get_candles_typed(&self.symbol, self.start, self.end, self.timeframe)?;
-
-        self.timestamps = response
-            .x_series
-            .get(&self.symbol)
-            .map(|series| {
-                series
-                    .iter()
-                    .filter_map(|seconds| DateTime::<Utc>::from_timestamp(*seconds, 0))
-                    .map(|timestamp| timestamp.naive_utc() + Duration::hours(3))
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-        self.candles = response
-            .hloc
-            .get(&self.symbol)
-            .map(|entries| {
-                entries
-                    .iter()
-                    .map(|entry| [entry.high, entry.low, entry.open, entry.close])
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-        self.volumes = response.vl.get(&self.symbol).cloned().unwrap_or_default();
+            .get_candles_range_tz(&self.symbol, self.start, self.end, self.timeframe)?;
 
         Ok(self)
     }
+
+    /// Returns downloaded candle rows.
+    pub fn items(&self) -> &[CandlePoint] {
+        &self.candles.items
+    }
+
+    /// Returns candle timestamps in UTC.
+    pub fn timestamps(&self) -> Vec<DateTime<Utc>> {
+        self.candles
+            .items
+            .iter()
+            .filter_map(CandlePoint::utc)
+            .collect()
+    }
+
+    /// Returns candle timestamps in the exchange time zone.
+    pub fn local_timestamps(&self) -> Vec<DateTime<Tz>> {
+        self.candles
+            .items
+            .iter()
+            .filter_map(|candle| candle.local_time(self.timezone))
+            .collect()
+    }
+
+    /// Returns candles with UTC and exchange-local timestamps.
+    pub fn zoned(&self) -> Vec<ZonedCandle> {
+        self.candles
+            .zoned()
+            .into_iter()
+            .map(|zoned| ZonedCandle {
+                local: zoned.utc.with_timezone(&self.timezone),
+                ..zoned
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TradernetSymbol;
+    use crate::candles::{CandlePoint, SymbolCandles};
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Timelike, Utc};
+    use chrono_tz::America::New_York;
+
+    fn candle(ts: i64) -> CandlePoint {
+        CandlePoint {
+            ts,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 1,
+        }
+    }
+
+    /// `AAPL.US` with 14:30 UTC candles on both sides of the 2024-03-10 DST switch.
+    fn symbol_across_dst() -> TradernetSymbol {
+        let moscow = FixedOffset::east_opt(3 * 3600).expect("valid offset");
+        let mut symbol = TradernetSymbol::new(
+            "AAPL.US",
+            None,
+            moscow
+                .with_ymd_and_hms(2024, 3, 8, 3, 0, 0)
+                .single()
+                .expect("valid start"),
+            moscow
+                .with_ymd_and_hms(2024, 3, 12, 3, 0, 0)
+                .single()
+                .expect("valid end"),
+        );
+        symbol.candles = SymbolCandles {
+            symbol: "AAPL.US".to_string(),
+            // 2024-03-08 14:30 UTC and 2024-03-11 14:30 UTC
+            items: vec![candle(1_709_908_200), candle(1_710_167_400)],
+        };
+        symbol
+    }
+
+    #[test]
+    fn new_converts_range_to_utc_and_derives_exchange_zone() {
+        let symbol = symbol_across_dst();
+
+        assert_eq!(
+            symbol.start,
+            Utc.with_ymd_and_hms(2024, 3, 8, 0, 0, 0)
+                .single()
+                .expect("valid time")
+        );
+        assert_eq!(symbol.timezone, New_York);
+        assert_eq!(
+            symbol.with_timezone(chrono_tz::UTC).timezone,
+            chrono_tz::UTC
+        );
+    }
+
+    #[test]
+    fn local_timestamps_follow_daylight_saving_time() {
+        let symbol = symbol_across_dst();
+
+        let utc = symbol.timestamps();
+        assert_eq!(utc.len(), 2);
+        assert!(utc.iter().all(|ts| ts.hour() == 14 && ts.minute() == 30));
+
+        let local = symbol.local_timestamps();
+        assert_eq!(
+            local
+                .iter()
+                .map(|ts| (ts.date_naive(), ts.hour()))
+                .collect::<Vec<_>>(),
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 3, 8).expect("valid date"), 9),
+                (
+                    NaiveDate::from_ymd_opt(2024, 3, 11).expect("valid date"),
+                    10
+                ),
+            ]
+        );
+
+        let zoned = symbol.zoned();
+        assert_eq!(zoned.len(), 2);
+        assert_eq!(
+            zoned.iter().map(|candle| candle.local).collect::<Vec<_>>(),
+            local
+        );
+        assert_eq!(
+            zoned.iter().map(|candle| candle.utc).collect::<Vec<_>>(),
+            utc
+        );
+        assert_eq!(zoned[1].candle, candle(1_710_167_400));
+    }
 }
@@ -1,7 +1,18 @@
+use crate::common::client_helpers::build_trades_history_params;
+use crate::common::json_fields::{decimal_from_f64, decimal_from_str, parse_date, parse_datetime};
 use crate::errors::TradernetError;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize};
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Default number of trades requested per `getTradesHistory` page.
+pub const TRADES_HISTORY_PAGE_SIZE: i64 = 100;
+
+/// Default minimum delay between consecutive `getTradesHistory` page requests.
+pub const TRADES_HISTORY_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
 
 /// Typed response for the `getTradesHistory` endpoint.
 ///
@@ -137,6 +148,143 @@ pub struct TradeRow {
     pub extra: HashMap<String, Value>,
 }
 
+/// Direction of a trade.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    /// Maps the `getTradesHistory` `type` code (`1` buy, `2` sell).
+    pub fn from_trade_type(code: i64) -> Option<Self> {
+        match code {
+            1 => Some(Self::Buy),
+            2 => Some(Self::Sell),
+            _ => None,
+        }
+    }
+}
+
+impl TradeRow {
+    /// Returns the trade direction decoded from `type`.
+    pub fn side(&self) -> Option<TradeSide> {
+        self.trade_type.and_then(TradeSide::from_trade_type)
+    }
+
+    /// Returns the row with numeric strings parsed into decimals and dates parsed.
+    pub fn typed(&self) -> TypedTradeRow {
+        TypedTradeRow::from(self)
+    }
+}
+
+/// [`TradeRow`] with amounts as [`Decimal`], parsed dates and a decoded side.
+///
+/// Conversion is lossy: values that cannot be parsed become `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct TypedTradeRow {
+    pub id: Option<i64>,
+    pub order_id: Option<i64>,
+    pub side: Option<TradeSide>,
+    /// Raw `type` code.
+    pub trade_type: Option<i64>,
+    pub date: Option<NaiveDateTime>,
+    pub instr_nm: Option<String>,
+    pub curr_c: Option<String>,
+    pub p: Option<Decimal>,
+    pub q: Option<Decimal>,
+    pub v: Option<Decimal>,
+    pub summ: Option<Decimal>,
+    pub profit: Option<Decimal>,
+    pub commiss_exchange: Option<Decimal>,
+    pub reception: Option<i64>,
+    pub login: Option<String>,
+    pub curr_q: Option<String>,
+    pub instr_type_c: Option<i64>,
+    pub mkt_id: Option<i64>,
+    pub instr_id: Option<i64>,
+    pub comment: Option<String>,
+    pub step_price: Option<Decimal>,
+    pub min_step: Option<Decimal>,
+    pub rate_offer: Option<Decimal>,
+    pub fv: Option<Decimal>,
+    pub acd: Option<Decimal>,
+    pub go_sum: Option<Decimal>,
+    pub curr_price: Option<Decimal>,
+    pub curr_price_money: Option<Decimal>,
+    pub curr_price_begin: Option<Decimal>,
+    pub curr_price_begin_money: Option<Decimal>,
+    /// Settlement date.
+    pub pay_d: Option<NaiveDate>,
+    /// Exchange trade date.
+    pub trade_d_exch: Option<NaiveDate>,
+    pub t2_confirm: Option<String>,
+    pub trade_nb: Option<i64>,
+    pub repo_close: Option<String>,
+    pub start_cash: Option<Decimal>,
+    pub end_cash: Option<Decimal>,
+    pub otc: Option<String>,
+    pub details: Option<String>,
+    pub orig_cl_ord_id: Option<String>,
+    pub extra: HashMap<String, Value>,
+}
+
+impl From<&TradeRow> for TypedTradeRow {
+    fn from(row: &TradeRow) -> Self {
+        let decimal = |value: &Option<String>| value.as_deref().and_then(decimal_from_str);
+        let float = |value: Option<f64>| value.and_then(decimal_from_f64);
+        Self {
+            id: row.id,
+            order_id: row.order_id,
+            side: row.side(),
+            trade_type: row.trade_type,
+            date: row.date.as_deref().and_then(parse_datetime),
+            instr_nm: row.instr_nm.clone(),
+            curr_c: row.curr_c.clone(),
+            p: float(row.p),
+            q: float(row.q),
+            v: float(row.v),
+            summ: float(row.summ),
+            profit: decimal(&row.profit),
+            commiss_exchange: decimal(&row.commiss_exchange),
+            reception: row.reception,
+            login: row.login.clone(),
+            curr_q: row.curr_q.clone(),
+            instr_type_c: row.instr_type_c,
+            mkt_id: row.mkt_id,
+            instr_id: row.instr_id,
+            comment: row.comment.clone(),
+            step_price: decimal(&row.step_price),
+            min_step: decimal(&row.min_step),
+            rate_offer: float(row.rate_offer),
+            fv: float(row.fv),
+            acd: decimal(&row.acd),
+            go_sum: decimal(&row.go_sum),
+            curr_price: decimal(&row.curr_price),
+            curr_price_money: decimal(&row.curr_price_money),
+            curr_price_begin: decimal(&row.curr_price_begin),
+            curr_price_begin_money: decimal(&row.curr_price_begin_money),
+            pay_d: row.pay_d.as_deref().and_then(parse_date),
+            trade_d_exch: row.trade_d_exch.as_deref().and_then(parse_date),
+            t2_confirm: row.t2_confirm.clone(),
+            trade_nb: row.trade_nb,
+            repo_close: row.repo_close.clone(),
+            start_cash: decimal(&row.start_cash),
+            end_cash: decimal(&row.end_cash),
+            otc: row.otc.clone(),
+            details: row.details.clone(),
+            orig_cl_ord_id: row.orig_cl_ord_id.clone(),
+            extra: row.extra.clone(),
+        }
+    }
+}
+
+impl From<TradeRow> for TypedTradeRow {
+    fn from(row: TradeRow) -> Self {
+        Self::from(&row)
+    }
+}
+
 /// Method-level API error returned by `getTradesHistory`.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct TradesHistoryApiError {
@@ -145,6 +293,11 @@ pub struct TradesHistoryApiError {
 }
 
 impl TradesHistoryResponse {
+    /// Returns trades converted to [`TypedTradeRow`].
+    pub fn typed_trades(&self) -> Vec<TypedTradeRow> {
+        self.trades.trade.iter().map(TypedTradeRow::from).collect()
+    }
+
     /// Returns max trade id from the response, if present.
     pub fn max_trade_id(&self) -> Option<i64> {
         self.trades
@@ -155,6 +308,169 @@ impl TradesHistoryResponse {
     }
 }
 
+/// Filters and paging options for `trades_history_iter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradesHistoryFilters {
+    pub symbol: Option<String>,
+    pub currency: Option<String>,
+    /// Reception (office id).
+    pub reception: Option<i64>,
+    /// Trade id to start from, e.g. the last stored `max_trade_id`.
+    pub from_trade_id: Option<i64>,
+    /// Trades requested per page.
+    pub page_size: i64,
+    /// Minimum delay between page requests.
+    pub request_interval: Duration,
+}
+
+impl Default for TradesHistoryFilters {
+    fn default() -> Self {
+        Self {
+            symbol: None,
+            currency: None,
+            reception: None,
+            from_trade_id: None,
+            page_size: TRADES_HISTORY_PAGE_SIZE,
+            request_interval: TRADES_HISTORY_REQUEST_INTERVAL,
+        }
+    }
+}
+
+impl TradesHistoryFilters {
+    /// Restricts trades to a symbol.
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Restricts trades to a currency.
+    pub fn with_currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// Restricts trades to a reception (office id).
+    pub fn with_reception(mut self, reception: i64) -> Self {
+        self.reception = Some(reception);
+        self
+    }
+
+    /// Resumes paging from a trade id.
+    pub fn with_from_trade_id(mut self, trade_id: i64) -> Self {
+        self.from_trade_id = Some(trade_id);
+        self
+    }
+
+    /// Sets the number of trades requested per page.
+    pub fn with_page_size(mut self, page_size: i64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets the minimum delay between page requests.
+    pub fn with_request_interval(mut self, interval: Duration) -> Self {
+        self.request_interval = interval;
+        self
+    }
+}
+
+/// Cursor state shared by the sync iterator and the async stream.
+///
+/// Pages are requested from the highest trade id seen so far; repeated rows are
+/// dropped by id. Paging stops on a short page, a page without new trades, or
+/// once `max_trade_id` is reached.
+#[derive(Debug)]
+pub(crate) struct TradesHistoryPager {
+    start: NaiveDate,
+    end: NaiveDate,
+    filters: TradesHistoryFilters,
+    next_trade_id: Option<i64>,
+    seen: HashSet<i64>,
+    last_request: Option<Instant>,
+    done: bool,
+}
+
+impl TradesHistoryPager {
+    pub(crate) fn new(
+        start: NaiveDate,
+        end: NaiveDate,
+        filters: TradesHistoryFilters,
+    ) -> Result<Self, TradernetError> {
+        if filters.page_size <= 0 {
+            return Err(TradernetError::InvalidInput(
+                "page_size must be positive".to_string(),
+            ));
+        }
+        let pager = Self {
+            start,
+            end,
+            next_trade_id: filters.from_trade_id,
+            filters,
+            seen: HashSet::new(),
+            last_request: None,
+            done: false,
+        };
+        pager.params()?;
+        Ok(pager)
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Returns how long to wait before the next request to honour the interval.
+    pub(crate) fn delay(&self) -> Option<Duration> {
+        let elapsed = self.last_request?.elapsed();
+        self.filters
+            .request_interval
+            .checked_sub(elapsed)
+            .filter(|delay| !delay.is_zero())
+    }
+
+    /// Returns parameters for the next page and marks the request time.
+    pub(crate) fn next_params(&mut self) -> Result<Map<String, Value>, TradernetError> {
+        self.last_request = Some(Instant::now());
+        self.params()
+    }
+
+    /// Stops paging, e.g. after a failed request.
+    pub(crate) fn finish(&mut self) {
+        self.done = true;
+    }
+
+    /// Consumes a page and returns trades not seen before.
+    pub(crate) fn absorb(&mut self, response: TradesHistoryResponse) -> Vec<TradeRow> {
+        let max_trade_id = response.max_trade_id();
+        let page_len = response.trades.trade.len();
+        let fresh = response
+            .trades
+            .trade
+            .into_iter()
+            .filter(|row| row.id.is_none_or(|id| self.seen.insert(id)))
+            .collect::<Vec<_>>();
+
+        let last_id = fresh.iter().filter_map(|row| row.id).max();
+        self.done = fresh.is_empty()
+            || page_len < self.filters.page_size as usize
+            || last_id.is_none()
+            || matches!((last_id, max_trade_id), (Some(last), Some(max)) if last >= max);
+        self.next_trade_id = last_id;
+        fresh
+    }
+
+    fn params(&self) -> Result<Map<String, Value>, TradernetError> {
+        build_trades_history_params(
+            self.start,
+            self.end,
+            self.next_trade_id,
+            Some(self.filters.page_size),
+            self.filters.symbol.as_deref(),
+            self.filters.currency.as_deref(),
+            self.filters.reception,
+        )
+    }
+}
+
 /// Parses raw `getTradesHistory` JSON into [`TradesHistoryResponse`] and surfaces
 /// method-level API errors.
 pub fn parse_trades_history_response(
@@ -324,10 +640,30 @@ fn parse_string(value: Option<&Value>) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::{
-        TradesHistoryResponse, parse_trades_history_api_error, parse_trades_history_response,
+        TradeSide, TradesHistoryFilters, TradesHistoryPager, TradesHistoryResponse,
+        parse_trades_history_api_error, parse_trades_history_response,
     };
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
     use serde_json::json;
 
+    fn page(max_trade_id: i64, ids: &[i64]) -> TradesHistoryResponse {
+        let trades = ids.iter().map(|id| json!({"id": id})).collect::<Vec<_>>();
+        parse_trades_history_response(json!({
+            "trades": {"max_trade_id": [{"@text": max_trade_id.to_string()}], "trade": trades}
+        }))
+        .expect("page must parse")
+    }
+
+    fn pager(page_size: i64) -> TradesHistoryPager {
+        TradesHistoryPager::new(
+            NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date"),
+            TradesHistoryFilters::default().with_page_size(page_size),
+        )
+        .expect("valid pager")
+    }
+
     #[test]
     fn trades_history_response_parses_nominal_payload() {
         let payload = json!({
@@ -426,4 +762,100 @@ mod tests {
                 .contains("api method error (7): Пользователь не найден")
         );
     }
+
+    #[test]
+    fn typed_trade_rows_parse_amounts_dates_and_side() {
+        let parsed = parse_trades_history_response(json!({
+            "trades": {"trade": [{
+                "id": 1,
+                "p": 141.4,
+                "q": "20",
+                "type": 2,
+                "date": "2019-08-15T10:10:22",
+                "pay_d": "2019-08-19 00:00:00",
+                "trade_d_exch": "2019-08-15",
+                "profit": " 12.50 ",
+                "commiss_exchange": "1e-2",
+                "curr_price": "n/a",
+                "extra_field": "keep"
+            }]}
+        }))
+        .expect("must parse");
+
+        let typed = parsed.typed_trades();
+
+        assert_eq!(typed.len(), 1);
+        let row = &typed[0];
+        assert_eq!(row.side, Some(TradeSide::Sell));
+        assert_eq!(row.p, Some(Decimal::new(1414, 1)));
+        assert_eq!(row.q, Some(Decimal::from(20)));
+        assert_eq!(row.profit, Some(Decimal::new(125, 1)));
+        assert_eq!(row.commiss_exchange, Some(Decimal::new(1, 2)));
+        assert_eq!(row.curr_price, None);
+        assert_eq!(
+            row.date.map(|date| date.to_string()),
+            Some("2019-08-15 10:10:22".to_string())
+        );
+        assert_eq!(row.pay_d, NaiveDate::from_ymd_opt(2019, 8, 19));
+        assert_eq!(row.trade_d_exch, NaiveDate::from_ymd_opt(2019, 8, 15));
+        assert_eq!(row.extra.get("extra_field"), Some(&json!("keep")));
+    }
+
+    #[test]
+    fn pager_follows_trade_id_cursor_and_dedupes() {
+        let mut pager = pager(3);
+        let first = pager.next_params().expect("valid params");
+        assert!(first.get("tradeId").is_none());
+        assert_eq!(first.get("max").and_then(|v| v.as_i64()), Some(3));
+
+        let rows = pager.absorb(page(9, &[1, 2, 3]));
+        assert_eq!(rows.len(), 3);
+        assert!(!pager.is_done());
+        let next = pager.next_params().expect("valid params");
+        assert_eq!(next.get("tradeId").and_then(|v| v.as_i64()), Some(3));
+
+        let rows = pager.absorb(page(9, &[3, 4, 5]));
+        assert_eq!(
+            rows.iter().filter_map(|row| row.id).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert!(!pager.is_done());
+
+        let rows = pager.absorb(page(9, &[5, 9]));
+        assert_eq!(rows.len(), 1);
+        assert!(pager.is_done());
+    }
+
+    #[test]
+    fn pager_resumes_from_trade_id() {
+        let mut pager = TradesHistoryPager::new(
+            NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date"),
+            TradesHistoryFilters::default().with_from_trade_id(42),
+        )
+        .expect("valid pager");
+
+        let params = pager.next_params().expect("valid params");
+        assert_eq!(params.get("tradeId").and_then(|v| v.as_i64()), Some(42));
+    }
+
+    #[test]
+    fn pager_stops_at_max_trade_id_and_on_stale_pages() {
+        let mut pager_a = pager(2);
+        pager_a.absorb(page(2, &[1, 2]));
+        assert!(pager_a.is_done());
+
+        let mut pager_b = pager(2);
+        pager_b.absorb(page(10, &[1, 2]));
+        assert!(pager_b.absorb(page(10, &[1, 2])).is_empty());
+        assert!(pager_b.is_done());
+
+        let error = TradesHistoryPager::new(
+            NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date"),
+            NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date"),
+            TradesHistoryFilters::default().with_page_size(0),
+        )
+        .expect_err("zero page size is invalid");
+        assert!(error.to_string().contains("page_size must be positive"));
+    }
 }
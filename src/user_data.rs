@@ -44,7 +44,7 @@ pub struct Quotes {
     pub q: Vec<Quote>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct Quote {
     #[serde(default, deserialize_with = "deserialize_option_f64")]
     pub acd: Option<f64>,
@@ -207,6 +207,113 @@ pub struct Quote {
     pub yld_ytm_bid: Option<i64>,
 }
 
+impl Quote {
+    /// Merges an incremental quote update into this quote.
+    ///
+    /// Only fields present in `delta` (`Some`) overwrite the current values;
+    /// fields missing from the update keep their previous state.
+    pub fn merge_from(&mut self, delta: Quote) {
+        macro_rules! merge_fields {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if delta.$field.is_some() {
+                        self.$field = delta.$field;
+                    }
+                )*
+            };
+        }
+
+        merge_fields!(
+            acd,
+            baf,
+            bac,
+            bap,
+            bas,
+            base_contract_code,
+            base_currency,
+            base_ltr,
+            bbf,
+            bbc,
+            bbp,
+            bbs,
+            c,
+            chg,
+            chg110,
+            chg22,
+            chg220,
+            chg5,
+            close_price,
+            codesub_nm,
+            cpn,
+            cpp,
+            dpb,
+            dps,
+            emitent_type,
+            fv,
+            init,
+            ipo,
+            issue_nb,
+            kind,
+            ltp,
+            ltr,
+            lts,
+            ltc,
+            ltt,
+            market_status,
+            maxtp,
+            min_step,
+            mintp,
+            mrg,
+            mtd,
+            n,
+            name,
+            name2,
+            ncd,
+            ncp,
+            op,
+            option_type,
+            otc_instr,
+            p110,
+            p22,
+            p220,
+            p5,
+            pcp,
+            pp,
+            quote_basis,
+            rev,
+            scheme_calc,
+            step_price,
+            strike_price,
+            trades,
+            trading_reference_price,
+            trading_session_sub_id,
+            quote_type,
+            utc_offset,
+            virt_base_instr,
+            vlt,
+            vol,
+            x_agg_futures,
+            x_curr,
+            x_curr_val,
+            x_descr,
+            x_dsc1,
+            x_dsc1_reception,
+            x_dsc2,
+            x_dsc3,
+            x_istrade,
+            x_lot,
+            x_max,
+            x_min,
+            x_min_lot_q,
+            x_short,
+            x_short_reception,
+            yld,
+            yld_ytm_ask,
+            yld_ytm_bid
+        );
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PortfolioSummary {
     pub loaded: bool,
@@ -1,19 +1,24 @@
+use crate::common::string_utils::ticker_words;
 use crate::core::{AsyncCore, Core, WsCredentials};
 use crate::errors::TradernetError;
+use crate::quote_cache::QuoteCache;
 use crate::user_data::Quote;
+use crate::ws_record::{WsRecordConfig, WsRecorder};
 use crate::ws_types::{
     MarketDepthEvent, MarketDepthUpdate, MarketsEvent, MarketsUpdate, OrderDataRow, OrdersEvent,
-    PortfolioEvent, PortfolioUpdate, QuoteEvent, SubscribeRequest, UnsubscribeRequest, WsEvent,
-    WsReconnectConfig,
+    PortfolioEvent, PortfolioUpdate, QuoteEvent, SubscribeRequest, UnsubscribeRequest, WsChannel,
+    WsError, WsEvent, WsReconnectConfig,
 };
 use async_stream::{stream, try_stream};
 use futures_util::stream::BoxStream;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::Value;
-use std::collections::HashSet;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tokio_tungstenite::connect_async;
@@ -24,9 +29,24 @@ use url::Url;
 pub struct TradernetWebsocket {
     credentials: WsCredentials,
     websocket_url_override: Option<String>,
+    options: SessionOptions,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SessionOptions {
+    quote_snapshots: bool,
+    record: Option<WsRecordConfig>,
 }
 
 type WsEventsRx = mpsc::UnboundedReceiver<Result<WsEvent, TradernetError>>;
+type AckTx = oneshot::Sender<Result<(), TradernetError>>;
+
+/// Buffer size of the fan-out channel feeding per-symbol streams.
+const STREAM_CHANNEL_CAPACITY: usize = 1024;
+
+/// Market data events buffered for [`TradernetWsSession::events`] before newer
+/// ones are dropped.
+const EVENTS_CHANNEL_CAPACITY: usize = 4096;
 
 /// Multi-subscription WebSocket session using a single underlying connection.
 ///
@@ -38,16 +58,31 @@ type WsEventsRx = mpsc::UnboundedReceiver<Result<WsEvent, TradernetError>>;
 ///
 /// `unsubscribe` is implemented locally (event filtering). If server-side unsubscribe
 /// is not supported by protocol, no unsubscribe command is sent.
+///
+/// Incremental `q` frames are merged into per-symbol snapshots; see
+/// [`quote_snapshot`](Self::quote_snapshot) and
+/// [`TradernetWebsocket::with_quote_snapshots`].
+///
+/// Raw frames can be written to disk with [`TradernetWebsocket::with_recording`]
+/// and played back through [`crate::ws_record::ReplaySession`].
+///
+/// Besides the single-consumer [`events`](Self::events) stream, any number of
+/// typed per-symbol streams can be opened with [`quote_stream`](Self::quote_stream),
+/// [`book_stream`](Self::book_stream) and [`order_stream`](Self::order_stream).
 pub struct TradernetWsSession {
     command_tx: mpsc::UnboundedSender<SessionCommand>,
     events_rx: Arc<Mutex<Option<WsEventsRx>>>,
+    /// Market data events queued in `events_rx`, see [`EventSink`].
+    buffered: Arc<AtomicUsize>,
+    stream_tx: broadcast::Sender<WsEvent>,
     subscriptions: Arc<Mutex<SubscriptionState>>,
+    quote_cache: Arc<Mutex<QuoteCache>>,
     closed: Arc<AtomicBool>,
     worker: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 
 enum SessionCommand {
-    Subscribe(SubscribeRequest),
+    Subscribe(SubscribeRequest, Option<AckTx>),
     Unsubscribe(UnsubscribeRequest),
     Close,
 }
@@ -59,9 +94,111 @@ struct SubscriptionState {
     portfolio: bool,
     orders: bool,
     markets: bool,
+    stream_refs: HashMap<StreamTarget, StreamRef>,
+}
+
+/// Subscription target shared by typed stream handles.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum StreamTarget {
+    Quotes(String),
+    OrderBook(String),
+    Orders,
+}
+
+impl StreamTarget {
+    fn subscribe_request(&self) -> SubscribeRequest {
+        match self {
+            StreamTarget::Quotes(symbol) => SubscribeRequest::Quotes {
+                symbols: vec![symbol.clone()],
+            },
+            StreamTarget::OrderBook(symbol) => SubscribeRequest::OrderBook {
+                symbols: vec![symbol.clone()],
+            },
+            StreamTarget::Orders => SubscribeRequest::Orders,
+        }
+    }
+
+    fn unsubscribe_request(&self) -> UnsubscribeRequest {
+        match self {
+            StreamTarget::Quotes(symbol) => UnsubscribeRequest::Quotes {
+                symbols: vec![symbol.clone()],
+            },
+            StreamTarget::OrderBook(symbol) => UnsubscribeRequest::OrderBook {
+                symbols: vec![symbol.clone()],
+            },
+            StreamTarget::Orders => UnsubscribeRequest::Orders,
+        }
+    }
+
+    fn is_requested_by(&self, req: &SubscribeRequest) -> bool {
+        match (self, req) {
+            (StreamTarget::Quotes(symbol), SubscribeRequest::Quotes { symbols })
+            | (StreamTarget::OrderBook(symbol), SubscribeRequest::OrderBook { symbols }) => {
+                symbols.contains(symbol)
+            }
+            (StreamTarget::Orders, SubscribeRequest::Orders) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Reference count of stream handles for a target.
+///
+/// `owned` is set when the first handle created the subscription, so only then
+/// is it removed after the last handle is dropped.
+#[derive(Debug, Clone, Copy, Default)]
+struct StreamRef {
+    count: usize,
+    owned: bool,
 }
 
 impl SubscriptionState {
+    fn is_active(&self, target: &StreamTarget) -> bool {
+        match target {
+            StreamTarget::Quotes(symbol) => self.quotes.contains(symbol),
+            StreamTarget::OrderBook(symbol) => self.order_book.contains(symbol),
+            StreamTarget::Orders => self.orders,
+        }
+    }
+
+    fn acquire_stream(&mut self, target: &StreamTarget) -> Option<SubscribeRequest> {
+        let active = self.is_active(target);
+        let entry = self.stream_refs.entry(target.clone()).or_default();
+        entry.count += 1;
+        if entry.count == 1 {
+            entry.owned = !active;
+        }
+        if active {
+            None
+        } else {
+            self.apply_subscribe(target.subscribe_request())
+        }
+    }
+
+    fn release_stream(&mut self, target: &StreamTarget) -> Option<UnsubscribeRequest> {
+        let entry = self.stream_refs.get_mut(target)?;
+        entry.count = entry.count.saturating_sub(1);
+        if entry.count > 0 {
+            return None;
+        }
+        let owned = entry.owned;
+        self.stream_refs.remove(target);
+        if owned {
+            self.apply_unsubscribe(target.unsubscribe_request())
+        } else {
+            None
+        }
+    }
+
+    /// Marks targets requested explicitly as no longer owned by stream handles.
+    fn disown_streams(&mut self, req: &SubscribeRequest) {
+        for (target, entry) in self.stream_refs.iter_mut() {
+            if target.is_requested_by(req) {
+                entry.owned = false;
+            }
+        }
+    }
+
     fn apply_subscribe(&mut self, req: SubscribeRequest) -> Option<SubscribeRequest> {
         match req {
             SubscribeRequest::Quotes { symbols } => {
@@ -162,7 +299,7 @@ impl SubscriptionState {
 
     fn allows_event(&self, event: &WsEvent) -> bool {
         match event {
-            WsEvent::Quote(quote) => quote
+            WsEvent::Quote(quote) | WsEvent::QuoteSnapshot(quote) => quote
                 .c
                 .as_ref()
                 .is_some_and(|symbol| self.quotes.contains(symbol)),
@@ -170,9 +307,11 @@ impl SubscriptionState {
             WsEvent::Portfolio(_) => self.portfolio,
             WsEvent::Orders(_) => self.orders,
             WsEvent::Markets(_) => self.markets,
-            WsEvent::Error(_) | WsEvent::Connected | WsEvent::Reconnecting | WsEvent::Closed => {
-                true
-            }
+            WsEvent::Error(_)
+            | WsEvent::Connected
+            | WsEvent::Reconnecting
+            | WsEvent::Closed
+            | WsEvent::Dropped(_) => true,
         }
     }
 }
@@ -223,6 +362,7 @@ impl TradernetWebsocket {
         Self {
             credentials: WsCredentials { public, private },
             websocket_url_override: None,
+            options: SessionOptions::default(),
         }
     }
 
@@ -231,6 +371,7 @@ impl TradernetWebsocket {
         Self {
             credentials: core.ws_credentials(),
             websocket_url_override: None,
+            options: SessionOptions::default(),
         }
     }
 
@@ -239,6 +380,7 @@ impl TradernetWebsocket {
         Self {
             credentials: core.ws_credentials(),
             websocket_url_override: None,
+            options: SessionOptions::default(),
         }
     }
 
@@ -250,11 +392,31 @@ impl TradernetWebsocket {
         self
     }
 
+    /// Enables [`WsEvent::QuoteSnapshot`] events in sessions opened by this client.
+    ///
+    /// Each `q` frame is still emitted as a [`WsEvent::Quote`] delta and is followed
+    /// by the merged per-symbol snapshot.
+    pub fn with_quote_snapshots(mut self, enabled: bool) -> Self {
+        self.options.quote_snapshots = enabled;
+        self
+    }
+
+    /// Records every raw text frame of sessions opened by this client.
+    ///
+    /// Frames are written before subscription filtering as JSON lines with a
+    /// receive timestamp, see [`crate::ws_record::RecordedFrame`]. The file is
+    /// created on connect and finalized when the session closes.
+    pub fn with_recording(mut self, config: WsRecordConfig) -> Self {
+        self.options.record = Some(config);
+        self
+    }
+
     /// Creates a WebSocket client from [`Core`] with custom endpoint URL.
     pub fn with_websocket_url_from_core(core: &Core, websocket_url: impl Into<String>) -> Self {
         Self {
             credentials: core.ws_credentials(),
             websocket_url_override: Some(websocket_url.into()),
+            options: SessionOptions::default(),
         }
     }
 
@@ -266,6 +428,7 @@ impl TradernetWebsocket {
         Self {
             credentials: core.ws_credentials(),
             websocket_url_override: Some(websocket_url.into()),
+            options: SessionOptions::default(),
         }
     }
 
@@ -280,7 +443,18 @@ impl TradernetWebsocket {
         reconnect: WsReconnectConfig,
     ) -> Result<TradernetWsSession, TradernetError> {
         let url = self.websocket_url_with_auth()?;
-        Ok(TradernetWsSession::start(url, reconnect))
+        let recorder = self
+            .options
+            .record
+            .as_ref()
+            .map(WsRecorder::create)
+            .transpose()?;
+        Ok(TradernetWsSession::start(
+            url,
+            reconnect,
+            self.options.clone(),
+            recorder,
+        ))
     }
 
     /// Subscribes to quote updates for a list of symbols.
@@ -308,7 +482,7 @@ impl TradernetWebsocket {
             while let Some(message) = events.next().await {
                 match message? {
                     WsEvent::Quote(quote) => yield QuoteEvent::Quote(quote),
-                    WsEvent::Error(error) => yield QuoteEvent::Error(error),
+                    WsEvent::Error(error) => yield QuoteEvent::Error(error.raw),
                     WsEvent::Closed => break,
                     _ => {}
                 }
@@ -336,7 +510,7 @@ impl TradernetWebsocket {
             while let Some(message) = events.next().await {
                 match message? {
                     WsEvent::MarketDepth(update) => yield MarketDepthEvent::Update(update),
-                    WsEvent::Error(error) => yield MarketDepthEvent::Error(error),
+                    WsEvent::Error(error) => yield MarketDepthEvent::Error(error.raw),
                     WsEvent::Closed => break,
                     _ => {}
                 }
@@ -359,7 +533,7 @@ impl TradernetWebsocket {
             while let Some(message) = events.next().await {
                 match message? {
                     WsEvent::Portfolio(update) => yield PortfolioEvent::Portfolio(update),
-                    WsEvent::Error(error) => yield PortfolioEvent::Error(error),
+                    WsEvent::Error(error) => yield PortfolioEvent::Error(error.raw),
                     WsEvent::Closed => break,
                     _ => {}
                 }
@@ -382,7 +556,7 @@ impl TradernetWebsocket {
             while let Some(message) = events.next().await {
                 match message? {
                     WsEvent::Orders(update) => yield OrdersEvent::Orders(update),
-                    WsEvent::Error(error) => yield OrdersEvent::Error(error),
+                    WsEvent::Error(error) => yield OrdersEvent::Error(error.raw),
                     WsEvent::Closed => break,
                     _ => {}
                 }
@@ -405,7 +579,7 @@ impl TradernetWebsocket {
             while let Some(message) = events.next().await {
                 match message? {
                     WsEvent::Markets(update) => yield MarketsEvent::Markets(update),
-                    WsEvent::Error(error) => yield MarketsEvent::Error(error),
+                    WsEvent::Error(error) => yield MarketsEvent::Error(error.raw),
                     WsEvent::Closed => break,
                     _ => {}
                 }
@@ -432,7 +606,7 @@ impl TradernetWebsocket {
     fn parse_quote_event(event: &str, data: &Value) -> Result<Option<QuoteEvent>, TradernetError> {
         match Self::parse_ws_event(event, data)? {
             Some(WsEvent::Quote(quote)) => Ok(Some(QuoteEvent::Quote(quote))),
-            Some(WsEvent::Error(error)) => Ok(Some(QuoteEvent::Error(error))),
+            Some(WsEvent::Error(error)) => Ok(Some(QuoteEvent::Error(error.raw))),
             _ => Ok(None),
         }
     }
@@ -444,7 +618,7 @@ impl TradernetWebsocket {
     ) -> Result<Option<MarketDepthEvent>, TradernetError> {
         match Self::parse_ws_event(event, data)? {
             Some(WsEvent::MarketDepth(depth)) => Ok(Some(MarketDepthEvent::Update(depth))),
-            Some(WsEvent::Error(error)) => Ok(Some(MarketDepthEvent::Error(error))),
+            Some(WsEvent::Error(error)) => Ok(Some(MarketDepthEvent::Error(error.raw))),
             _ => Ok(None),
         }
     }
@@ -456,7 +630,7 @@ impl TradernetWebsocket {
     ) -> Result<Option<PortfolioEvent>, TradernetError> {
         match Self::parse_ws_event(event, data)? {
             Some(WsEvent::Portfolio(portfolio)) => Ok(Some(PortfolioEvent::Portfolio(portfolio))),
-            Some(WsEvent::Error(error)) => Ok(Some(PortfolioEvent::Error(error))),
+            Some(WsEvent::Error(error)) => Ok(Some(PortfolioEvent::Error(error.raw))),
             _ => Ok(None),
         }
     }
@@ -468,7 +642,7 @@ impl TradernetWebsocket {
     ) -> Result<Option<OrdersEvent>, TradernetError> {
         match Self::parse_ws_event(event, data)? {
             Some(WsEvent::Orders(orders)) => Ok(Some(OrdersEvent::Orders(orders))),
-            Some(WsEvent::Error(error)) => Ok(Some(OrdersEvent::Error(error))),
+            Some(WsEvent::Error(error)) => Ok(Some(OrdersEvent::Error(error.raw))),
             _ => Ok(None),
         }
     }
@@ -480,12 +654,12 @@ impl TradernetWebsocket {
     ) -> Result<Option<MarketsEvent>, TradernetError> {
         match Self::parse_ws_event(event, data)? {
             Some(WsEvent::Markets(markets)) => Ok(Some(MarketsEvent::Markets(markets))),
-            Some(WsEvent::Error(error)) => Ok(Some(MarketsEvent::Error(error))),
+            Some(WsEvent::Error(error)) => Ok(Some(MarketsEvent::Error(error.raw))),
             _ => Ok(None),
         }
     }
 
-    fn parse_ws_message(text: &str) -> Result<Option<WsEvent>, TradernetError> {
+    pub(crate) fn parse_ws_message(text: &str) -> Result<Option<WsEvent>, TradernetError> {
         let parsed: Value = serde_json::from_str(text)?;
         if let Some((event, data)) = parsed.as_array().and_then(|values| {
             let event = values.first()?.as_str()?;
@@ -519,55 +693,118 @@ impl TradernetWebsocket {
                 let markets: MarketsUpdate = serde_json::from_value(data.clone())?;
                 Ok(Some(WsEvent::Markets(markets)))
             }
-            "error" => Ok(Some(WsEvent::Error(data.clone()))),
+            "error" => Ok(Some(WsEvent::Error(WsError::from_payload(data.clone())))),
             _ => Ok(None),
         }
     }
 }
 
 impl TradernetWsSession {
-    fn start(url: Url, reconnect: WsReconnectConfig) -> Self {
+    fn start(
+        url: Url,
+        reconnect: WsReconnectConfig,
+        options: SessionOptions,
+        recorder: Option<WsRecorder>,
+    ) -> Self {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let buffered = Arc::new(AtomicUsize::new(0));
+        let (stream_tx, _) = broadcast::channel(STREAM_CHANNEL_CAPACITY);
         let subscriptions = Arc::new(Mutex::new(SubscriptionState::default()));
+        let quote_cache = Arc::new(Mutex::new(QuoteCache::new()));
         let closed = Arc::new(AtomicBool::new(false));
 
         let worker = tokio::spawn(run_session_loop(
             url,
             command_rx,
-            events_tx,
+            EventSink::new(events_tx, Arc::clone(&buffered), EVENTS_CHANNEL_CAPACITY),
+            stream_tx.clone(),
             Arc::clone(&subscriptions),
+            Arc::clone(&quote_cache),
             Arc::clone(&closed),
             reconnect,
+            options,
+            recorder,
         ));
 
         Self {
             command_tx,
             events_rx: Arc::new(Mutex::new(Some(events_rx))),
+            buffered,
+            stream_tx,
             subscriptions,
+            quote_cache,
             closed,
             worker: Arc::new(Mutex::new(Some(worker))),
         }
     }
 
     /// Adds a subscription request with deduplication.
+    ///
+    /// Returns once the command is queued. Use
+    /// [`subscribe_and_confirm`](Self::subscribe_and_confirm) to wait for the server.
     pub async fn subscribe(&self, req: SubscribeRequest) -> Result<(), TradernetError> {
+        self.queue_subscribe(req, None).map(|_| ())
+    }
+
+    /// Adds a subscription request and waits until the server confirms it.
+    ///
+    /// The subscription is confirmed by the first data frame for every newly
+    /// requested symbol (or by the first frame of a channel-level subscription).
+    /// A server `error` attributed to the request fails it with
+    /// [`TradernetError::WsSubscription`] and rolls back the rejected symbols;
+    /// if nothing arrives within `timeout`, [`TradernetError::Timeout`] is returned
+    /// and the subscription stays active. Already active targets return immediately.
+    pub async fn subscribe_and_confirm(
+        &self,
+        req: SubscribeRequest,
+        timeout: Duration,
+    ) -> Result<(), TradernetError> {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        let Some(delta) = self.queue_subscribe(req, Some(ack_tx))? else {
+            return Ok(());
+        };
+
+        match tokio::time::timeout(timeout, ack_rx).await {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(error))) => {
+                if let TradernetError::WsSubscription(ws_error) = &error {
+                    self.rollback_subscribe(&delta, ws_error);
+                }
+                Err(error)
+            }
+            Ok(Err(_)) => Err(TradernetError::InvalidInput(
+                "websocket session is closed".to_string(),
+            )),
+            Err(_) => Err(TradernetError::Timeout(format!(
+                "{:?} subscription was not confirmed within {timeout:?}",
+                delta.channel()
+            ))),
+        }
+    }
+
+    fn queue_subscribe(
+        &self,
+        req: SubscribeRequest,
+        ack: Option<AckTx>,
+    ) -> Result<Option<SubscribeRequest>, TradernetError> {
         if self.closed.load(Ordering::SeqCst) {
             return Err(TradernetError::InvalidInput(
                 "websocket session is closed".to_string(),
             ));
         }
 
-        let delta = {
-            let mut state = self.subscriptions.lock().map_err(|_| {
-                TradernetError::InvalidInput("subscriptions mutex poisoned".to_string())
-            })?;
-            state.apply_subscribe(req)
-        };
+        // The command is queued under the lock so a concurrent reconnect either
+        // replays this target or sees the command, never both.
+        let mut state = self.subscriptions.lock().map_err(|_| {
+            TradernetError::InvalidInput("subscriptions mutex poisoned".to_string())
+        })?;
+        state.disown_streams(&req);
+        let delta = state.apply_subscribe(req);
 
-        if let Some(delta) = delta {
+        if let Some(delta) = &delta {
             self.command_tx
-                .send(SessionCommand::Subscribe(delta))
+                .send(SessionCommand::Subscribe(delta.clone(), ack))
                 .map_err(|_| {
                     TradernetError::InvalidInput(
                         "websocket session command channel closed".to_string(),
@@ -575,7 +812,31 @@ impl TradernetWsSession {
                 })?;
         }
 
-        Ok(())
+        Ok(delta)
+    }
+
+    fn rollback_subscribe(&self, delta: &SubscribeRequest, error: &WsError) {
+        let rejected = delta
+            .symbols()
+            .iter()
+            .filter(|symbol| error.symbols.contains(symbol))
+            .cloned()
+            .collect::<Vec<_>>();
+        let symbols = if rejected.is_empty() {
+            delta.symbols().to_vec()
+        } else {
+            rejected
+        };
+        let req = match delta {
+            SubscribeRequest::Quotes { .. } => UnsubscribeRequest::Quotes { symbols },
+            SubscribeRequest::OrderBook { .. } => UnsubscribeRequest::OrderBook { symbols },
+            SubscribeRequest::Portfolio => UnsubscribeRequest::Portfolio,
+            SubscribeRequest::Orders => UnsubscribeRequest::Orders,
+            SubscribeRequest::Markets => UnsubscribeRequest::Markets,
+        };
+        if let Ok(mut state) = self.subscriptions.lock() {
+            state.apply_unsubscribe(req);
+        }
     }
 
     /// Removes a subscription request.
@@ -609,10 +870,40 @@ impl TradernetWsSession {
         Ok(())
     }
 
+    /// Seeds quote snapshots with full quotes, e.g. from
+    /// [`crate::Tradernet::get_quotes_typed`].
+    ///
+    /// Subsequent `q` deltas are merged on top of the seeded values. Returns the
+    /// number of quotes stored.
+    pub fn seed_quotes<I>(&self, quotes: I) -> Result<usize, TradernetError>
+    where
+        I: IntoIterator<Item = Quote>,
+    {
+        let mut cache = self
+            .quote_cache
+            .lock()
+            .map_err(|_| TradernetError::InvalidInput("quote cache mutex poisoned".to_string()))?;
+        Ok(cache.seed(quotes))
+    }
+
+    /// Returns the current merged quote snapshot for a symbol.
+    pub fn quote_snapshot(&self, symbol: &str) -> Option<Quote> {
+        self.quote_cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(symbol).cloned())
+    }
+
     /// Returns the session event stream.
     ///
     /// This receiver is single-consumer. Calling `events()` more than once returns
     /// a stream with a single `InvalidInput` error.
+    ///
+    /// Events received before the stream is taken are buffered. Up to 4096 market
+    /// data events (quotes, snapshots, order books, market statuses) are kept;
+    /// newer ones are dropped until the consumer catches up, and the number of
+    /// dropped events is reported by [`WsEvent::Dropped`]. Order, portfolio,
+    /// error and session events are never dropped.
     pub fn events(&self) -> BoxStream<'static, Result<WsEvent, TradernetError>> {
         let maybe_rx = self
             .events_rx
@@ -620,9 +911,11 @@ impl TradernetWsSession {
             .ok()
             .and_then(|mut guard| guard.take());
 
+        let buffered = Arc::clone(&self.buffered);
         match maybe_rx {
             Some(mut rx) => Box::pin(stream! {
                 while let Some(item) = rx.recv().await {
+                    release_buffered(&buffered, &item);
                     yield item;
                 }
             }),
@@ -634,6 +927,90 @@ impl TradernetWsSession {
         }
     }
 
+    /// Returns a stream of quote updates for one symbol.
+    ///
+    /// The symbol is subscribed on first poll and unsubscribed when the last
+    /// stream for it is dropped, unless it was also subscribed explicitly via
+    /// [`subscribe`](Self::subscribe). The stream ends when the session closes.
+    ///
+    /// A consumer that falls more than 1024 events behind skips the missed
+    /// events; the subscription is then re-sent so the server replays the
+    /// current state.
+    pub fn quote_stream(&self, symbol: impl Into<String>) -> BoxStream<'static, Quote> {
+        let symbol = symbol.into();
+        let target = StreamTarget::Quotes(symbol.clone());
+        self.typed_stream(target, move |event| match event {
+            WsEvent::Quote(quote) if quote.c.as_deref() == Some(symbol.as_str()) => Some(quote),
+            _ => None,
+        })
+    }
+
+    /// Returns a stream of order book updates for one symbol.
+    ///
+    /// Subscription lifecycle follows [`quote_stream`](Self::quote_stream).
+    pub fn book_stream(&self, symbol: impl Into<String>) -> BoxStream<'static, MarketDepthUpdate> {
+        let symbol = symbol.into();
+        let target = StreamTarget::OrderBook(symbol.clone());
+        self.typed_stream(target, move |event| match event {
+            WsEvent::MarketDepth(update) if update.i == symbol => Some(update),
+            _ => None,
+        })
+    }
+
+    /// Returns a stream of active order rows.
+    ///
+    /// Each `orders` frame is flattened into individual rows. Subscription
+    /// lifecycle follows [`quote_stream`](Self::quote_stream); after a lag the
+    /// server resends all active orders, so rows may repeat but none are lost.
+    pub fn order_stream(&self) -> BoxStream<'static, OrderDataRow> {
+        self.typed_stream(StreamTarget::Orders, |event| match event {
+            WsEvent::Orders(orders) => orders,
+            _ => Vec::new(),
+        })
+    }
+
+    fn typed_stream<T, I, F>(&self, target: StreamTarget, select: F) -> BoxStream<'static, T>
+    where
+        T: Send + 'static,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send,
+        F: Fn(WsEvent) -> I + Send + 'static,
+    {
+        let handle = StreamHandle {
+            command_tx: self.command_tx.clone(),
+            subscriptions: Arc::clone(&self.subscriptions),
+            closed: Arc::clone(&self.closed),
+            target,
+        };
+        let stream_tx = self.stream_tx.clone();
+
+        Box::pin(stream! {
+            let mut rx = stream_tx.subscribe();
+            let guard = match handle.acquire() {
+                Ok(guard) => guard,
+                Err(error) => {
+                    log::warn!("failed to open websocket stream: {error}");
+                    return;
+                }
+            };
+
+            loop {
+                match rx.recv().await {
+                    Ok(WsEvent::Closed) | Err(RecvError::Closed) => break,
+                    Ok(event) => {
+                        for item in select(event) {
+                            yield item;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("websocket stream lagged, {skipped} events skipped, resubscribing");
+                        guard.resync();
+                    }
+                }
+            }
+        })
+    }
+
     /// Closes the session and waits for background worker shutdown.
     pub async fn close(&self) -> Result<(), TradernetError> {
         if self.closed.swap(true, Ordering::SeqCst) {
@@ -663,21 +1040,105 @@ impl Drop for TradernetWsSession {
     }
 }
 
+/// Subscription owned by a typed stream, released when dropped.
+struct StreamHandle {
+    command_tx: mpsc::UnboundedSender<SessionCommand>,
+    subscriptions: Arc<Mutex<SubscriptionState>>,
+    closed: Arc<AtomicBool>,
+    target: StreamTarget,
+}
+
+impl StreamHandle {
+    fn acquire(self) -> Result<StreamHandle, TradernetError> {
+        if self
+            .target_symbol()
+            .is_some_and(|symbol| symbol.trim().is_empty())
+        {
+            return Err(TradernetError::InvalidInput(
+                "stream symbol must not be empty".to_string(),
+            ));
+        }
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(TradernetError::InvalidInput(
+                "websocket session is closed".to_string(),
+            ));
+        }
+
+        {
+            let mut state = self.subscriptions.lock().map_err(|_| {
+                TradernetError::InvalidInput("subscriptions mutex poisoned".to_string())
+            })?;
+            if let Some(delta) = state.acquire_stream(&self.target) {
+                self.command_tx
+                    .send(SessionCommand::Subscribe(delta, None))
+                    .map_err(|_| {
+                        TradernetError::InvalidInput(
+                            "websocket session command channel closed".to_string(),
+                        )
+                    })?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Re-sends the subscription so the server replays current state after a lag.
+    fn resync(&self) {
+        if !self.closed.load(Ordering::SeqCst) {
+            let _ = self.command_tx.send(SessionCommand::Subscribe(
+                self.target.subscribe_request(),
+                None,
+            ));
+        }
+    }
+
+    fn target_symbol(&self) -> Option<&str> {
+        match &self.target {
+            StreamTarget::Quotes(symbol) | StreamTarget::OrderBook(symbol) => Some(symbol),
+            StreamTarget::Orders => None,
+        }
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        let delta = self
+            .subscriptions
+            .lock()
+            .ok()
+            .and_then(|mut state| state.release_stream(&self.target));
+        if let Some(delta) = delta
+            && !self.closed.load(Ordering::SeqCst)
+        {
+            let _ = self.command_tx.send(SessionCommand::Unsubscribe(delta));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn run_session_loop(
     url: Url,
     mut command_rx: mpsc::UnboundedReceiver<SessionCommand>,
-    events_tx: mpsc::UnboundedSender<Result<WsEvent, TradernetError>>,
+    mut events: EventSink,
+    stream_tx: broadcast::Sender<WsEvent>,
     subscriptions: Arc<Mutex<SubscriptionState>>,
+    quote_cache: Arc<Mutex<QuoteCache>>,
     closed: Arc<AtomicBool>,
     reconnect: WsReconnectConfig,
+    options: SessionOptions,
+    mut recorder: Option<WsRecorder>,
 ) {
     let mut reconnect_attempt: u32 = 0;
     let mut has_connected_before = false;
+    let mut pending_acks: Vec<PendingAck> = Vec::new();
 
     'outer: loop {
         while let Ok(command) = command_rx.try_recv() {
-            if matches!(command, SessionCommand::Close) {
-                break 'outer;
+            match command {
+                SessionCommand::Close => break 'outer,
+                SessionCommand::Subscribe(req, Some(ack)) => {
+                    pending_acks.push(PendingAck::new(&req, ack));
+                }
+                _ => {}
             }
         }
 
@@ -690,8 +1151,8 @@ async fn run_session_loop(
             Ok(connection) => connection,
             Err(error) => {
                 log::warn!("websocket connection failed: {error}");
-                let _ = events_tx.send(Err(error.into()));
-                let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                events.send(Err(error.into()));
+                events.send(Ok(WsEvent::Reconnecting));
 
                 let delay = backoff_delay(reconnect, reconnect_attempt);
                 reconnect_attempt = reconnect_attempt.saturating_add(1);
@@ -707,19 +1168,41 @@ async fn run_session_loop(
             log::info!("websocket connected");
         }
         has_connected_before = true;
-        let _ = events_tx.send(Ok(WsEvent::Connected));
+        events.send(Ok(WsEvent::Connected));
 
         let (mut write, mut read) = ws_stream.split();
 
-        let active_requests = subscriptions
-            .lock()
-            .map(|state| state.active_requests())
-            .unwrap_or_default();
+        // Commands queued while connecting are already part of the subscription
+        // state replayed below; sending them again would duplicate frames.
+        // Subscribers queue commands under the same lock, so draining here and
+        // taking the snapshot cannot miss or double a target.
+        let (active_requests, close_requested) = {
+            let state = subscriptions.lock();
+            let mut close_requested = false;
+            while let Ok(command) = command_rx.try_recv() {
+                match command {
+                    SessionCommand::Close => close_requested = true,
+                    SessionCommand::Subscribe(req, Some(ack)) => {
+                        pending_acks.push(PendingAck::new(&req, ack));
+                    }
+                    _ => {}
+                }
+            }
+            let active_requests = state
+                .map(|state| state.active_requests())
+                .unwrap_or_default();
+            (active_requests, close_requested)
+        };
+        if close_requested {
+            closed.store(true, Ordering::SeqCst);
+            let _ = write.close().await;
+            break 'outer;
+        }
         for req in active_requests {
             if let Err(error) = send_subscribe(&mut write, req).await {
                 log::warn!("failed to restore subscription after reconnect: {error}");
-                let _ = events_tx.send(Err(error));
-                let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                events.send(Err(error));
+                events.send(Ok(WsEvent::Reconnecting));
                 break;
             }
         }
@@ -733,11 +1216,14 @@ async fn run_session_loop(
                     };
 
                     match command {
-                        SessionCommand::Subscribe(req) => {
+                        SessionCommand::Subscribe(req, ack) => {
+                            if let Some(ack) = ack {
+                                pending_acks.push(PendingAck::new(&req, ack));
+                            }
                             if let Err(error) = send_subscribe(&mut write, req).await {
                                 log::warn!("failed to send subscribe request: {error}");
-                                let _ = events_tx.send(Err(error));
-                                let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                                events.send(Err(error));
+                                events.send(Ok(WsEvent::Reconnecting));
                                 break;
                             }
                         }
@@ -754,39 +1240,70 @@ async fn run_session_loop(
                 message = read.next() => {
                     match message {
                         Some(Ok(Message::Text(text))) => {
+                            if let Some(active) = recorder.as_mut()
+                                && let Err(error) = active.record(text.as_ref())
+                            {
+                                log::warn!("websocket frame recording failed, recording stopped: {error}");
+                                recorder = None;
+                            }
+
                             match TradernetWebsocket::parse_ws_message(text.as_ref()) {
-                                Ok(Some(event)) => {
+                                Ok(Some(mut event)) => {
+                                    if let WsEvent::Error(error) = &mut event
+                                        && let Ok(state) = subscriptions.lock()
+                                    {
+                                        attribute_error(error, &pending_acks, &state);
+                                    }
+                                    resolve_pending_acks(&mut pending_acks, &event);
+
                                     let should_emit = subscriptions
                                         .lock()
                                         .map(|state| state.allows_event(&event))
                                         .unwrap_or(false);
 
-                                    if should_emit {
-                                        let _ = events_tx.send(Ok(event));
+                                    if !should_emit {
+                                        continue;
+                                    }
+
+                                    let snapshot = match &event {
+                                        WsEvent::Quote(quote) => quote_cache
+                                            .lock()
+                                            .ok()
+                                            .and_then(|mut cache| cache.apply(quote.clone()).cloned()),
+                                        _ => None,
+                                    };
+                                    if stream_tx.receiver_count() > 0 {
+                                        let _ = stream_tx.send(event.clone());
+                                    }
+                                    events.send(Ok(event));
+                                    if options.quote_snapshots
+                                        && let Some(snapshot) = snapshot
+                                    {
+                                        events.send(Ok(WsEvent::QuoteSnapshot(snapshot)));
                                     }
                                 }
                                 Ok(None) => {}
                                 Err(error) => {
                                     log::warn!("websocket message parse failed: {error}");
-                                    let _ = events_tx.send(Err(error));
+                                    events.send(Err(error));
                                 }
                             }
                         }
                         Some(Ok(Message::Close(_))) => {
                             log::warn!("websocket closed by server");
-                            let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                            events.send(Ok(WsEvent::Reconnecting));
                             break;
                         }
                         Some(Ok(_)) => {}
                         Some(Err(error)) => {
                             log::warn!("websocket read failed: {error}");
-                            let _ = events_tx.send(Err(error.into()));
-                            let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                            events.send(Err(error.into()));
+                            events.send(Ok(WsEvent::Reconnecting));
                             break;
                         }
                         None => {
                             log::warn!("websocket stream ended");
-                            let _ = events_tx.send(Ok(WsEvent::Reconnecting));
+                            events.send(Ok(WsEvent::Reconnecting));
                             break;
                         }
                     }
@@ -802,10 +1319,200 @@ async fn run_session_loop(
         reconnect_attempt = reconnect_attempt.saturating_add(1);
     }
 
-    let _ = events_tx.send(Ok(WsEvent::Closed));
+    if let Some(recorder) = recorder {
+        let finished = tokio::task::spawn_blocking(move || recorder.finish())
+            .await
+            .map_err(|error| TradernetError::from(std::io::Error::other(error)))
+            .and_then(|result| result);
+        if let Err(error) = finished {
+            log::warn!("failed to finalize websocket recording: {error}");
+        }
+    }
+
+    let _ = stream_tx.send(WsEvent::Closed);
+    events.send(Ok(WsEvent::Closed));
     log::info!("websocket session closed");
 }
 
+/// Feeds the [`TradernetWsSession::events`] channel without blocking the session loop.
+///
+/// Market data is capped at `capacity` undelivered events so an idle consumer
+/// cannot grow memory without bound; events over the cap are counted and reported
+/// with [`WsEvent::Dropped`] ahead of the next delivered event. Other events are
+/// always queued.
+struct EventSink {
+    tx: mpsc::UnboundedSender<Result<WsEvent, TradernetError>>,
+    buffered: Arc<AtomicUsize>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl EventSink {
+    fn new(
+        tx: mpsc::UnboundedSender<Result<WsEvent, TradernetError>>,
+        buffered: Arc<AtomicUsize>,
+        capacity: usize,
+    ) -> Self {
+        Self {
+            tx,
+            buffered,
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    fn send(&mut self, item: Result<WsEvent, TradernetError>) {
+        let market_data = matches!(&item, Ok(event) if is_market_data(event));
+        if market_data && self.buffered.load(Ordering::SeqCst) >= self.capacity {
+            if self.dropped == 0 {
+                log::warn!("websocket events buffer is full, dropping market data");
+            }
+            self.dropped += 1;
+            return;
+        }
+        if self.dropped > 0 {
+            log::warn!(
+                "websocket events consumer lagged, {} events dropped",
+                self.dropped
+            );
+            let _ = self.tx.send(Ok(WsEvent::Dropped(self.dropped)));
+            self.dropped = 0;
+        }
+        if market_data {
+            self.buffered.fetch_add(1, Ordering::SeqCst);
+        }
+        if self.tx.send(item).is_err() && market_data {
+            self.buffered.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn is_market_data(event: &WsEvent) -> bool {
+    matches!(
+        event,
+        WsEvent::Quote(_)
+            | WsEvent::QuoteSnapshot(_)
+            | WsEvent::MarketDepth(_)
+            | WsEvent::Markets(_)
+    )
+}
+
+/// Frees the [`EventSink`] slot of an event taken from the channel.
+fn release_buffered(buffered: &AtomicUsize, item: &Result<WsEvent, TradernetError>) {
+    if matches!(item, Ok(event) if is_market_data(event)) {
+        buffered.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Subscription awaiting its first data frame or a server error.
+struct PendingAck {
+    channel: WsChannel,
+    symbols: HashSet<String>,
+    waiting: HashSet<String>,
+    tx: Option<AckTx>,
+}
+
+impl PendingAck {
+    fn new(req: &SubscribeRequest, tx: AckTx) -> Self {
+        let symbols = req.symbols().iter().cloned().collect::<HashSet<_>>();
+        Self {
+            channel: req.channel(),
+            waiting: symbols.clone(),
+            symbols,
+            tx: Some(tx),
+        }
+    }
+
+    fn finish(&mut self, result: Result<(), TradernetError>) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(result);
+        }
+    }
+
+    fn is_rejected_by(&self, error: &WsError) -> bool {
+        error.channel == Some(self.channel)
+            && (self.symbols.is_empty()
+                || error.symbols.is_empty()
+                || error
+                    .symbols
+                    .iter()
+                    .any(|symbol| self.symbols.contains(symbol)))
+    }
+}
+
+/// Fills missing `channel`/`symbols` of an error from session state.
+///
+/// Symbols are matched against subscribed tickers mentioned in the message; an
+/// error that still cannot be attributed is assigned to the only pending
+/// subscription, if there is exactly one.
+fn attribute_error(error: &mut WsError, pending: &[PendingAck], state: &SubscriptionState) {
+    if error.symbols.is_empty()
+        && let Some(message) = error.message.as_deref()
+    {
+        let words = ticker_words(message);
+        let mut mentioned = state
+            .quotes
+            .iter()
+            .chain(state.order_book.iter())
+            .chain(pending.iter().flat_map(|ack| ack.symbols.iter()))
+            .filter(|symbol| words.contains(symbol.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        mentioned.sort();
+        mentioned.dedup();
+        error.symbols = mentioned;
+    }
+
+    if error.channel.is_none() && !error.symbols.is_empty() {
+        let mentions =
+            |symbols: &HashSet<String>| error.symbols.iter().any(|symbol| symbols.contains(symbol));
+        error.channel = pending
+            .iter()
+            .find(|ack| mentions(&ack.symbols))
+            .map(|ack| ack.channel)
+            .or_else(|| mentions(&state.quotes).then_some(WsChannel::Quotes))
+            .or_else(|| mentions(&state.order_book).then_some(WsChannel::OrderBook));
+    }
+
+    if error.channel.is_none()
+        && let [ack] = pending
+    {
+        error.channel = Some(ack.channel);
+        if error.symbols.is_empty() {
+            error.symbols = sorted_symbols(&ack.symbols);
+        }
+    }
+}
+
+fn resolve_pending_acks(pending: &mut Vec<PendingAck>, event: &WsEvent) {
+    for ack in pending.iter_mut() {
+        match (ack.channel, event) {
+            (WsChannel::Quotes, WsEvent::Quote(quote)) => {
+                if let Some(symbol) = quote.c.as_ref() {
+                    ack.waiting.remove(symbol);
+                }
+            }
+            (WsChannel::OrderBook, WsEvent::MarketDepth(update)) => {
+                ack.waiting.remove(&update.i);
+            }
+            (WsChannel::Portfolio, WsEvent::Portfolio(_))
+            | (WsChannel::Orders, WsEvent::Orders(_))
+            | (WsChannel::Markets, WsEvent::Markets(_)) => {}
+            (_, WsEvent::Error(error)) if ack.is_rejected_by(error) => {
+                ack.finish(Err(TradernetError::WsSubscription(Box::new(error.clone()))));
+                continue;
+            }
+            _ => continue,
+        }
+
+        if ack.waiting.is_empty() {
+            ack.finish(Ok(()));
+        }
+    }
+
+    pending.retain(|ack| ack.tx.as_ref().is_some_and(|tx| !tx.is_closed()));
+}
+
 async fn send_subscribe<S>(sink: &mut S, req: SubscribeRequest) -> Result<(), TradernetError>
 where
     S: futures_util::Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
@@ -838,11 +1545,172 @@ fn backoff_delay(config: WsReconnectConfig, attempt: u32) -> std::time::Duration
 
 #[cfg(test)]
 mod tests {
-    use super::TradernetWebsocket;
+    use super::{
+        EventSink, PendingAck, StreamTarget, SubscriptionState, TradernetWebsocket,
+        attribute_error, release_buffered, resolve_pending_acks,
+    };
+    use crate::errors::TradernetError;
+    use crate::user_data::Quote;
     use crate::ws_types::{
-        MarketDepthEvent, MarketDepthSide, MarketsEvent, OrdersEvent, PortfolioEvent, WsEvent,
+        MarketDepthEvent, MarketDepthSide, MarketsEvent, OrdersEvent, PortfolioEvent,
+        SubscribeRequest, WsChannel, WsError, WsEvent,
     };
     use serde_json::json;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::sync::{mpsc, oneshot};
+
+    fn quotes_request(symbols: &[&str]) -> SubscribeRequest {
+        SubscribeRequest::Quotes {
+            symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn event_sink_reports_dropped_market_data_and_keeps_other_events() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let buffered = Arc::new(AtomicUsize::new(0));
+        let mut sink = EventSink::new(tx, Arc::clone(&buffered), 2);
+        let quote = || Ok(WsEvent::Quote(Quote::default()));
+
+        for _ in 0..4 {
+            sink.send(quote());
+        }
+        sink.send(Ok(WsEvent::Orders(Vec::new())));
+        sink.send(Ok(WsEvent::Error(WsError::default())));
+        sink.send(quote());
+        sink.send(Ok(WsEvent::Closed));
+
+        let mut received = Vec::new();
+        while let Ok(item) = rx.try_recv() {
+            release_buffered(&buffered, &item);
+            received.push(item.expect("no errors were sent"));
+        }
+        assert_eq!(received.len(), 7);
+        assert!(matches!(received[0], WsEvent::Quote(_)));
+        assert!(matches!(received[1], WsEvent::Quote(_)));
+        assert!(matches!(received[2], WsEvent::Dropped(2)));
+        assert!(matches!(received[3], WsEvent::Orders(_)));
+        assert!(matches!(received[4], WsEvent::Error(_)));
+        assert!(matches!(received[5], WsEvent::Dropped(1)));
+        assert!(matches!(received[6], WsEvent::Closed));
+
+        sink.send(quote());
+        assert!(matches!(rx.try_recv(), Ok(Ok(WsEvent::Quote(_)))));
+    }
+
+    #[test]
+    fn stream_refs_subscribe_once_and_release_owned_targets() {
+        let mut state = SubscriptionState::default();
+        let aapl = StreamTarget::Quotes("AAPL.US".to_string());
+
+        assert_eq!(
+            state.acquire_stream(&aapl),
+            Some(quotes_request(&["AAPL.US"]))
+        );
+        assert_eq!(state.acquire_stream(&aapl), None);
+        assert_eq!(state.release_stream(&aapl), None);
+        assert!(state.quotes.contains("AAPL.US"));
+        assert!(state.release_stream(&aapl).is_some());
+        assert!(state.quotes.is_empty());
+        assert_eq!(state.release_stream(&aapl), None);
+    }
+
+    #[test]
+    fn stream_refs_keep_explicit_subscriptions() {
+        let mut state = SubscriptionState::default();
+        let orders = StreamTarget::Orders;
+        let tsla = StreamTarget::Quotes("TSLA.US".to_string());
+
+        state.apply_subscribe(SubscribeRequest::Orders);
+        assert_eq!(state.acquire_stream(&orders), None);
+        assert_eq!(state.release_stream(&orders), None);
+        assert!(state.orders);
+
+        state.acquire_stream(&tsla);
+        let explicit = quotes_request(&["TSLA.US"]);
+        state.disown_streams(&explicit);
+        state.apply_subscribe(explicit);
+        assert_eq!(state.release_stream(&tsla), None);
+        assert!(state.quotes.contains("TSLA.US"));
+    }
+
+    #[test]
+    fn pending_ack_confirms_after_every_symbol_sent_data() {
+        let (tx, mut rx) = oneshot::channel();
+        let mut pending = vec![PendingAck::new(
+            &quotes_request(&["AAPL.US", "TSLA.US"]),
+            tx,
+        )];
+
+        let quote = |symbol: &str| {
+            TradernetWebsocket::parse_ws_event("q", &json!({"c": symbol}))
+                .expect("must parse")
+                .expect("must return event")
+        };
+        resolve_pending_acks(&mut pending, &quote("AAPL.US"));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(pending.len(), 1);
+
+        resolve_pending_acks(&mut pending, &quote("TSLA.US"));
+        assert!(matches!(rx.try_recv(), Ok(Ok(()))));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn error_mentioning_symbol_is_attributed_and_rejects_ack() {
+        let (quotes_tx, mut quotes_rx) = oneshot::channel();
+        let (markets_tx, mut markets_rx) = oneshot::channel();
+        let mut pending = vec![
+            PendingAck::new(&quotes_request(&["FOO.US"]), quotes_tx),
+            PendingAck::new(&SubscribeRequest::Markets, markets_tx),
+        ];
+
+        let mut state = SubscriptionState::default();
+        state.apply_subscribe(quotes_request(&["AAPL.US", "FOO.US"]));
+
+        let mut error =
+            WsError::from_payload(json!({"code": 3, "message": "Unknown ticker FOO.US"}));
+        attribute_error(&mut error, &pending, &state);
+        assert_eq!(error.channel, Some(WsChannel::Quotes));
+        assert_eq!(error.symbols, vec!["FOO.US".to_string()]);
+
+        resolve_pending_acks(&mut pending, &WsEvent::Error(error));
+        let Ok(Err(TradernetError::WsSubscription(rejected))) = quotes_rx.try_recv() else {
+            panic!("expected subscription error");
+        };
+        assert_eq!(rejected.code, Some(3));
+        assert!(markets_rx.try_recv().is_err());
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn error_symbols_match_whole_tickers_only() {
+        let mut state = SubscriptionState::default();
+        state.apply_subscribe(quotes_request(&["T.US", "AT.US", "T.USD"]));
+
+        let mut error =
+            WsError::from_payload(json!({"code": 3, "message": "Unknown ticker: AT.US."}));
+        attribute_error(&mut error, &[], &state);
+        assert_eq!(error.symbols, vec!["AT.US".to_string()]);
+
+        let mut error =
+            WsError::from_payload(json!({"code": 3, "message": "Bad tickers (T.USD, T.US)"}));
+        attribute_error(&mut error, &[], &state);
+        assert_eq!(error.symbols, vec!["T.US".to_string(), "T.USD".to_string()]);
+    }
+
+    #[test]
+    fn anonymous_error_is_attributed_to_single_pending_subscription() {
+        let (tx, _rx) = oneshot::channel();
+        let pending = vec![PendingAck::new(&quotes_request(&["FOO.US"]), tx)];
+
+        let mut error = WsError::from_payload(json!("Subscription failed"));
+        attribute_error(&mut error, &pending, &SubscriptionState::default());
+
+        assert_eq!(error.channel, Some(WsChannel::Quotes));
+        assert_eq!(error.symbols, vec!["FOO.US".to_string()]);
+    }
 
     #[test]
     fn parses_quote_event_q_to_ws_event() {
@@ -1063,6 +1931,15 @@ mod tests {
 
         assert_eq!(error["code"], 400);
         assert_eq!(error["message"], "boom");
+
+        let WsEvent::Error(typed) = TradernetWebsocket::parse_ws_event("error", &payload)
+            .expect("must parse")
+            .expect("must return event")
+        else {
+            panic!("expected typed error event")
+        };
+        assert_eq!(typed.code, Some(400));
+        assert_eq!(typed.message.as_deref(), Some("boom"));
     }
 
     #[test]
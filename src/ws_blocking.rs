@@ -0,0 +1,168 @@
+use crate::errors::TradernetError;
+use crate::user_data::Quote;
+use crate::ws::{TradernetWebsocket, TradernetWsSession};
+use crate::ws_types::{SubscribeRequest, UnsubscribeRequest, WsEvent, WsReconnectConfig};
+use futures_util::StreamExt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+type BlockingEventsRx = mpsc::Receiver<Result<WsEvent, TradernetError>>;
+
+/// Blocking facade over [`TradernetWsSession`] for synchronous code.
+///
+/// The session owns a single-threaded Tokio worker that keeps the connection
+/// alive, so reconnects with backoff and subscription replay work exactly like
+/// in the async session. Events are buffered until read with
+/// [`recv`](Self::recv), [`recv_timeout`](Self::recv_timeout) or
+/// [`events`](Self::events).
+///
+/// Methods must not be called from within an async runtime. Dropping the session
+/// there is allowed but skips the graceful close.
+pub struct BlockingWsSession {
+    session: TradernetWsSession,
+    events_rx: BlockingEventsRx,
+    /// Taken on drop to shut the runtime down without blocking.
+    runtime: Option<Runtime>,
+}
+
+impl BlockingWsSession {
+    /// Opens a blocking session using default reconnect config.
+    pub fn connect(ws: &TradernetWebsocket) -> Result<Self, TradernetError> {
+        Self::connect_with_config(ws, WsReconnectConfig::default())
+    }
+
+    /// Opens a blocking session using custom reconnect config.
+    pub fn connect_with_config(
+        ws: &TradernetWebsocket,
+        reconnect: WsReconnectConfig,
+    ) -> Result<Self, TradernetError> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("tradernet-ws")
+            .enable_all()
+            .build()?;
+
+        let session = runtime.block_on(ws.connect_with_config(reconnect))?;
+        let mut events = session.events();
+        let (events_tx, events_rx) = mpsc::channel();
+        runtime.spawn(async move {
+            while let Some(item) = events.next().await {
+                if events_tx.send(item).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            session,
+            events_rx,
+            runtime: Some(runtime),
+        })
+    }
+
+    /// Adds a subscription request with deduplication.
+    pub fn subscribe(&self, req: SubscribeRequest) -> Result<(), TradernetError> {
+        self.block_on(self.session.subscribe(req))
+    }
+
+    /// Adds a subscription request and waits until the server confirms it.
+    ///
+    /// See [`TradernetWsSession::subscribe_and_confirm`] for confirmation rules.
+    pub fn subscribe_and_confirm(
+        &self,
+        req: SubscribeRequest,
+        timeout: Duration,
+    ) -> Result<(), TradernetError> {
+        self.block_on(self.session.subscribe_and_confirm(req, timeout))
+    }
+
+    /// Removes a subscription request.
+    ///
+    /// See [`TradernetWsSession::unsubscribe`] for filtering semantics.
+    pub fn unsubscribe(&self, req: UnsubscribeRequest) -> Result<(), TradernetError> {
+        self.block_on(self.session.unsubscribe(req))
+    }
+
+    /// Seeds quote snapshots, see [`TradernetWsSession::seed_quotes`].
+    pub fn seed_quotes<I>(&self, quotes: I) -> Result<usize, TradernetError>
+    where
+        I: IntoIterator<Item = Quote>,
+    {
+        self.session.seed_quotes(quotes)
+    }
+
+    /// Returns the current merged quote snapshot for a symbol.
+    pub fn quote_snapshot(&self, symbol: &str) -> Option<Quote> {
+        self.session.quote_snapshot(symbol)
+    }
+
+    /// Blocks until the next event.
+    ///
+    /// Returns `None` once the session has emitted [`WsEvent::Closed`] and all
+    /// buffered events were read.
+    pub fn recv(&self) -> Option<Result<WsEvent, TradernetError>> {
+        self.events_rx.recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next event.
+    ///
+    /// Returns `Ok(None)` when no event arrived in time and an `InvalidInput`
+    /// error once the session is closed and drained.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Option<WsEvent>, TradernetError> {
+        match self.events_rx.recv_timeout(timeout) {
+            Ok(item) => item.map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(TradernetError::InvalidInput(
+                "websocket session is closed".to_string(),
+            )),
+        }
+    }
+
+    /// Returns a blocking iterator over session events.
+    ///
+    /// The iterator ends after [`WsEvent::Closed`].
+    pub fn events(&self) -> BlockingWsEvents<'_> {
+        BlockingWsEvents { session: self }
+    }
+
+    /// Closes the session and waits for background worker shutdown.
+    pub fn close(&self) -> Result<(), TradernetError> {
+        self.block_on(self.session.close())
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match &self.runtime {
+            Some(runtime) => runtime.block_on(future),
+            None => unreachable!("runtime is only taken on drop"),
+        }
+    }
+}
+
+impl Drop for BlockingWsSession {
+    fn drop(&mut self) {
+        let Some(runtime) = self.runtime.take() else {
+            return;
+        };
+        if tokio::runtime::Handle::try_current().is_err() {
+            let _ = runtime.block_on(self.session.close());
+        } else {
+            // Blocking on or dropping a runtime inside async context panics, so
+            // the worker is stopped without waiting for a graceful close.
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Blocking iterator returned by [`BlockingWsSession::events`].
+pub struct BlockingWsEvents<'a> {
+    session: &'a BlockingWsSession,
+}
+
+impl Iterator for BlockingWsEvents<'_> {
+    type Item = Result<WsEvent, TradernetError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.session.recv()
+    }
+}
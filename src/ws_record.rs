@@ -0,0 +1,390 @@
+use crate::errors::TradernetError;
+use crate::quote_cache::QuoteCache;
+use crate::ws::TradernetWebsocket;
+use crate::ws_types::WsEvent;
+use async_stream::stream;
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use futures_util::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+
+/// Lines read ahead of playback by the blocking reader task.
+const REPLAY_LINE_BUFFER: usize = 256;
+
+/// Single raw WebSocket frame stored in a recording.
+///
+/// Recordings are JSON-lines files with one `{"ts": ..., "frame": ...}` object
+/// per received text frame.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct RecordedFrame {
+    /// Receive timestamp in Unix milliseconds.
+    pub ts: i64,
+    /// Raw text frame as received from the server.
+    pub frame: String,
+}
+
+impl RecordedFrame {
+    /// Returns the receive timestamp as UTC datetime.
+    pub fn received_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.ts)
+    }
+}
+
+/// Recording settings for [`TradernetWebsocket::with_recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsRecordConfig {
+    /// Output file path. Existing files are truncated.
+    pub path: PathBuf,
+    /// Compress output with gzip.
+    pub gzip: bool,
+}
+
+impl WsRecordConfig {
+    /// Creates a recording config. Gzip is enabled when the path ends with `.gz`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let gzip = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"));
+        Self { path, gzip }
+    }
+
+    /// Overrides gzip compression detected from the file extension.
+    pub fn with_gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+}
+
+enum RecordSink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl RecordSink {
+    fn write(&mut self, frame: &RecordedFrame) -> Result<(), TradernetError> {
+        let mut line = serde_json::to_vec(frame)?;
+        line.push(b'\n');
+        match self {
+            RecordSink::Plain(writer) => writer.write_all(&line)?,
+            RecordSink::Gzip(writer) => writer.write_all(&line)?,
+        }
+        Ok(())
+    }
+
+    /// Flushes buffered plain lines; gzip output is only complete after `finish`.
+    fn flush(&mut self) -> Result<(), TradernetError> {
+        if let RecordSink::Plain(writer) = self {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), TradernetError> {
+        match self {
+            RecordSink::Plain(mut writer) => writer.flush()?,
+            RecordSink::Gzip(writer) => writer.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Recorder used by the session worker to persist raw frames.
+///
+/// Frames are handed to a dedicated writer thread so disk I/O never blocks the
+/// session loop. Plain recordings are flushed whenever the writer catches up.
+pub(crate) struct WsRecorder {
+    tx: Option<std_mpsc::Sender<RecordedFrame>>,
+    writer: Option<thread::JoinHandle<Result<(), TradernetError>>>,
+}
+
+impl WsRecorder {
+    pub(crate) fn create(config: &WsRecordConfig) -> Result<Self, TradernetError> {
+        let writer = BufWriter::new(File::create(&config.path)?);
+        let sink = if config.gzip {
+            RecordSink::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            RecordSink::Plain(writer)
+        };
+        let (tx, rx) = std_mpsc::channel();
+        let writer = thread::Builder::new()
+            .name("tradernet-ws-recorder".to_string())
+            .spawn(move || write_frames(sink, rx))?;
+        Ok(Self {
+            tx: Some(tx),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queues a frame; fails with the writer error once writing has failed.
+    pub(crate) fn record(&mut self, frame: &str) -> Result<(), TradernetError> {
+        let entry = RecordedFrame {
+            ts: Utc::now().timestamp_millis(),
+            frame: frame.to_string(),
+        };
+        if self.tx.as_ref().is_some_and(|tx| tx.send(entry).is_ok()) {
+            return Ok(());
+        }
+        self.tx = None;
+        match self.writer.take().map(join) {
+            Some(Err(error)) => Err(error),
+            _ => Err(io::Error::other("websocket recorder writer stopped").into()),
+        }
+    }
+
+    /// Waits for queued frames to be written and finalizes the file, blocking the
+    /// calling thread.
+    pub(crate) fn finish(mut self) -> Result<(), TradernetError> {
+        self.tx = None;
+        self.writer.take().map_or(Ok(()), join)
+    }
+}
+
+fn write_frames(
+    mut sink: RecordSink,
+    rx: std_mpsc::Receiver<RecordedFrame>,
+) -> Result<(), TradernetError> {
+    while let Ok(frame) = rx.recv() {
+        sink.write(&frame)?;
+        while let Ok(frame) = rx.try_recv() {
+            sink.write(&frame)?;
+        }
+        sink.flush()?;
+    }
+    sink.finish()
+}
+
+fn join(writer: thread::JoinHandle<Result<(), TradernetError>>) -> Result<(), TradernetError> {
+    writer
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("websocket recorder writer panicked").into()))
+}
+
+/// Playback pacing for [`ReplaySession`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReplaySpeed {
+    /// Reproduce the original gaps between frames.
+    #[default]
+    Original,
+    /// Divide original gaps by the given factor (e.g. `10.0` plays 10x faster).
+    Accelerated(f64),
+    /// Emit frames without any delay.
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    fn delay(self, gap_ms: i64) -> Option<Duration> {
+        if gap_ms <= 0 {
+            return None;
+        }
+        let gap = Duration::from_millis(gap_ms as u64);
+        match self {
+            ReplaySpeed::Original => Some(gap),
+            ReplaySpeed::Accelerated(factor) if factor.is_finite() && factor > 0.0 => {
+                Some(gap.div_f64(factor))
+            }
+            ReplaySpeed::Accelerated(_) | ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// Offline session replaying a recording made with [`TradernetWebsocket::with_recording`].
+///
+/// Frames go through the same parser as a live [`crate::ws::TradernetWsSession`],
+/// so recordings can be used as regression fixtures. Gzip input is detected
+/// automatically. No subscription filtering is applied: every recorded frame is
+/// replayed, followed by [`WsEvent::Closed`].
+pub struct ReplaySession {
+    reader: Mutex<Option<Box<dyn BufRead + Send>>>,
+    speed: ReplaySpeed,
+    quote_snapshots: bool,
+}
+
+impl ReplaySession {
+    /// Opens a recording file for replay at original speed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TradernetError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn BufRead + Send> = if is_gzip {
+            Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        Ok(Self::from_reader(reader))
+    }
+
+    /// Creates a replay session from an uncompressed JSON-lines reader.
+    pub fn from_reader<R>(reader: R) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        Self {
+            reader: Mutex::new(Some(Box::new(BufReader::new(reader)))),
+            speed: ReplaySpeed::default(),
+            quote_snapshots: false,
+        }
+    }
+
+    /// Sets playback pacing.
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Emits [`WsEvent::QuoteSnapshot`] after each quote delta, like
+    /// [`TradernetWebsocket::with_quote_snapshots`].
+    pub fn with_quote_snapshots(mut self, enabled: bool) -> Self {
+        self.quote_snapshots = enabled;
+        self
+    }
+
+    /// Returns the replayed event stream.
+    ///
+    /// This stream is single-consumer. Calling `events()` more than once returns
+    /// a stream with a single `InvalidInput` error. The recording is read on a
+    /// blocking Tokio task, so the stream must be polled inside a Tokio runtime.
+    pub fn events(&self) -> BoxStream<'static, Result<WsEvent, TradernetError>> {
+        let maybe_reader = self.reader.lock().ok().and_then(|mut guard| guard.take());
+        let Some(reader) = maybe_reader else {
+            return Box::pin(stream! {
+                yield Err(TradernetError::InvalidInput(
+                    "events stream is already taken".to_string(),
+                ));
+            });
+        };
+
+        let speed = self.speed;
+        let quote_snapshots = self.quote_snapshots;
+        Box::pin(stream! {
+            let mut lines = spawn_line_reader(reader);
+            let mut quote_cache = QuoteCache::new();
+            let mut previous_ts = None;
+
+            while let Some(line) = lines.recv().await {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(error) => {
+                        yield Err(error.into());
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let frame: RecordedFrame = match serde_json::from_str(&line) {
+                    Ok(frame) => frame,
+                    Err(error) => {
+                        log::warn!("recorded frame parse failed: {error}");
+                        yield Err(error.into());
+                        continue;
+                    }
+                };
+
+                if let Some(delay) = previous_ts.and_then(|previous| speed.delay(frame.ts - previous)) {
+                    sleep(delay).await;
+                }
+                previous_ts = Some(frame.ts);
+
+                match TradernetWebsocket::parse_ws_message(&frame.frame) {
+                    Ok(Some(event)) => {
+                        let snapshot = match &event {
+                            WsEvent::Quote(quote) if quote_snapshots => {
+                                quote_cache.apply(quote.clone()).cloned()
+                            }
+                            _ => None,
+                        };
+                        yield Ok(event);
+                        if let Some(snapshot) = snapshot {
+                            yield Ok(WsEvent::QuoteSnapshot(snapshot));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        log::warn!("websocket message parse failed: {error}");
+                        yield Err(error);
+                    }
+                }
+            }
+
+            yield Ok(WsEvent::Closed);
+        })
+    }
+}
+
+/// Reads lines on a blocking task so file and gzip I/O stay off the async executor.
+fn spawn_line_reader(reader: Box<dyn BufRead + Send>) -> mpsc::Receiver<io::Result<String>> {
+    let (tx, rx) = mpsc::channel(REPLAY_LINE_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        for line in reader.lines() {
+            let failed = line.is_err();
+            if tx.blocking_send(line).is_err() || failed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordedFrame, ReplaySpeed, WsRecordConfig, WsRecorder};
+    use std::time::Duration;
+
+    #[test]
+    fn recorder_writes_frames_on_writer_thread() {
+        let path = std::env::temp_dir().join(format!(
+            "tradernet-ws-recorder-{}.jsonl",
+            std::process::id()
+        ));
+        let mut recorder = WsRecorder::create(&WsRecordConfig::new(&path)).expect("recorder");
+        recorder.record(r#"["q",{"c":"AAPL.US"}]"#).expect("queued");
+        recorder.record(r#"["q",{"c":"TSLA.US"}]"#).expect("queued");
+        recorder.finish().expect("recording finalized");
+
+        let contents = std::fs::read_to_string(&path).expect("recording");
+        std::fs::remove_file(&path).ok();
+        let frames = contents
+            .lines()
+            .map(|line| serde_json::from_str::<RecordedFrame>(line).expect("frame line"))
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].frame, r#"["q",{"c":"TSLA.US"}]"#);
+    }
+
+    #[test]
+    fn record_config_detects_gzip_from_extension() {
+        assert!(WsRecordConfig::new("session.jsonl.gz").gzip);
+        assert!(!WsRecordConfig::new("session.jsonl").gzip);
+        assert!(
+            !WsRecordConfig::new("session.jsonl.gz")
+                .with_gzip(false)
+                .gzip
+        );
+    }
+
+    #[test]
+    fn replay_speed_scales_gaps() {
+        assert_eq!(
+            ReplaySpeed::Original.delay(250),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            ReplaySpeed::Accelerated(10.0).delay(250),
+            Some(Duration::from_millis(25))
+        );
+        assert_eq!(ReplaySpeed::AsFastAsPossible.delay(250), None);
+        assert_eq!(ReplaySpeed::Original.delay(-5), None);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::user_data::Quote;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::fmt;
 use std::time::Duration;
 
 /// Typed WebSocket event returned by [`crate::ws::TradernetWebsocket::quotes`].
@@ -64,6 +65,160 @@ pub enum SubscribeRequest {
     Markets,
 }
 
+impl SubscribeRequest {
+    /// Returns the channel this request subscribes to.
+    pub fn channel(&self) -> WsChannel {
+        match self {
+            SubscribeRequest::Quotes { .. } => WsChannel::Quotes,
+            SubscribeRequest::OrderBook { .. } => WsChannel::OrderBook,
+            SubscribeRequest::Portfolio => WsChannel::Portfolio,
+            SubscribeRequest::Orders => WsChannel::Orders,
+            SubscribeRequest::Markets => WsChannel::Markets,
+        }
+    }
+
+    /// Returns requested symbols (empty for channel-level subscriptions).
+    pub fn symbols(&self) -> &[String] {
+        match self {
+            SubscribeRequest::Quotes { symbols } | SubscribeRequest::OrderBook { symbols } => {
+                symbols
+            }
+            _ => &[],
+        }
+    }
+}
+
+/// WebSocket subscription channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WsChannel {
+    /// Quote updates (`"quotes"` / `"q"`).
+    Quotes,
+    /// Order book updates (`"orderBook"` / `"b"`).
+    OrderBook,
+    /// Portfolio updates.
+    Portfolio,
+    /// Active orders updates.
+    Orders,
+    /// Markets status updates.
+    Markets,
+}
+
+impl WsChannel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "quotes" | "q" => Some(WsChannel::Quotes),
+            "orderbook" | "order_book" | "b" => Some(WsChannel::OrderBook),
+            "portfolio" => Some(WsChannel::Portfolio),
+            "orders" => Some(WsChannel::Orders),
+            "markets" => Some(WsChannel::Markets),
+            _ => None,
+        }
+    }
+}
+
+/// Typed WebSocket `error` payload.
+///
+/// `channel` and `symbols` are taken from the payload when present. A live
+/// session additionally attributes errors to pending or active subscriptions
+/// (see [`crate::ws::TradernetWsSession::subscribe_and_confirm`]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct WsError {
+    /// Error code, if provided.
+    pub code: Option<i64>,
+    /// Human-readable error message, if provided.
+    pub message: Option<String>,
+    /// Subscription channel the error refers to.
+    pub channel: Option<WsChannel>,
+    /// Symbols the error refers to.
+    pub symbols: Vec<String>,
+    /// Raw error payload.
+    pub raw: Value,
+}
+
+impl WsError {
+    /// Builds a typed error from a raw `error` payload.
+    ///
+    /// Parsing is lossy: strings are treated as messages and unknown shapes are
+    /// kept only in [`WsError::raw`].
+    pub fn from_payload(raw: Value) -> Self {
+        let mut error = WsError::default();
+        match &raw {
+            Value::String(message) => error.message = non_empty(message),
+            Value::Object(map) => {
+                error.code = ["code", "errCode", "error_code"]
+                    .iter()
+                    .find_map(|key| map.get(*key).and_then(value_as_i64));
+                error.message = ["message", "msg", "errMsg", "error", "text"]
+                    .iter()
+                    .find_map(|key| map.get(*key).and_then(value_as_string));
+                error.channel = ["channel", "cmd", "event", "type"]
+                    .iter()
+                    .find_map(|key| map.get(*key)?.as_str().and_then(WsChannel::from_name));
+                error.symbols = ["symbols", "tickers", "ticker", "symbol", "c", "i"]
+                    .iter()
+                    .find_map(|key| map.get(*key).map(value_as_symbols))
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+        error.raw = raw;
+        error
+    }
+}
+
+impl fmt::Display for WsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message.as_deref().unwrap_or("unknown websocket error"))?;
+        if let Some(code) = self.code {
+            write!(f, " (code {code})")?;
+        }
+        if let Some(channel) = self.channel {
+            write!(f, " [{channel:?}")?;
+            if !self.symbols.is_empty() {
+                write!(f, ": {}", self.symbols.join(", "))?;
+            }
+            f.write_str("]")?;
+        } else if !self.symbols.is_empty() {
+            write!(f, " [{}]", self.symbols.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+fn value_as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(value) => value
+            .as_i64()
+            .or_else(|| value.as_f64().map(|value| value.trunc() as i64)),
+        Value::String(value) => value.trim().parse::<i64>().ok(),
+        _ => None,
+    }
+}
+
+fn value_as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => non_empty(value),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn value_as_symbols(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => value.split(',').filter_map(non_empty).collect(),
+        Value::Array(values) => values
+            .iter()
+            .filter_map(|value| value.as_str().and_then(non_empty))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 /// Unified unsubscribe request for [`crate::ws::TradernetWsSession`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UnsubscribeRequest {
@@ -80,11 +235,17 @@ pub enum UnsubscribeRequest {
 }
 
 /// Unified event stream item returned by [`crate::ws::TradernetWsSession::events`].
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
+#[non_exhaustive]
 pub enum WsEvent {
     /// Quote update event (`"q"`).
     Quote(Quote),
+    /// Full quote snapshot with the preceding [`WsEvent::Quote`] delta merged in.
+    ///
+    /// Emitted only when enabled via
+    /// [`crate::ws::TradernetWebsocket::with_quote_snapshots`].
+    QuoteSnapshot(Quote),
     /// Order book update event (`"b"`).
     MarketDepth(MarketDepthUpdate),
     /// Portfolio update event (`"portfolio"`).
@@ -93,14 +254,18 @@ pub enum WsEvent {
     Orders(Vec<OrderDataRow>),
     /// Market statuses update event (`"markets"`).
     Markets(MarketsUpdate),
-    /// Raw error payload from WebSocket (`"error"`).
-    Error(Value),
+    /// Typed error payload from WebSocket (`"error"`).
+    Error(WsError),
     /// Session connected.
     Connected,
     /// Session is reconnecting.
     Reconnecting,
     /// Session closed.
     Closed,
+    /// Number of market data events (quotes, snapshots, order books and market
+    /// statuses) dropped because the consumer fell behind. Emitted before the next
+    /// delivered event; order, portfolio, error and session events are never dropped.
+    Dropped(u64),
 }
 
 /// Reconnect strategy for [`crate::ws::TradernetWsSession`].
@@ -821,7 +986,10 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{MarketDepthSide, MarketDepthUpdate, MarketsUpdate, OrderDataRow, PortfolioUpdate};
+    use super::{
+        MarketDepthSide, MarketDepthUpdate, MarketsUpdate, OrderDataRow, PortfolioUpdate,
+        WsChannel, WsError,
+    };
     use serde_json::json;
 
     #[test]
@@ -1039,4 +1207,33 @@ mod tests {
         assert_eq!(update.m[1].n, "");
         assert_eq!(update.m[1].n2, "true");
     }
+
+    #[test]
+    fn ws_error_reads_code_message_channel_and_symbols() {
+        let error = WsError::from_payload(json!({
+            "code": "12",
+            "msg": "Unknown ticker",
+            "cmd": "quotes",
+            "tickers": ["FOO.US", ""]
+        }));
+
+        assert_eq!(error.code, Some(12));
+        assert_eq!(error.message.as_deref(), Some("Unknown ticker"));
+        assert_eq!(error.channel, Some(WsChannel::Quotes));
+        assert_eq!(error.symbols, vec!["FOO.US".to_string()]);
+        assert_eq!(
+            error.to_string(),
+            "Unknown ticker (code 12) [Quotes: FOO.US]"
+        );
+    }
+
+    #[test]
+    fn ws_error_accepts_plain_string_payload() {
+        let error = WsError::from_payload(json!("Not authorized"));
+
+        assert_eq!(error.code, None);
+        assert_eq!(error.message.as_deref(), Some("Not authorized"));
+        assert!(error.symbols.is_empty());
+        assert_eq!(error.raw, json!("Not authorized"));
+    }
 }
@@ -1,5 +1,8 @@
-use chrono::{NaiveDate, NaiveTime};
-use tradernet_sdk_rs::{AsyncTradernet, TradernetError};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use futures_util::StreamExt;
+use tradernet_sdk_rs::{
+    AsyncTradernet, CpsHistoryFilters, Timeframe, TradernetError, TradesHistoryFilters,
+};
 
 #[tokio::test]
 async fn async_client_reports_missing_keys() {
@@ -30,6 +33,27 @@ async fn async_client_get_candles_series_reports_missing_keys() {
     assert!(matches!(error, TradernetError::MissingKeypair));
 }
 
+#[tokio::test]
+async fn async_client_get_candles_multi_fails_on_request_errors() {
+    let client = AsyncTradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let end = NaiveDate::from_ymd_opt(2026, 1, 2)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let symbols = (0..12)
+        .map(|index| format!("S{index}.US"))
+        .collect::<Vec<_>>();
+
+    let error = client
+        .get_candles_multi(&symbols, start, end, Timeframe::Day1)
+        .await
+        .expect_err("errors not caused by a symbol must fail the call");
+
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
 #[tokio::test]
 async fn async_client_get_trades_history_with_reception_validates_input() {
     let client = AsyncTradernet::new(None, None).expect("client should be created");
@@ -57,3 +81,73 @@ async fn async_client_get_trades_history_typed_validates_input() {
 
     assert!(matches!(error, TradernetError::InvalidInput(_)));
 }
+
+#[tokio::test]
+async fn async_client_get_candles_range_reports_missing_keys() {
+    let client = AsyncTradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let end = NaiveDate::from_ymd_opt(2026, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+
+    let error = client
+        .get_candles_range("AAPL.US", start, end, Timeframe::Minute5)
+        .await
+        .expect_err("missing keys should error");
+
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[tokio::test]
+async fn async_client_trades_history_iter_stops_after_error() {
+    let client = AsyncTradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date");
+    let end = NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date");
+
+    let mut trades = client
+        .trades_history_iter(start, end, TradesHistoryFilters::default())
+        .expect("valid filters");
+    assert!(matches!(
+        trades.next().await,
+        Some(Err(TradernetError::MissingKeypair))
+    ));
+    assert!(trades.next().await.is_none());
+}
+
+#[tokio::test]
+async fn async_client_get_requests_history_all_validates_page_size() {
+    let client = AsyncTradernet::new(None, None).expect("client should be created");
+    let start = Local
+        .with_ymd_and_hms(2026, 1, 1, 0, 0, 0)
+        .single()
+        .expect("valid local time");
+    let end = Local
+        .with_ymd_and_hms(2026, 12, 31, 0, 0, 0)
+        .single()
+        .expect("valid local time");
+
+    let error = client
+        .get_requests_history_all(start, end, CpsHistoryFilters::default().with_page_size(0))
+        .await
+        .expect_err("zero page size must be rejected");
+    assert!(matches!(error, TradernetError::InvalidInput(_)));
+
+    let error = client
+        .get_requests_history_all(start, end, CpsHistoryFilters::default())
+        .await
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[tokio::test]
+async fn async_client_security_info_typed_reports_missing_keys() {
+    let client = AsyncTradernet::new(None, None).expect("client should be created");
+
+    let error = client
+        .security_info_typed("AAPL.US", true)
+        .await
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
@@ -1,5 +1,9 @@
 use chrono::{NaiveDate, NaiveTime};
-use tradernet_sdk_rs::{Tradernet, TradernetError};
+use serde_json::json;
+use tradernet_sdk_rs::{
+    AdjustmentMode, CandleCache, CorporateAction, Timeframe, Tradernet, TradernetError,
+    TradesHistoryFilters,
+};
 
 #[test]
 fn client_get_candles_series_reports_missing_keys() {
@@ -18,6 +22,98 @@ fn client_get_candles_series_reports_missing_keys() {
     assert!(matches!(error, TradernetError::MissingKeypair));
 }
 
+#[test]
+fn client_get_candles_range_validates_range_and_reports_missing_keys() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let end = NaiveDate::from_ymd_opt(2026, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+
+    let error = client
+        .get_candles_range("AAPL.US", end, start, Timeframe::Minute1)
+        .expect_err("reversed range must be rejected");
+    assert!(matches!(error, TradernetError::InvalidInput(_)));
+
+    let error = client
+        .get_candles_range("AAPL.US", start, end, Timeframe::Minute1)
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[test]
+fn client_get_candles_multi_fails_on_request_errors() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let end = NaiveDate::from_ymd_opt(2026, 1, 2)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+
+    let error = client
+        .get_candles_multi(Vec::<&str>::new(), start, end, Timeframe::Day1)
+        .expect_err("empty symbol list must be rejected");
+    assert!(matches!(error, TradernetError::InvalidInput(_)));
+
+    let error = client
+        .get_candles_multi(["AAPL.US", "TSLA.US"], start, end, Timeframe::Day1)
+        .expect_err("errors not caused by a symbol must fail the call");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[test]
+fn client_cached_candles_adjust_for_past_split() {
+    // Midnight Moscow time on 2024-06-07, 2024-06-10 and 2024-06-11.
+    let (friday, monday, tuesday) = (1_717_707_600, 1_717_966_800, 1_718_053_200);
+    let dir =
+        std::env::temp_dir().join(format!("tradernet-adjusted-candles-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("cache dir should be created");
+    let bar = |ts: i64, close: f64, volume: i64| json!({"ts": ts, "open": close, "high": close, "low": close, "close": close, "volume": volume});
+    let entry = json!({
+        "symbol": "NVDA.US",
+        "timeframe": 86_400,
+        "updated_at": tuesday,
+        "covered": [[friday, tuesday]],
+        "items": [bar(friday, 1200.0, 100), bar(monday, 120.0, 1000), bar(tuesday, 121.0, 900)]
+    });
+    std::fs::write(dir.join("NVDA.US_86400.json"), entry.to_string())
+        .expect("cache entry should be written");
+
+    let client = Tradernet::new(None, None)
+        .expect("client should be created")
+        .with_candle_cache(CandleCache::new(&dir));
+    let start = NaiveDate::from_ymd_opt(2024, 6, 7)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let end = NaiveDate::from_ymd_opt(2024, 6, 11)
+        .expect("valid date")
+        .and_time(NaiveTime::from_hms_opt(0, 0, 0).expect("valid time"));
+    let split = CorporateAction::split(
+        "NVDA.US",
+        NaiveDate::from_ymd_opt(2024, 6, 10).expect("valid date"),
+        10.0,
+    );
+
+    let adjusted = client
+        .get_candles_range("NVDA.US", start, end, Timeframe::Day1)
+        .expect("cached history should be served without requests")
+        .adjust(
+            Timeframe::Day1,
+            &[split],
+            AdjustmentMode::SplitsAndDividends,
+        );
+    std::fs::remove_dir_all(&dir).expect("cache dir should be removed");
+
+    let items = &adjusted.candles.items;
+    assert_eq!(adjusted.factors.len(), 1);
+    assert_eq!((items[0].close, items[0].volume), (120.0, 1000));
+    assert_eq!((items[1].close, items[1].volume), (120.0, 1000));
+    assert_eq!((items[2].close, items[2].volume), (121.0, 900));
+}
+
 #[test]
 fn client_get_trades_history_with_reception_validates_input() {
     let client = Tradernet::new(None, None).expect("client should be created");
@@ -43,3 +139,78 @@ fn client_get_trades_history_typed_validates_input() {
 
     assert!(matches!(error, TradernetError::InvalidInput(_)));
 }
+
+#[test]
+fn client_trades_history_iter_stops_after_error() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date");
+    let end = NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date");
+
+    assert!(matches!(
+        client.trades_history_iter(end, start, TradesHistoryFilters::default()),
+        Err(TradernetError::InvalidInput(_))
+    ));
+
+    let mut trades = client
+        .trades_history_iter(start, end, TradesHistoryFilters::default())
+        .expect("valid filters");
+    assert!(matches!(
+        trades.next(),
+        Some(Err(TradernetError::MissingKeypair))
+    ));
+    assert!(trades.next().is_none());
+}
+
+#[test]
+fn client_get_broker_report_typed_reports_missing_keys() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1).expect("valid date");
+    let end = NaiveDate::from_ymd_opt(2026, 12, 31).expect("valid date");
+    let period = NaiveTime::from_hms_opt(23, 59, 59).expect("valid time");
+
+    let error = client
+        .get_broker_report_typed(start, end, period, None)
+        .expect_err("missing keys should error");
+
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[test]
+fn client_get_order_files_typed_requires_an_id() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+
+    let error = client
+        .get_order_files_typed(None, None)
+        .expect_err("an order id is required");
+    assert!(matches!(error, TradernetError::InvalidInput(_)));
+
+    let error = client
+        .get_order_files_typed(Some(1), None)
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[test]
+fn client_security_lookups_typed_report_missing_keys() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+
+    let error = client
+        .security_info_typed("AAPL.US", true)
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+
+    let error = client
+        .symbol_typed("AAPL.US", "en")
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
+
+#[test]
+fn client_get_option_chain_reports_missing_keys() {
+    let client = Tradernet::new(None, None).expect("client should be created");
+
+    let error = client
+        .get_option_chain("AAPL.US", "FIX")
+        .expect_err("missing keys should error");
+    assert!(matches!(error, TradernetError::MissingKeypair));
+}
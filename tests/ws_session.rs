@@ -6,8 +6,9 @@ use tokio::runtime::Builder;
 use tokio::time::{Duration, timeout};
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 use tradernet_sdk_rs::{
-    Core, QuoteEvent, SubscribeRequest, TradernetWebsocket, UnsubscribeRequest, WsEvent,
-    WsReconnectConfig,
+    BlockingWsSession, Core, QuoteEvent, ReplaySession, ReplaySpeed, SubscribeRequest,
+    TradernetError, TradernetWebsocket, UnsubscribeRequest, WsChannel, WsEvent, WsReconnectConfig,
+    WsRecordConfig,
 };
 
 fn reconnect_config() -> WsReconnectConfig {
@@ -415,3 +416,539 @@ fn legacy_quotes_method_works_via_session_wrapper() {
         server.await.expect("server join");
     });
 }
+
+#[test]
+fn ws_session_emits_merged_quote_snapshots_when_enabled() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core)
+        .with_websocket_url(format!("ws://{}", addr))
+        .with_quote_snapshots(true);
+
+    runtime().block_on(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accept client");
+            let mut socket = accept_async(stream).await.expect("ws handshake");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command[0], "quotes");
+
+            for event in [
+                serde_json::json!(["q", {"c": "AAPL.US", "bap": 191.3, "ltp": 191.2}]),
+                serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.25}]),
+            ] {
+                socket
+                    .send(Message::Text(event.to_string()))
+                    .await
+                    .expect("send quote");
+            }
+            socket.close(None).await.expect("close socket");
+        });
+
+        let session = ws
+            .connect_with_config(reconnect_config())
+            .await
+            .expect("connect");
+        let seed = serde_json::from_value(serde_json::json!({"c": "AAPL.US", "bbp": 191.1}))
+            .expect("seed quote");
+        assert_eq!(session.seed_quotes([seed]).expect("seed quotes"), 1);
+        session
+            .subscribe(SubscribeRequest::Quotes {
+                symbols: vec!["AAPL.US".to_string()],
+            })
+            .await
+            .expect("subscribe quotes");
+
+        let mut events = session.events();
+        let mut deltas = Vec::new();
+        let mut snapshots = Vec::new();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while tokio::time::Instant::now() < deadline && snapshots.len() < 2 {
+            let event = timeout(Duration::from_millis(300), events.next()).await;
+            let Ok(Some(Ok(event))) = event else {
+                continue;
+            };
+            match event {
+                WsEvent::Quote(quote) => deltas.push(quote),
+                WsEvent::QuoteSnapshot(quote) => snapshots.push(quote),
+                _ => {}
+            }
+        }
+
+        session.close().await.expect("close session");
+        server.await.expect("server join");
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[1].bap, None, "deltas are passed through as-is");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].ltp, Some(191.25));
+        assert_eq!(snapshots[1].bap, Some(191.3));
+        assert_eq!(snapshots[1].bbp, Some(191.1));
+        assert_eq!(
+            session
+                .quote_snapshot("AAPL.US")
+                .and_then(|quote| quote.ltp),
+            Some(191.25)
+        );
+    });
+}
+
+#[test]
+fn ws_session_records_frames_and_replays_them() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let path = std::env::temp_dir().join(format!(
+        "tradernet-ws-record-{}-{}.jsonl.gz",
+        std::process::id(),
+        addr.port()
+    ));
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core)
+        .with_websocket_url(format!("ws://{}", addr))
+        .with_recording(WsRecordConfig::new(&path));
+
+    let live = runtime().block_on(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accept client");
+            let mut socket = accept_async(stream).await.expect("ws handshake");
+
+            let _ = next_text(&mut socket).await;
+            for event in [
+                serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.2}]),
+                serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.25}]),
+            ] {
+                socket
+                    .send(Message::Text(event.to_string()))
+                    .await
+                    .expect("send quote");
+            }
+            let _ = timeout(Duration::from_secs(3), socket.next()).await;
+        });
+
+        let session = ws
+            .connect_with_config(reconnect_config())
+            .await
+            .expect("connect");
+        session
+            .subscribe(SubscribeRequest::Quotes {
+                symbols: vec!["AAPL.US".to_string()],
+            })
+            .await
+            .expect("subscribe quotes");
+
+        let mut events = session.events();
+        let mut live = Vec::new();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while tokio::time::Instant::now() < deadline && live.len() < 2 {
+            let event = timeout(Duration::from_millis(300), events.next()).await;
+            if let Ok(Some(Ok(WsEvent::Quote(quote)))) = event {
+                live.push(quote.ltp);
+            }
+        }
+
+        session.close().await.expect("close session");
+        server.await.expect("server join");
+        live
+    });
+    assert_eq!(live, vec![Some(191.2), Some(191.25)]);
+
+    let replayed = runtime().block_on(async {
+        let replay = ReplaySession::open(&path)
+            .expect("open recording")
+            .with_speed(ReplaySpeed::AsFastAsPossible);
+        replay.events().collect::<Vec<_>>().await
+    });
+    let _ = std::fs::remove_file(&path);
+
+    let quotes = replayed
+        .iter()
+        .filter_map(|event| match event {
+            Ok(WsEvent::Quote(quote)) => Some(quote.ltp),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(quotes, live);
+    assert!(matches!(replayed.last(), Some(Ok(WsEvent::Closed))));
+}
+
+#[test]
+fn replay_session_plays_fixture_with_snapshots() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/ws_session.jsonl"
+    );
+
+    let events = runtime().block_on(async {
+        let replay = ReplaySession::open(path)
+            .expect("open fixture")
+            .with_speed(ReplaySpeed::Accelerated(1000.0))
+            .with_quote_snapshots(true);
+        let events = replay.events().collect::<Vec<_>>().await;
+
+        let mut again = replay.events();
+        assert!(matches!(again.next().await, Some(Err(_))));
+        events
+    });
+
+    let events = events
+        .into_iter()
+        .map(|event| event.expect("fixture event must parse"))
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 7);
+    assert!(matches!(events[2], WsEvent::MarketDepth(_)));
+    let WsEvent::QuoteSnapshot(snapshot) = &events[4] else {
+        panic!("expected quote snapshot");
+    };
+    assert_eq!(snapshot.ltp, Some(191.25));
+    assert_eq!(snapshot.bbp, Some(191.1));
+    assert!(matches!(events[5], WsEvent::Error(_)));
+    assert!(matches!(events[6], WsEvent::Closed));
+}
+
+#[test]
+fn blocking_ws_session_receives_events_and_replays_after_reconnect() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let server_runtime = runtime();
+    let server = server_runtime.spawn(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let (stream, _) = listener.accept().await.expect("accept first client");
+        let mut first = accept_async(stream).await.expect("first ws handshake");
+        let text = next_text(&mut first).await;
+        let command: Value = serde_json::from_str(&text).expect("valid command json");
+        assert_eq!(command[0], "quotes");
+        first.close(None).await.expect("close first socket");
+
+        let (stream, _) = listener.accept().await.expect("accept second client");
+        let mut second = accept_async(stream).await.expect("second ws handshake");
+        let text = next_text(&mut second).await;
+        let command: Value = serde_json::from_str(&text).expect("valid replay json");
+        assert_eq!(command, serde_json::json!(["quotes", ["AAPL.US"]]));
+
+        second
+            .send(Message::Text(
+                serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.2}]).to_string(),
+            ))
+            .await
+            .expect("send quote");
+        let _ = timeout(Duration::from_secs(3), second.next()).await;
+    });
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core).with_websocket_url(format!("ws://{}", addr));
+    let session = BlockingWsSession::connect_with_config(&ws, reconnect_config()).expect("connect");
+    session
+        .subscribe(SubscribeRequest::Quotes {
+            symbols: vec!["AAPL.US".to_string()],
+        })
+        .expect("subscribe quotes");
+
+    let mut reconnects = 0;
+    let mut quote = None;
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while std::time::Instant::now() < deadline && quote.is_none() {
+        match session.recv_timeout(Duration::from_millis(300)) {
+            Ok(Some(WsEvent::Reconnecting)) => reconnects += 1,
+            Ok(Some(WsEvent::Quote(update))) => quote = update.ltp,
+            _ => {}
+        }
+    }
+    assert!(reconnects >= 1, "expected reconnect event");
+    assert_eq!(quote, Some(191.2));
+    assert_eq!(
+        session
+            .quote_snapshot("AAPL.US")
+            .and_then(|quote| quote.ltp),
+        Some(191.2)
+    );
+
+    session.close().expect("close session");
+    let remaining = session.events().collect::<Vec<_>>();
+    assert!(matches!(remaining.last(), Some(Ok(WsEvent::Closed))));
+    assert!(session.recv_timeout(Duration::from_millis(10)).is_err());
+    assert!(session.subscribe(SubscribeRequest::Markets).is_err());
+
+    server_runtime.block_on(server).expect("server join");
+}
+
+#[test]
+fn ws_session_subscribe_and_confirm_resolves_on_data_error_and_timeout() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core).with_websocket_url(format!("ws://{}", addr));
+
+    runtime().block_on(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accept client");
+            let mut socket = accept_async(stream).await.expect("ws handshake");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["quotes", ["AAPL.US"]]));
+            socket
+                .send(Message::Text(
+                    serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.2}]).to_string(),
+                ))
+                .await
+                .expect("send quote");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["quotes", ["FOO.US"]]));
+            socket
+                .send(Message::Text(
+                    serde_json::json!(["error", {"code": 7, "message": "Unknown ticker FOO.US"}])
+                        .to_string(),
+                ))
+                .await
+                .expect("send error");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["markets"]));
+            let _ = timeout(Duration::from_secs(3), socket.next()).await;
+        });
+
+        let session = ws
+            .connect_with_config(reconnect_config())
+            .await
+            .expect("connect");
+        let confirm = Duration::from_secs(3);
+
+        session
+            .subscribe_and_confirm(
+                SubscribeRequest::Quotes {
+                    symbols: vec!["AAPL.US".to_string()],
+                },
+                confirm,
+            )
+            .await
+            .expect("quotes must be confirmed");
+
+        let error = session
+            .subscribe_and_confirm(
+                SubscribeRequest::Quotes {
+                    symbols: vec!["FOO.US".to_string()],
+                },
+                confirm,
+            )
+            .await
+            .expect_err("unknown ticker must be rejected");
+        let TradernetError::WsSubscription(ws_error) = error else {
+            panic!("expected subscription error, got {error:?}");
+        };
+        assert_eq!(ws_error.code, Some(7));
+        assert_eq!(ws_error.channel, Some(WsChannel::Quotes));
+        assert_eq!(ws_error.symbols, vec!["FOO.US".to_string()]);
+
+        let error = session
+            .subscribe_and_confirm(SubscribeRequest::Markets, Duration::from_millis(200))
+            .await
+            .expect_err("markets must time out");
+        assert!(matches!(error, TradernetError::Timeout(_)));
+
+        let mut events = session.events();
+        let mut typed_error = None;
+        while let Ok(Some(Ok(event))) = timeout(Duration::from_millis(300), events.next()).await {
+            if let WsEvent::Error(error) = event {
+                typed_error = Some(error);
+            }
+        }
+        let typed_error = typed_error.expect("error event must be emitted");
+        assert_eq!(
+            typed_error.message.as_deref(),
+            Some("Unknown ticker FOO.US")
+        );
+
+        session.close().await.expect("close session");
+        server.await.expect("server join");
+    });
+}
+
+#[test]
+fn ws_session_typed_streams_subscribe_on_poll_and_filter_events() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core).with_websocket_url(format!("ws://{}", addr));
+
+    runtime().block_on(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accept client");
+            let mut socket = accept_async(stream).await.expect("ws handshake");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["quotes", ["AAPL.US"]]));
+            for event in [
+                serde_json::json!(["q", {"c": "TSLA.US", "ltp": 250.0}]),
+                serde_json::json!(["q", {"c": "AAPL.US", "ltp": 191.2}]),
+            ] {
+                socket
+                    .send(Message::Text(event.to_string()))
+                    .await
+                    .expect("send quote");
+            }
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["orders"]));
+            let orders = serde_json::json!([
+                "orders",
+                [
+                    {"order_id": 1, "instr": "AAPL.US"},
+                    {"order_id": 2, "instr": "TSLA.US"}
+                ]
+            ]);
+            socket
+                .send(Message::Text(orders.to_string()))
+                .await
+                .expect("send orders");
+            let _ = timeout(Duration::from_secs(3), socket.next()).await;
+        });
+
+        let session = ws
+            .connect_with_config(reconnect_config())
+            .await
+            .expect("connect");
+
+        let mut first = session.quote_stream("AAPL.US");
+        let mut second = session.quote_stream("AAPL.US");
+        let (first_quote, second_quote) = timeout(Duration::from_secs(3), async {
+            tokio::join!(first.next(), second.next())
+        })
+        .await
+        .expect("quotes must arrive");
+        assert_eq!(first_quote.and_then(|quote| quote.ltp), Some(191.2));
+        assert_eq!(second_quote.and_then(|quote| quote.ltp), Some(191.2));
+
+        let mut orders = session.order_stream();
+        let first_order = timeout(Duration::from_secs(3), orders.next())
+            .await
+            .expect("order must arrive")
+            .expect("order row");
+        let second_order = timeout(Duration::from_secs(3), orders.next())
+            .await
+            .expect("order must arrive")
+            .expect("order row");
+        assert_eq!(first_order.order_id, 1);
+        assert_eq!(second_order.instr, "TSLA.US");
+
+        drop(first);
+        drop(second);
+
+        session.close().await.expect("close session");
+        assert!(
+            timeout(Duration::from_secs(1), orders.next())
+                .await
+                .expect("stream must end after close")
+                .is_none()
+        );
+        server.await.expect("server join");
+    });
+}
+
+#[test]
+fn ws_session_sends_subscribe_queued_while_connecting_once() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    std_listener
+        .set_nonblocking(true)
+        .expect("set nonblocking listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let core = Core::new(None, None).expect("core");
+    let ws = TradernetWebsocket::from_core(&core).with_websocket_url(format!("ws://{}", addr));
+
+    runtime().block_on(async move {
+        let listener = TcpListener::from_std(std_listener).expect("tokio listener");
+
+        let server = tokio::spawn(async move {
+            // Delay the handshake so the subscribe below is queued while connecting.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let (stream, _) = listener.accept().await.expect("accept client");
+            let mut socket = accept_async(stream).await.expect("ws handshake");
+
+            let text = next_text(&mut socket).await;
+            let command: Value = serde_json::from_str(&text).expect("valid command json");
+            assert_eq!(command, serde_json::json!(["quotes", ["AAPL.US"]]));
+
+            let mut duplicates = 0;
+            while let Ok(Some(Ok(message))) =
+                timeout(Duration::from_millis(500), socket.next()).await
+            {
+                if matches!(message, Message::Text(_)) {
+                    duplicates += 1;
+                }
+            }
+            duplicates
+        });
+
+        let session = ws
+            .connect_with_config(reconnect_config())
+            .await
+            .expect("connect");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        session
+            .subscribe(SubscribeRequest::Quotes {
+                symbols: vec!["AAPL.US".to_string()],
+            })
+            .await
+            .expect("subscribe quotes");
+
+        let duplicates = server.await.expect("server join");
+        assert_eq!(duplicates, 0, "subscribe frame must be sent exactly once");
+        session.close().await.expect("close session");
+    });
+}
+
+#[tokio::test]
+async fn blocking_ws_session_can_be_dropped_inside_runtime() {
+    let std_listener = StdTcpListener::bind("127.0.0.1:0").expect("bind mock ws listener");
+    let addr = std_listener.local_addr().expect("local addr");
+
+    let session = std::thread::spawn(move || {
+        let core = Core::new(None, None).expect("core");
+        let ws = TradernetWebsocket::from_core(&core).with_websocket_url(format!("ws://{}", addr));
+        BlockingWsSession::connect_with_config(&ws, reconnect_config())
+    })
+    .join()
+    .expect("connect thread")
+    .expect("blocking connect");
+
+    drop(session);
+    drop(std_listener);
+}